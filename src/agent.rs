@@ -4,7 +4,7 @@ use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::providers::Provider;
+use crate::providers::{CancelToken, CompletionParams, Provider, ProviderError};
 
 #[derive(Debug, Clone)]
 pub struct ExecutionContract {
@@ -43,6 +43,7 @@ pub enum FailureCode {
     Unauthorized,
     ProviderUnavailable,
     CliMissing,
+    Cancelled,
     Unknown,
 }
 
@@ -53,6 +54,7 @@ impl std::fmt::Display for FailureCode {
             FailureCode::Unauthorized => write!(f, "unauthorized"),
             FailureCode::ProviderUnavailable => write!(f, "provider_unavailable"),
             FailureCode::CliMissing => write!(f, "cli_missing"),
+            FailureCode::Cancelled => write!(f, "cancelled"),
             FailureCode::Unknown => write!(f, "unknown"),
         }
     }
@@ -78,15 +80,49 @@ pub async fn execute_with_contract(
     model: Option<&str>,
     working_dir: Option<&Path>,
     contract: &ExecutionContract,
+) -> Result<String, ExecutionError> {
+    execute_with_contract_cancellable(provider, prompt, model, working_dir, contract, None).await
+}
+
+/// Like [`execute_with_contract`], but stops as soon as `cancel` fires.
+///
+/// CLI providers kill their child process on cancellation; any partial output
+/// is carried in the returned `cancelled` error message.
+pub async fn execute_with_contract_cancellable(
+    provider: Arc<dyn Provider>,
+    prompt: &str,
+    model: Option<&str>,
+    working_dir: Option<&Path>,
+    contract: &ExecutionContract,
+    cancel: Option<CancelToken>,
 ) -> Result<String, ExecutionError> {
     let attempts = contract.retries + 1;
     let timeout = Duration::from_secs(contract.timeout_seconds);
     let mut last_error: Option<ExecutionError> = None;
+    let params = CompletionParams {
+        cancel: cancel.clone(),
+        ..CompletionParams::default()
+    };
 
     for attempt in 1..=attempts {
-        let result = tokio::time::timeout(timeout, provider.complete(prompt, model, working_dir)).await;
+        if cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return Err(cancelled_error(String::new()));
+        }
+        let call = provider.complete_with_params(prompt, model, working_dir, &params);
+        let result = match cancel.as_ref() {
+            // Providers without process handles are stopped by dropping the call.
+            Some(token) => tokio::select! {
+                r = tokio::time::timeout(timeout, call) => r,
+                _ = token.cancelled() => Ok(Err(ProviderError::Cancelled(String::new()))),
+            },
+            None => tokio::time::timeout(timeout, call).await,
+        };
         match result {
             Ok(Ok(text)) => return Ok(text),
+            Ok(Err(ProviderError::Cancelled(partial))) => {
+                tracing::warn!("Execution attempt {}/{} cancelled", attempt, attempts);
+                return Err(cancelled_error(partial));
+            }
             Ok(Err(e)) => {
                 let err = classify_error(&e.to_string());
                 last_error = Some(err.clone());
@@ -121,6 +157,18 @@ pub async fn execute_with_contract(
     }))
 }
 
+fn cancelled_error(partial: String) -> ExecutionError {
+    let partial = partial.trim();
+    ExecutionError {
+        code: FailureCode::Cancelled,
+        message: if partial.is_empty() {
+            "execution cancelled".to_string()
+        } else {
+            format!("execution cancelled; partial output:\n{}", partial)
+        },
+    }
+}
+
 fn classify_error(message: &str) -> ExecutionError {
     let m = message.to_lowercase();
     let code = if m.contains("unauthorized")
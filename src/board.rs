@@ -2,9 +2,10 @@
 #![allow(dead_code)]
 
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use crate::config::{AgentConfig, Settings, TeamConfig};
-use crate::core::routing::{extract_mentions, find_team_for_agent, is_teammate};
+use crate::core::routing::{extract_mentions, is_teammate, resolve_team_for_agent};
 use crate::error::{Error, Result};
 use crate::heartbeat::tasks::TaskSpawner;
 use crate::memory::{Memory, MemoryScope};
@@ -149,86 +150,517 @@ pub fn install_default_pack(settings: &mut Settings, workspace_root: &Path) -> R
     Ok(())
 }
 
+/// A finished board discussion: every member's full input and the CEO's
+/// decision, with when and for how long the board met.
+#[derive(Debug, Clone)]
+pub struct BoardDiscussion {
+    pub team_id: String,
+    pub topic: String,
+    pub ceo: String,
+    /// `(member, input)` in team order.
+    pub member_inputs: Vec<(String, String)>,
+    /// Members whose call ran past the per-member timeout or the deadline.
+    pub timed_out: Vec<String>,
+    /// Members not asked because the overall deadline had passed.
+    pub skipped: Vec<String>,
+    pub ceo_decision: String,
+    /// Id and topic of the prior decision this discussion continued from.
+    pub continued_from: Option<(String, String)>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub duration: Duration,
+    pub dry_run: bool,
+}
+
+impl BoardDiscussion {
+    /// Plain-text transcript, as printed by `board discuss`.
+    pub fn render(&self) -> String {
+        let continued = self
+            .continued_from
+            .as_ref()
+            .map(|(id, topic)| format!("Continuing from decision {} ({})\n\n", id, topic))
+            .unwrap_or_default();
+        let missing = self
+            .missing_members()
+            .map(|m| format!("\n\nMembers without input: {}\n", m))
+            .unwrap_or_default();
+        format!(
+            "Board @{} discussion on: {}\n\n{}{}{}\nCEO (@{}) decision:\n{}",
+            self.team_id,
+            self.topic,
+            continued,
+            render_member_inputs(&self.member_inputs).trim(),
+            missing,
+            self.ceo,
+            self.ceo_decision
+        )
+    }
+
+    /// `@a (timed out), @b (skipped)`, or `None` when everyone answered.
+    pub fn missing_members(&self) -> Option<String> {
+        missing_members(&self.timed_out, &self.skipped)
+    }
+
+    /// Markdown transcript: a metadata header, one section per member and
+    /// the CEO decision.
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# Board discussion: {}\n\n", self.topic);
+        md.push_str(&format!("- Team: @{}\n", self.team_id));
+        md.push_str(&format!("- Topic: {}\n", self.topic));
+        md.push_str(&format!("- Timestamp: {}\n", self.started_at.to_rfc3339()));
+        md.push_str(&format!("- Duration: {:.1}s\n", self.duration.as_secs_f64()));
+        if let Some((id, topic)) = &self.continued_from {
+            md.push_str(&format!("- Continued from: {} ({})\n", id, topic));
+        }
+        if let Some(missing) = self.missing_members() {
+            md.push_str(&format!("- Members without input: {}\n", missing));
+        }
+        if self.dry_run {
+            md.push_str("- Dry run: yes\n");
+        }
+        for (member, input) in &self.member_inputs {
+            md.push_str(&format!("\n## @{}\n\n{}\n", member, input));
+        }
+        md.push_str(&format!("\n## CEO decision (@{})\n\n{}\n", self.ceo, self.ceo_decision));
+        md
+    }
+}
+
+fn missing_members(timed_out: &[String], skipped: &[String]) -> Option<String> {
+    let missing: Vec<String> = timed_out
+        .iter()
+        .map(|m| format!("@{} (timed out)", m))
+        .chain(skipped.iter().map(|m| format!("@{} (skipped)", m)))
+        .collect();
+    (!missing.is_empty()).then(|| missing.join(", "))
+}
+
+/// How a board member's turn in a discussion ended.
+enum MemberReply {
+    Answered,
+    TimedOut,
+    Skipped,
+}
+
+/// `fut`'s output, or `None` if `limit` passes first.
+async fn within<T>(limit: Option<Duration>, fut: impl std::future::Future<Output = T>) -> Option<T> {
+    match limit {
+        Some(limit) => tokio::time::timeout(limit, fut).await.ok(),
+        None => Some(fut.await),
+    }
+}
+
+/// Time limit for one call: the smaller of `timeout` and what is left
+/// before `deadline`. `None` once the deadline has passed.
+fn call_limit(timeout: Option<Duration>, deadline: Option<Instant>) -> Option<Option<Duration>> {
+    let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+    if remaining.is_some_and(|r| r.is_zero()) {
+        return None;
+    }
+    Some(match (timeout, remaining) {
+        (Some(t), Some(r)) => Some(t.min(r)),
+        (t, r) => t.or(r),
+    })
+}
+
+fn render_member_inputs(member_inputs: &[(String, String)]) -> String {
+    let mut synthesis = String::new();
+    for (member, input) in member_inputs {
+        synthesis.push_str(&format!("@{} input:\n{}\n\n", member, input));
+    }
+    synthesis
+}
+
 /// Run a board discussion and return the synthesized decision.
+///
+/// `timeout_secs` bounds each member's reply; see [`discuss`].
 pub async fn run_board_discussion(
     settings: &Settings,
     team_id: &str,
     topic: &str,
-    _timeout_secs: Option<u64>,
+    timeout_secs: Option<u64>,
+    continue_prior: bool,
+    dry_run: bool,
 ) -> Result<String> {
+    let member_timeout = timeout_secs.map(Duration::from_secs);
+    Ok(discuss(settings, team_id, topic, member_timeout, None, continue_prior, dry_run).await?.render())
+}
+
+/// Run a board discussion.
+///
+/// Members are asked concurrently, up to `board.max_concurrency` at once,
+/// and their inputs kept in team order. Each member (and the CEO) gets at
+/// most `member_timeout` to reply; a member who runs past it, or past
+/// `deadline` for the whole discussion, is recorded as `[@member timed out]`,
+/// and members still waiting for a slot once `deadline` has passed are
+/// skipped. The CEO is told who gave no input and is held to the same
+/// limits, so no decision is made once `deadline` has passed.
+///
+/// With `continue_prior`, the most relevant earlier decision on a similar
+/// topic is given to every member and the CEO so the board builds on it.
+/// With `dry_run`, no provider is invoked: members and the CEO get
+/// placeholder responses in the same transcript format, and nothing is saved.
+pub async fn discuss(
+    settings: &Settings,
+    team_id: &str,
+    topic: &str,
+    member_timeout: Option<Duration>,
+    deadline: Option<Duration>,
+    continue_prior: bool,
+    dry_run: bool,
+) -> Result<BoardDiscussion> {
+    let started_at = chrono::Utc::now();
+    let started = Instant::now();
+    let deadline = deadline.map(|d| started + d);
+    let (ceo, members) = board_participants(settings, team_id)?;
+
+    let prior = if continue_prior {
+        find_prior_decision(team_id, topic, settings.memory.decay_rate)
+    } else {
+        None
+    };
+    let prior_block = prior
+        .as_ref()
+        .map(|p| format!("Prior board decision to build on (refine or revise it, don't restart):\n{}\n\n", p.render()))
+        .unwrap_or_default();
+
+    // Members answer concurrently, at most `board.max_concurrency` at a time;
+    // join_all keeps their replies in team order.
+    let permits = tokio::sync::Semaphore::new(settings.board.max_concurrency.max(1));
+    let replies = futures::future::join_all(members.iter().map(|member| {
+        let permits = &permits;
+        let prior_block = &prior_block;
+        async move {
+            let _permit = permits.acquire().await;
+            let Some(limit) = call_limit(member_timeout, deadline) else {
+                return (format!("[@{} skipped: board deadline passed]", member), MemberReply::Skipped);
+            };
+
+            let prompt = format!(
+                "You are @{} in the {} board.\n\nTopic:\n{}\n\n{}Give your expert recommendation in 5-8 bullets: risks, opportunities, and next action.",
+                member, team_id, topic, prior_block
+            );
+
+            if dry_run {
+                return (format!("[dry-run @{} would respond]", member), MemberReply::Answered);
+            }
+            match within(limit, TaskSpawner::invoke_agent_cli(member, &prompt, settings)).await {
+                Some(reply) => (
+                    reply.unwrap_or_else(|e| format!("Error from @{}: {}", member, e)),
+                    MemberReply::Answered,
+                ),
+                None => (format!("[@{} timed out]", member), MemberReply::TimedOut),
+            }
+        }
+    }))
+    .await;
+
+    let mut member_inputs = Vec::new();
+    let mut timed_out = Vec::new();
+    let mut skipped = Vec::new();
+    for (member, (response, reply)) in members.iter().zip(replies) {
+        match reply {
+            MemberReply::Answered => {}
+            MemberReply::TimedOut => timed_out.push(member.clone()),
+            MemberReply::Skipped => skipped.push(member.clone()),
+        }
+        member_inputs.push((member.clone(), response.trim().to_string()));
+    }
+
+    let missing_block = missing_members(&timed_out, &skipped)
+        .map(|m| format!("Members without input (say so in your rationale): {}\n\n", m))
+        .unwrap_or_default();
+    let ceo_prompt = format!(
+        "You are @{} and lead board @{}.\n\nTopic:\n{}\n\n{}Recent team memory:\n{}\n\nBoard inputs:\n{}\n{}Provide final decision with:\nDECISION\nRATIONALE\nNEXT STEPS with @owner.",
+        ceo,
+        team_id,
+        topic,
+        prior_block,
+        render_recent_team_memory(team_id, topic, settings.memory.decay_rate),
+        render_member_inputs(&member_inputs),
+        missing_block
+    );
+
+    let ceo_decision = if dry_run {
+        dry_run_decision(&ceo, topic, &member_inputs)
+    } else if let Some(limit) = call_limit(member_timeout, deadline) {
+        match within(limit, TaskSpawner::invoke_agent_cli(&ceo, &ceo_prompt, settings)).await {
+            Some(reply) => reply.unwrap_or_else(|e| format!("CEO synthesis failed: {}", e)),
+            None => format!("CEO synthesis failed: @{} timed out", ceo),
+        }
+    } else {
+        format!("CEO synthesis skipped: board deadline passed before @{} could decide", ceo)
+    };
+    let ceo_decision = ceo_decision.trim().to_string();
+
+    if !dry_run {
+        persist_board_decision(team_id, topic, &ceo_decision)?;
+    }
+    Ok(BoardDiscussion {
+        team_id: team_id.to_string(),
+        topic: topic.to_string(),
+        ceo,
+        member_inputs,
+        timed_out,
+        skipped,
+        ceo_decision,
+        continued_from: prior.map(|p| (p.decision_id, p.topic)),
+        started_at,
+        duration: started.elapsed(),
+        dry_run,
+    })
+}
+
+/// Write `discussion` to `path` as Markdown and, unless it was a dry run,
+/// point `board.transcript.<ulid>` in team memory at the file. Returns the
+/// memory key, if one was written.
+pub fn save_board_transcript(discussion: &BoardDiscussion, path: &Path) -> Result<Option<String>> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, discussion.to_markdown())?;
+    if discussion.dry_run {
+        return Ok(None);
+    }
+
+    let key = format!("board.transcript.{}", ulid::Ulid::new());
+    let path = std::fs::canonicalize(path)?;
+    Memory::set(&key, &path.display().to_string(), MemoryScope::Team, Some(&discussion.team_id))?;
+    Ok(Some(key))
+}
+
+/// A board's CEO (its leader, else first member) and its other configured
+/// members, in team order. Disabled agents sit out; a disabled leader is
+/// replaced by the first enabled member.
+fn board_participants(settings: &Settings, team_id: &str) -> Result<(String, Vec<String>)> {
     let team = settings
         .teams
         .get(team_id)
         .ok_or_else(|| Error::NotFound(format!("Team not found: {}", team_id)))?;
 
+    let disabled = |id: &str| settings.agents.get(id).is_some_and(|a| !a.enabled);
     let ceo = team
         .leader_agent
         .clone()
-        .or_else(|| team.agents.first().cloned())
-        .ok_or_else(|| Error::Other(format!("Team {} has no members", team_id)))?;
+        .filter(|l| !disabled(l))
+        .or_else(|| team.agents.iter().find(|m| !disabled(m)).cloned())
+        .ok_or_else(|| Error::Other(format!("Team {} has no enabled members", team_id)))?;
 
-    let mut member_inputs = Vec::new();
-    for member in &team.agents {
-        if member == &ceo {
-            continue;
+    let members = team
+        .agents
+        .iter()
+        .filter(|m| **m != ceo && settings.is_agent_enabled(m))
+        .cloned()
+        .collect();
+    Ok((ceo, members))
+}
+
+/// Outcome of a board vote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteTally {
+    pub options: Vec<String>,
+    /// `(member, choice)` in voting order; `None` is an abstention.
+    pub votes: Vec<(String, Option<String>)>,
+    /// Votes per option, in option order.
+    pub counts: Vec<(String, usize)>,
+    /// `None` when a tie could not be broken by the leader's vote.
+    pub winner: Option<String>,
+    /// Set when the leader's vote decided a tie.
+    pub tie_broken_by: Option<String>,
+}
+
+impl VoteTally {
+    /// Printable breakdown of the vote.
+    pub fn render(&self) -> String {
+        let mut out = String::from("Tally:\n");
+        for (option, count) in &self.counts {
+            out.push_str(&format!("  {}: {}\n", option, count));
         }
-        if !settings.agents.contains_key(member) {
-            continue;
+        out.push_str("Votes:\n");
+        for (member, choice) in &self.votes {
+            out.push_str(&format!("  @{}: {}\n", member, choice.as_deref().unwrap_or("(abstained)")));
         }
+        match (&self.winner, &self.tie_broken_by) {
+            (Some(w), Some(leader)) => out.push_str(&format!("Winner: {} (tie broken by @{})", w, leader)),
+            (Some(w), None) => out.push_str(&format!("Winner: {}", w)),
+            (None, _) => out.push_str("No winner: unresolved tie"),
+        }
+        out
+    }
+}
 
-        let prompt = format!(
-            "You are @{} in the {} board.\n\nTopic:\n{}\n\nGive your expert recommendation in 5-8 bullets: risks, opportunities, and next action.",
-            member, team_id, topic
-        );
-
-        let response = TaskSpawner::invoke_agent_cli(member, &prompt, settings)
-            .await
-            .unwrap_or_else(|e| format!("Error from @{}: {}", member, e));
+/// The option a member's reply votes for: a `VOTE: <option>` line, else the
+/// only option mentioned as a whole word. Matching ignores case.
+pub fn parse_vote(reply: &str, options: &[String]) -> Option<String> {
+    let find = |text: &str| options.iter().find(|o| o.eq_ignore_ascii_case(text.trim())).cloned();
+    for line in reply.lines() {
+        let line = line.trim().trim_start_matches(['*', '-', ' ']);
+        if line.get(..5).is_some_and(|p| p.eq_ignore_ascii_case("vote:")) {
+            let choice = line[5..].trim().trim_matches(|c: char| c == '*' || c == '`' || c == '.' || c == '"');
+            return find(choice);
+        }
+    }
+    let words: Vec<String> = reply
+        .split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+        .map(str::to_lowercase)
+        .collect();
+    let mentioned: Vec<&String> = options
+        .iter()
+        .filter(|o| words.iter().any(|w| *w == o.to_lowercase()))
+        .collect();
+    match mentioned.as_slice() {
+        [only] => Some((*only).clone()),
+        _ => None,
+    }
+}
 
-        member_inputs.push((member.clone(), response.trim().to_string()));
+/// Count member replies into a [`VoteTally`]; a member without a reply
+/// abstains. A tie for first place goes to the option `leader` voted for, if
+/// it is among the tied ones.
+pub fn tally_votes(leader: &str, options: &[String], replies: &[(String, Option<String>)]) -> VoteTally {
+    let votes: Vec<(String, Option<String>)> = replies
+        .iter()
+        .map(|(member, reply)| (member.clone(), reply.as_deref().and_then(|r| parse_vote(r, options))))
+        .collect();
+    let counts: Vec<(String, usize)> = options
+        .iter()
+        .map(|o| (o.clone(), votes.iter().filter(|(_, c)| c.as_ref() == Some(o)).count()))
+        .collect();
+    let top = counts.iter().map(|(_, n)| *n).max().unwrap_or(0);
+    let leading: Vec<&String> = counts.iter().filter(|(_, n)| *n == top && top > 0).map(|(o, _)| o).collect();
+    let (winner, tie_broken_by) = match leading.as_slice() {
+        [] => (None, None),
+        [only] => (Some((*only).clone()), None),
+        tied => {
+            let leader_choice = votes.iter().find(|(m, _)| m == leader).and_then(|(_, c)| c.as_ref());
+            match leader_choice.filter(|c| tied.contains(c)) {
+                Some(choice) => (Some(choice.clone()), Some(leader.to_string())),
+                None => (None, None),
+            }
+        }
+    };
+    VoteTally {
+        options: options.to_vec(),
+        votes,
+        counts,
+        winner,
+        tie_broken_by,
     }
+}
 
-    let mut synthesis = String::new();
-    for (member, input) in &member_inputs {
-        synthesis.push_str(&format!("@{} input:\n{}\n\n", member, input));
+/// Ask every board member, the CEO included, to vote on `topic` among
+/// `options`, then record the tally as a `board.decision.<id>` entry.
+/// Returns the decision id and the tally.
+pub async fn run_board_vote(
+    settings: &Settings,
+    team_id: &str,
+    topic: &str,
+    options: &[String],
+) -> Result<(String, VoteTally)> {
+    let mut unique: Vec<String> = Vec::new();
+    for option in options.iter().map(|o| o.trim()).filter(|o| !o.is_empty()) {
+        if !unique.iter().any(|u| u.eq_ignore_ascii_case(option)) {
+            unique.push(option.to_string());
+        }
+    }
+    if unique.len() < 2 {
+        return Err(Error::Config("A vote needs at least two distinct options".to_string()));
     }
 
-    let ceo_prompt = format!(
-        "You are @{} and lead board @{}.\n\nTopic:\n{}\n\nRecent team memory:\n{}\n\nBoard inputs:\n{}\nProvide final decision with:\nDECISION\nRATIONALE\nNEXT STEPS with @owner.",
-        ceo,
-        team_id,
-        topic,
-        render_recent_team_memory(team_id, topic),
-        synthesis
-    );
+    let (ceo, members) = board_participants(settings, team_id)?;
+    let mut replies = Vec::new();
+    for member in std::iter::once(&ceo).chain(members.iter()) {
+        let prompt = format!(
+            "You are @{} in the {} board.\n\nVote on:\n{}\n\nOptions: {}\n\nReply with one line `VOTE: <option>` using exactly one of the options, then one sentence of reasoning.",
+            member,
+            team_id,
+            topic,
+            unique.join(", ")
+        );
+        let reply = match TaskSpawner::invoke_agent_cli(member, &prompt, settings).await {
+            Ok(reply) => Some(reply),
+            Err(e) => {
+                tracing::warn!("@{} abstains from the {} vote: {}", member, team_id, e);
+                None
+            }
+        };
+        replies.push((member.clone(), reply));
+    }
 
-    let ceo_decision = TaskSpawner::invoke_agent_cli(&ceo, &ceo_prompt, settings)
-        .await
-        .unwrap_or_else(|e| format!("CEO synthesis failed: {}", e));
+    let tally = tally_votes(&ceo, &unique, &replies);
+    let id = persist_board_vote(team_id, topic, &ceo, &tally)?;
+    Ok((id, tally))
+}
 
-    let output = format!(
-        "Board @{} discussion on: {}\n\n{}\nCEO (@{}) decision:\n{}",
-        team_id,
-        topic,
-        synthesis.trim(),
-        ceo,
-        ceo_decision.trim()
-    );
+fn persist_board_vote(team_id: &str, topic: &str, ceo: &str, tally: &VoteTally) -> Result<String> {
+    let id = ulid::Ulid::new().to_string();
+    let key = format!("board.decision.{}", id);
+    let decision = match &tally.winner {
+        Some(winner) => format!("Vote: {}", winner),
+        None => "Vote: no winner (unresolved tie)".to_string(),
+    };
+    let record = serde_json::json!({
+        "decision_id": id,
+        "kind": "vote",
+        "topic": topic,
+        "decision": decision,
+        "owners": [ceo],
+        "deadlines": [],
+        "risks": [],
+        "options": tally.options,
+        "tally": tally
+            .counts
+            .iter()
+            .map(|(o, n)| (o.clone(), serde_json::json!(n)))
+            .collect::<serde_json::Map<_, _>>(),
+        "votes": tally
+            .votes
+            .iter()
+            .map(|(m, c)| serde_json::json!({ "member": m, "choice": c }))
+            .collect::<Vec<_>>(),
+        "winner": tally.winner,
+        "tie_broken_by": tally.tie_broken_by,
+        "raw": tally.render(),
+        "created_at": chrono::Utc::now().to_rfc3339(),
+        "confidence": "medium"
+    });
+    validate_decision_schema(&record)?;
+    Memory::set(&key, &record.to_string(), MemoryScope::Team, Some(team_id))?;
+    Memory::set(
+        "board.last_decision",
+        &format!("{} | {}", topic, decision),
+        MemoryScope::Team,
+        Some(team_id),
+    )?;
+    Ok(id)
+}
 
-    persist_board_decision(team_id, topic, ceo_decision.trim())?;
-    Ok(output)
+/// Placeholder CEO decision for a dry run, in the DECISION/RATIONALE/NEXT STEPS shape.
+fn dry_run_decision(ceo: &str, topic: &str, member_inputs: &[(String, String)]) -> String {
+    let members: Vec<String> = member_inputs.iter().map(|(m, _)| format!("@{}", m)).collect();
+    let members = if members.is_empty() { "none".to_string() } else { members.join(", ") };
+    format!(
+        "DECISION: [dry-run @{} would decide on: {}]\nRATIONALE: [dry-run] inputs from {}\nNEXT STEPS: [dry-run] @{} owns follow-up",
+        ceo, topic, members, ceo
+    )
 }
 
-/// Execute mention-based delegations from team leader response.
+/// Run the teammate delegations a leader's response mentions, in order.
+///
+/// The leader's team is resolved with [`resolve_team_for_agent`] against
+/// `preferred_teams`. Each `(agent, output)` is also sent on `on_result` as
+/// soon as it completes.
 pub async fn execute_leader_delegations(
     settings: &Settings,
     current_agent_id: &str,
+    preferred_teams: &[&str],
     response: &str,
+    on_result: Option<tokio::sync::mpsc::UnboundedSender<(String, String)>>,
 ) -> Result<Vec<(String, String)>> {
-    let (team_id, team) = match find_team_for_agent(current_agent_id, &settings.teams) {
-        Some(v) => v,
-        None => return Ok(Vec::new()),
-    };
+    let (team_id, team) =
+        match resolve_team_for_agent(current_agent_id, preferred_teams, &settings.teams) {
+            Some(selection) => (selection.team_id, selection.team),
+            None => return Ok(Vec::new()),
+        };
 
     if team.leader_agent.as_deref() != Some(current_agent_id) {
         return Ok(Vec::new());
@@ -286,16 +718,19 @@ pub async fn execute_leader_delegations(
             &target,
             &delegated_prompt,
             status,
-            &out,
+            crate::core::text::truncate_chars(&out, settings.memory.delegation_chars),
         )?;
+        if let Some(tx) = on_result.as_ref() {
+            let _ = tx.send((target.clone(), out.trim().to_string()));
+        }
         results.push((target, out.trim().to_string()));
     }
 
     Ok(results)
 }
 
-fn render_recent_team_memory(team_id: &str, query: &str) -> String {
-    match Memory::relevant(query, MemoryScope::Team, Some(team_id), 8) {
+fn render_recent_team_memory(team_id: &str, query: &str, decay_rate: f32) -> String {
+    match Memory::relevant(query, MemoryScope::Team, Some(team_id), 8, decay_rate) {
         Ok(entries) if !entries.is_empty() => entries
             .iter()
             .map(|e| format!("- {}: {}", e.key, e.value.chars().take(220).collect::<String>()))
@@ -305,6 +740,76 @@ fn render_recent_team_memory(team_id: &str, query: &str) -> String {
     }
 }
 
+/// An earlier board decision carried into a `--continue` discussion.
+#[derive(Debug, Clone, PartialEq)]
+struct PriorDecision {
+    decision_id: String,
+    topic: String,
+    decision: String,
+    created_at: String,
+}
+
+impl PriorDecision {
+    fn from_record(value: &str) -> Option<Self> {
+        let record: serde_json::Value = serde_json::from_str(value).ok()?;
+        let field = |k: &str| record.get(k).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let decision = match field("decision") {
+            d if d.trim().is_empty() => field("raw"),
+            d => d,
+        };
+        Some(Self {
+            decision_id: field("decision_id"),
+            topic: field("topic"),
+            decision,
+            created_at: field("created_at"),
+        })
+    }
+
+    fn render(&self) -> String {
+        format!(
+            "- id: {}\n- topic: {}\n- decided: {}\n- decision: {}",
+            self.decision_id,
+            self.topic,
+            self.created_at,
+            self.decision.chars().take(800).collect::<String>()
+        )
+    }
+}
+
+/// Words too common to tie two board topics together.
+const TOPIC_STOPWORDS: &[&str] = &[
+    "about", "after", "again", "also", "and", "are", "before", "but", "can", "does", "for", "from", "have",
+    "how", "into", "not", "now", "our", "should", "that", "the", "this", "what", "when", "which", "will",
+    "with", "would", "you", "your",
+];
+
+/// Lowercased words of `text` that say something about its subject.
+fn topic_words(text: &str) -> std::collections::HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|w| w.len() >= 3 && !TOPIC_STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Whether a prior decision is about `topic`: at least a third of the
+/// topic's significant words appear in the prior topic or decision.
+fn is_similar_topic(prior: &PriorDecision, topic: &str) -> bool {
+    let words = topic_words(topic);
+    let prior_words = topic_words(&format!("{} {}", prior.topic, prior.decision));
+    let shared = words.iter().filter(|w| prior_words.contains(*w)).count();
+    shared > 0 && shared * 3 >= words.len()
+}
+
+/// The most relevant earlier `board.decision.*` on a similar topic.
+fn find_prior_decision(team_id: &str, topic: &str, decay_rate: f32) -> Option<PriorDecision> {
+    Memory::relevant(topic, MemoryScope::Team, Some(team_id), usize::MAX, decay_rate)
+        .ok()?
+        .into_iter()
+        .filter(|e| e.key.starts_with("board.decision."))
+        .filter_map(|e| PriorDecision::from_record(&e.value))
+        .find(|p| is_similar_topic(p, topic))
+}
+
 fn persist_board_decision(team_id: &str, topic: &str, decision_text: &str) -> Result<()> {
     let id = ulid::Ulid::new().to_string();
     let key = format!("board.decision.{}", id);
@@ -337,6 +842,122 @@ fn persist_board_decision(team_id: &str, topic: &str, decision_text: &str) -> Re
     Ok(())
 }
 
+/// Pick the member after `current` in round-robin order, skipping ids not in `known`.
+fn next_leader(members: &[String], current: Option<&str>, known: impl Fn(&str) -> bool) -> Option<String> {
+    let eligible: Vec<&String> = members.iter().filter(|m| known(m)).collect();
+    if eligible.is_empty() {
+        return None;
+    }
+    let next = match current.and_then(|c| eligible.iter().position(|m| *m == c)) {
+        Some(idx) => eligible[(idx + 1) % eligible.len()],
+        // Current leader is gone: start from the member listed after it, else the first.
+        None => current
+            .and_then(|c| members.iter().position(|m| m == c))
+            .and_then(|idx| members[idx + 1..].iter().find(|m| known(m)))
+            .unwrap_or(eligible[0]),
+    };
+    Some(next.clone())
+}
+
+/// Rotate a team's `leader_agent` to the next member (round-robin) and record
+/// the change as a board decision. Returns `(previous, new)` leader.
+pub fn rotate_team_leader(team_id: &str) -> Result<(Option<String>, String)> {
+    let mut settings = crate::config::load_settings()?;
+    let team = settings
+        .teams
+        .get(team_id)
+        .ok_or_else(|| Error::NotFound(format!("Team not found: {}", team_id)))?;
+    let previous = team.leader_agent.clone();
+    let next = next_leader(&team.agents, previous.as_deref(), |id| settings.agents.contains_key(id))
+        .ok_or_else(|| Error::Other(format!("Team @{} has no existing member agents to lead", team_id)))?;
+
+    if let Some(team) = settings.teams.get_mut(team_id) {
+        team.leader_agent = Some(next.clone());
+    }
+    crate::config::save_settings(&settings)?;
+
+    let decision = format!(
+        "DECISION: Rotate leadership of @{} from @{} to @{}\nOwner: @{}",
+        team_id,
+        previous.as_deref().unwrap_or("none"),
+        next,
+        next
+    );
+    persist_board_decision(team_id, "Leadership rotation", &decision)?;
+    tracing::info!(
+        "Rotated leader of @{}: {:?} -> {}",
+        team_id,
+        previous,
+        next
+    );
+    Ok((previous, next))
+}
+
+/// Normalise an `HH:MM` schedule time (`9:5` -> `09:05`), the form board
+/// schedules are matched against.
+pub fn normalize_schedule_time(time: &str) -> Result<String> {
+    let invalid = || Error::Config(format!("Invalid time '{}'. Use HH:MM", time));
+    let (hour, minute) = time.trim().split_once(':').ok_or_else(invalid)?;
+    let hour: u32 = hour.parse().map_err(|_| invalid())?;
+    let minute: u32 = minute.parse().map_err(|_| invalid())?;
+    if hour > 23 || minute > 59 {
+        return Err(invalid());
+    }
+    Ok(format!("{:02}:{:02}", hour, minute))
+}
+
+/// Whether `schedule` is a plain (non-cron) digest for `agent_id` at `time`.
+fn is_agent_digest_at(schedule: &crate::config::BoardSchedule, agent_id: &str, time: Option<&str>) -> bool {
+    schedule.schedule_type == "digest"
+        && schedule.cron.as_deref().is_none_or(|c| c.trim().is_empty())
+        && schedule.agent_id.as_deref() == Some(agent_id)
+        && time.is_none_or(|t| schedule.time == t)
+}
+
+/// Add a digest schedule at `time` for every configured agent that does not
+/// already have one then. Returns the `(agent, schedule id)` pairs added, in
+/// agent id order.
+pub fn add_agent_digests(settings: &mut Settings, time: &str, sender_id: Option<&str>) -> Result<Vec<(String, String)>> {
+    let time = normalize_schedule_time(time)?;
+    let mut agent_ids: Vec<String> = settings.agents.keys().cloned().collect();
+    agent_ids.sort();
+
+    let team_id = settings.board.team_id.clone();
+    let schedules = settings.board.schedules.get_or_insert_with(Vec::new);
+    let mut added = Vec::new();
+    for agent_id in agent_ids {
+        if schedules.iter().any(|s| is_agent_digest_at(s, &agent_id, Some(&time))) {
+            continue;
+        }
+        let id = format!("digest-{}", ulid::Ulid::new());
+        schedules.push(crate::config::BoardSchedule {
+            id: id.clone(),
+            schedule_type: "digest".to_string(),
+            time: time.clone(),
+            team_id: team_id.clone(),
+            agent_id: Some(agent_id.clone()),
+            sender_id: sender_id.map(String::from),
+            enabled: true,
+            cron: None,
+        });
+        added.push((agent_id, id));
+    }
+    Ok(added)
+}
+
+/// Remove the digest schedules of every configured agent, only those at
+/// `time` when given. Returns how many were removed.
+pub fn remove_agent_digests(settings: &mut Settings, time: Option<&str>) -> Result<usize> {
+    let time = time.map(normalize_schedule_time).transpose()?;
+    let agents = &settings.agents;
+    let Some(schedules) = settings.board.schedules.as_mut() else {
+        return Ok(0);
+    };
+    let before = schedules.len();
+    schedules.retain(|s| !agents.keys().any(|a| is_agent_digest_at(s, a, time.as_deref())));
+    Ok(before - schedules.len())
+}
+
 fn persist_delegation_result(
     team_id: &str,
     delegation_id: &str,
@@ -354,7 +975,7 @@ fn persist_delegation_result(
         "task": task,
         "status": status,
         "updated_at": chrono::Utc::now().to_rfc3339(),
-        "output": output.chars().take(1500).collect::<String>()
+        "output": output
     });
     Memory::set(&key, &record.to_string(), MemoryScope::Team, Some(team_id))?;
     Ok(())
@@ -471,7 +1092,13 @@ pub fn resolve_workspace_root(settings: &Settings) -> PathBuf {
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_board_decision, validate_decision_schema};
+    use super::{
+        add_agent_digests, discuss, is_similar_topic, next_leader, normalize_schedule_time, parse_board_decision,
+        parse_vote, remove_agent_digests, run_board_discussion, save_board_transcript, tally_votes,
+        validate_decision_schema, PriorDecision,
+    };
+    use crate::config::{AgentConfig, Settings};
+    use std::time::Duration;
 
     #[test]
     fn parses_decision_fields() {
@@ -491,4 +1118,321 @@ mod tests {
         });
         assert!(validate_decision_schema(&record).is_ok());
     }
+
+    #[test]
+    fn next_leader_rotates_round_robin_over_existing_members() {
+        let members: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let all = |_: &str| true;
+        assert_eq!(next_leader(&members, Some("a"), all).as_deref(), Some("b"));
+        assert_eq!(next_leader(&members, Some("c"), all).as_deref(), Some("a"));
+        assert_eq!(next_leader(&members, None, all).as_deref(), Some("a"));
+
+        // Missing agents are skipped, including a leader that no longer exists.
+        let no_b = |id: &str| id != "b";
+        assert_eq!(next_leader(&members, Some("a"), no_b).as_deref(), Some("c"));
+        assert_eq!(next_leader(&members, Some("b"), no_b).as_deref(), Some("c"));
+        assert_eq!(next_leader(&members, Some("a"), |_: &str| false), None);
+    }
+
+    #[test]
+    fn digest_schedules_for_all_agents_are_validated_and_deduped() {
+        let mut settings = Settings::default();
+        for id in ["coder", "assistant"] {
+            settings.agents.insert(id.to_string(), AgentConfig::default());
+        }
+
+        assert!(add_agent_digests(&mut settings, "25:00", None).is_err());
+        assert!(add_agent_digests(&mut settings, "noon", None).is_err());
+        assert_eq!(normalize_schedule_time("9:5").unwrap(), "09:05");
+
+        let added = add_agent_digests(&mut settings, "18:00", Some("42")).unwrap();
+        let agents: Vec<&str> = added.iter().map(|(a, _)| a.as_str()).collect();
+        assert_eq!(agents, vec!["assistant", "coder"]);
+
+        // Re-running is a no-op; a new agent only gets its own schedule.
+        settings.agents.insert("writer".to_string(), AgentConfig::default());
+        let again = add_agent_digests(&mut settings, "18:00", None).unwrap();
+        assert_eq!(again.len(), 1);
+        assert_eq!(again[0].0, "writer");
+        add_agent_digests(&mut settings, "08:00", None).unwrap();
+        assert_eq!(settings.board.schedules.as_ref().unwrap().len(), 6);
+
+        assert_eq!(remove_agent_digests(&mut settings, Some("8:00")).unwrap(), 3);
+        assert_eq!(remove_agent_digests(&mut settings, None).unwrap(), 3);
+        assert!(settings.board.schedules.as_ref().unwrap().is_empty());
+    }
+
+    #[test]
+    fn prior_decisions_are_parsed_and_matched_by_topic() {
+        let record = serde_json::json!({
+            "decision_id": "01J",
+            "topic": "Pricing for the pro tier",
+            "decision": "",
+            "raw": "DECISION: raise to $20",
+            "created_at": "2026-10-01T00:00:00Z",
+        });
+        let prior = PriorDecision::from_record(&record.to_string()).unwrap();
+        assert_eq!(prior.decision, "DECISION: raise to $20");
+        assert!(prior.render().contains("- id: 01J"));
+
+        assert!(is_similar_topic(&prior, "Revisit PRICING after launch"));
+        assert!(!is_similar_topic(&prior, "Hiring plan"));
+        assert!(!is_similar_topic(&prior, "to a in"));
+        // Shared filler words or word fragments do not make topics related.
+        assert!(!is_similar_topic(&prior, "Improve the onboarding process for new hires"));
+        assert!(!is_similar_topic(&prior, "Office move, hiring, budget and a pricing page typo"));
+        assert!(PriorDecision::from_record("not json").is_none());
+    }
+
+    #[tokio::test]
+    async fn dry_run_discussion_invokes_no_provider() {
+        let _home = crate::config::test_support::TempHome::new();
+        let mut settings = Settings::default();
+        // Any real call would fail against this port and show up as an error.
+        settings.models.max_retries = 0;
+        settings.models.ollama.base_url = Some("http://127.0.0.1:9".to_string());
+        for id in ["assistant", "coder", "seo"] {
+            let agent = AgentConfig { provider: Some("ollama".to_string()), ..AgentConfig::default() };
+            settings.agents.insert(id.to_string(), agent);
+        }
+        settings.teams.insert(
+            "board".to_string(),
+            crate::config::TeamConfig {
+                name: "Board".to_string(),
+                agents: vec!["assistant".to_string(), "coder".to_string(), "ghost".to_string(), "seo".to_string()],
+                leader_agent: Some("assistant".to_string()),
+            },
+        );
+
+        let output = run_board_discussion(&settings, "board", "Q4 roadmap", Some(1), false, true).await.unwrap();
+        assert!(!output.contains("Error") && !output.contains("failed"), "{}", output);
+        let coder = output.find("@coder input:\n[dry-run @coder would respond]").unwrap();
+        let seo = output.find("@seo input:\n[dry-run @seo would respond]").unwrap();
+        assert!(coder < seo);
+        assert!(!output.contains("@ghost"));
+
+        let (_, decision) = output.split_once("CEO (@assistant) decision:\n").unwrap();
+        assert_eq!(parse_board_decision(decision).decision, "[dry-run @assistant would decide on: Q4 roadmap]");
+        assert!(crate::memory::Memory::get("board.last_decision", crate::memory::MemoryScope::Team, Some("board"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn discussion_transcripts_are_written_as_markdown() {
+        let home = crate::config::test_support::TempHome::new();
+        let mut settings = Settings::default();
+        for id in ["assistant", "coder", "seo"] {
+            settings.agents.insert(id.to_string(), AgentConfig::default());
+        }
+        settings.teams.insert(
+            "board".to_string(),
+            crate::config::TeamConfig {
+                name: "Board".to_string(),
+                agents: vec!["assistant".to_string(), "coder".to_string(), "seo".to_string()],
+                leader_agent: Some("assistant".to_string()),
+            },
+        );
+
+        let mut discussion = discuss(&settings, "board", "Q4 roadmap", None, None, false, true).await.unwrap();
+        let path = home.tinyvegeta_dir().join("archive").join("q4.md");
+        assert_eq!(save_board_transcript(&discussion, &path).unwrap(), None, "dry runs save nothing to memory");
+
+        let md = std::fs::read_to_string(&path).unwrap();
+        assert!(md.starts_with("# Board discussion: Q4 roadmap\n\n- Team: @board\n- Topic: Q4 roadmap\n- Timestamp: "));
+        assert!(md.contains("\n- Duration: ") && md.contains("\n- Dry run: yes\n"));
+        let coder = md.find("\n## @coder\n\n[dry-run @coder would respond]\n").unwrap();
+        let seo = md.find("\n## @seo\n\n[dry-run @seo would respond]\n").unwrap();
+        let ceo = md.find("\n## CEO decision (@assistant)\n\nDECISION: [dry-run @assistant would decide on: Q4 roadmap]").unwrap();
+        assert!(coder < seo && seo < ceo);
+
+        discussion.dry_run = false;
+        let key = save_board_transcript(&discussion, &path).unwrap().unwrap();
+        assert!(key.starts_with("board.transcript."));
+        let pointer = crate::memory::Memory::get(&key, crate::memory::MemoryScope::Team, Some("board")).unwrap().unwrap();
+        assert_eq!(pointer.value, std::fs::canonicalize(&path).unwrap().display().to_string());
+    }
+
+    /// Settings for a board led by `assistant` with `members` on a fake
+    /// Ollama: each member answers `@<member> says ship it` after its
+    /// latency, and the CEO gets its own prompt echoed back.
+    async fn board_on_fake_ollama(members: &[(&str, Duration)]) -> Settings {
+        use axum::routing::post;
+        use axum::Json;
+
+        let latencies: std::collections::HashMap<String, Duration> =
+            members.iter().map(|(m, d)| (m.to_string(), *d)).collect();
+        let chat = axum::Router::new().route(
+            "/api/chat",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                let prompt = body["messages"][0]["content"].as_str().unwrap_or_default().to_string();
+                let member = prompt.strip_prefix("You are @").and_then(|p| p.split_once(' ')).map(|(m, _)| m.to_string());
+                let reply = match member.filter(|_| !prompt.contains("and lead board")) {
+                    Some(member) => {
+                        tokio::time::sleep(latencies.get(&member).copied().unwrap_or_default()).await;
+                        format!("@{} says ship it", member)
+                    }
+                    None => prompt,
+                };
+                Json(serde_json::json!({ "message": { "role": "assistant", "content": reply } }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, chat).await.unwrap() });
+
+        let mut settings = Settings::default();
+        settings.models.provider = "ollama".to_string();
+        settings.models.max_retries = 0;
+        settings.models.ollama.base_url = Some(url);
+        let ids: Vec<String> = std::iter::once("assistant").chain(members.iter().map(|(m, _)| *m)).map(String::from).collect();
+        for id in &ids {
+            settings.agents.insert(id.clone(), AgentConfig::default());
+        }
+        settings.teams.insert(
+            "board".to_string(),
+            crate::config::TeamConfig {
+                name: "Board".to_string(),
+                agents: ids,
+                leader_agent: Some("assistant".to_string()),
+            },
+        );
+        settings
+    }
+
+    const NEVER: Duration = Duration::from_secs(30);
+
+    #[tokio::test]
+    async fn slow_members_time_out_without_stalling_the_board() {
+        let _home = crate::config::test_support::TempHome::new();
+        let settings = board_on_fake_ollama(&[("slow", NEVER), ("fast", Duration::ZERO)]).await;
+
+        let discussion = discuss(&settings, "board", "Launch", Some(Duration::from_millis(500)), None, false, false)
+            .await
+            .unwrap();
+        assert_eq!(discussion.timed_out, vec!["slow".to_string()]);
+        assert!(discussion.skipped.is_empty());
+        assert_eq!(
+            discussion.member_inputs,
+            vec![
+                ("slow".to_string(), "[@slow timed out]".to_string()),
+                ("fast".to_string(), "@fast says ship it".to_string()),
+            ]
+        );
+        // The CEO still decides, knowing who gave no input.
+        assert!(discussion.ceo_decision.contains("Members without input (say so in your rationale): @slow (timed out)"));
+        assert!(discussion.render().contains("Members without input: @slow (timed out)\n\nCEO (@assistant) decision:"));
+        assert!(crate::memory::Memory::get("board.last_decision", crate::memory::MemoryScope::Team, Some("board"))
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn members_after_the_deadline_are_skipped() {
+        let _home = crate::config::test_support::TempHome::new();
+        let mut settings = board_on_fake_ollama(&[("slow", NEVER), ("fast", Duration::ZERO)]).await;
+        // One at a time, so @fast is still waiting when the deadline passes.
+        settings.board.max_concurrency = 1;
+
+        let discussion = discuss(&settings, "board", "Launch", None, Some(Duration::from_millis(500)), false, false)
+            .await
+            .unwrap();
+        assert_eq!(discussion.timed_out, vec!["slow".to_string()]);
+        assert_eq!(discussion.skipped, vec!["fast".to_string()]);
+        assert_eq!(discussion.member_inputs[1].1, "[@fast skipped: board deadline passed]");
+        assert!(discussion.render().contains("Members without input: @slow (timed out), @fast (skipped)"));
+    }
+
+    #[tokio::test]
+    async fn ceo_is_not_asked_once_the_deadline_has_passed() {
+        let _home = crate::config::test_support::TempHome::new();
+        let settings = board_on_fake_ollama(&[("fast", Duration::ZERO)]).await;
+
+        let discussion = discuss(&settings, "board", "Launch", Some(NEVER), Some(Duration::ZERO), false, false)
+            .await
+            .unwrap();
+        assert_eq!(discussion.skipped, vec!["fast".to_string()]);
+        assert_eq!(
+            discussion.ceo_decision,
+            "CEO synthesis skipped: board deadline passed before @assistant could decide"
+        );
+    }
+
+    #[tokio::test]
+    async fn members_answer_concurrently_in_team_order() {
+        let _home = crate::config::test_support::TempHome::new();
+        let ms = Duration::from_millis;
+        let members = [("a", ms(600)), ("b", ms(200)), ("c", ms(400)), ("d", ms(0))];
+        let mut settings = board_on_fake_ollama(&members).await;
+        settings.board.max_concurrency = 4;
+        // @e fails outright; the others still answer.
+        settings.teams.get_mut("board").unwrap().agents.push("e".to_string());
+        settings.agents.insert(
+            "e".to_string(),
+            AgentConfig { provider: Some("grok".to_string()), ..AgentConfig::default() },
+        );
+        settings.models.grok.base_url = Some("http://127.0.0.1:9".to_string());
+
+        let started = std::time::Instant::now();
+        let discussion = discuss(&settings, "board", "Launch", None, None, false, false).await.unwrap();
+        let elapsed = started.elapsed();
+        // Sequential calls would take at least 1.2s.
+        assert!(elapsed >= ms(600) && elapsed < ms(1200), "{:?}", elapsed);
+
+        let order: Vec<&str> = discussion.member_inputs.iter().map(|(m, _)| m.as_str()).collect();
+        assert_eq!(order, ["a", "b", "c", "d", "e"]);
+        for (member, input) in &discussion.member_inputs[..4] {
+            assert_eq!(input, &format!("@{} says ship it", member));
+        }
+        assert!(discussion.member_inputs[4].1.starts_with("Error from @e:"), "{}", discussion.member_inputs[4].1);
+        assert!(discussion.timed_out.is_empty() && discussion.skipped.is_empty());
+        assert!(discussion.ceo_decision.starts_with("You are @assistant and lead board @board."));
+    }
+
+    #[test]
+    fn canned_vote_replies_are_tallied_with_leader_tiebreak() {
+        let options: Vec<String> = ["yes", "no", "defer"].iter().map(|o| o.to_string()).collect();
+        let reply = |m: &str, r: &str| (m.to_string(), Some(r.to_string()));
+
+        assert_eq!(parse_vote("**VOTE: Yes**\nShip it.", &options).as_deref(), Some("yes"));
+        assert_eq!(parse_vote("I'd say defer until QA signs off.", &options).as_deref(), Some("defer"));
+        assert_eq!(parse_vote("yes or no, hard to say", &options), None);
+        assert_eq!(parse_vote("VOTE: maybe", &options), None);
+
+        let replies = vec![
+            reply("assistant", "VOTE: no\nNot ready."),
+            reply("coder", "VOTE: yes"),
+            reply("seo", "vote: YES"),
+            reply("sales", "VOTE: no"),
+            ("ops".to_string(), None),
+        ];
+        let tally = tally_votes("assistant", &options, &replies);
+        assert_eq!(
+            tally.counts,
+            vec![("yes".to_string(), 2), ("no".to_string(), 2), ("defer".to_string(), 0)]
+        );
+        assert_eq!(tally.votes[4], ("ops".to_string(), None));
+        assert_eq!(tally.winner.as_deref(), Some("no"));
+        assert_eq!(tally.tie_broken_by.as_deref(), Some("assistant"));
+        assert!(tally.render().contains("Winner: no (tie broken by @assistant)"));
+
+        let clear = tally_votes("assistant", &options, &replies[1..3]);
+        assert_eq!((clear.winner.as_deref(), clear.tie_broken_by), (Some("yes"), None));
+
+        // The leader voting outside the tie leaves it unresolved.
+        let replies = vec![
+            reply("assistant", "VOTE: defer"),
+            reply("coder", "VOTE: yes"),
+            reply("seo", "VOTE: yes"),
+            reply("sales", "VOTE: no"),
+            reply("ops", "VOTE: no"),
+        ];
+        assert_eq!(tally_votes("assistant", &options, &replies).winner, None);
+
+        // A member whose provider failed abstains, whatever the error says.
+        let replies = vec![reply("coder", "VOTE: yes"), ("ops".to_string(), None)];
+        let tally = tally_votes("assistant", &options, &replies);
+        assert_eq!(tally.votes[1], ("ops".to_string(), None));
+        assert_eq!(tally.winner.as_deref(), Some("yes"));
+    }
 }
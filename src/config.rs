@@ -3,22 +3,71 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::error::Error;
 pub type Result<T> = std::result::Result<T, Error>;
 
-/// Get the TinyVegeta home directory (~/.tinyvegeta).
-pub fn get_home_dir() -> Result<PathBuf> {
+/// Environment variable selecting a settings profile; `--profile` sets it.
+pub const PROFILE_ENV: &str = "TINYVEGETA_PROFILE";
+
+/// The active profile, from [`PROFILE_ENV`]; `None` when unset or empty.
+pub fn active_profile() -> Result<Option<String>> {
+    let profile = match std::env::var(PROFILE_ENV) {
+        Ok(p) if !p.trim().is_empty() => p.trim().to_string(),
+        _ => return Ok(None),
+    };
+    if !profile.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(Error::Config(format!(
+            "Invalid profile '{}'. Use letters, digits, '-' and '_'",
+            profile
+        )));
+    }
+    Ok(Some(profile))
+}
+
+/// Get the directory shared by all profiles (~/.tinyvegeta).
+pub fn get_base_dir() -> Result<PathBuf> {
     let home = directories::UserDirs::new()
         .ok_or_else(|| Error::Config("Could not determine home directory".to_string()))?;
 
     Ok(home.home_dir().join(".tinyvegeta"))
 }
 
-/// Get the settings file path.
+/// Get the TinyVegeta home directory holding runtime state (queue, memory, ...):
+/// ~/.tinyvegeta, or ~/.tinyvegeta/profiles/<profile> when a profile is active.
+pub fn get_home_dir() -> Result<PathBuf> {
+    let base = get_base_dir()?;
+    Ok(match active_profile()? {
+        Some(profile) => base.join("profiles").join(profile),
+        None => base,
+    })
+}
+
+/// Get the settings file path: ~/.tinyvegeta/settings.json, or
+/// settings.<profile>.json when a profile is active.
 pub fn get_settings_path() -> Result<PathBuf> {
-    Ok(get_home_dir()?.join("settings.json"))
+    let file = match active_profile()? {
+        Some(profile) => format!("settings.{}.json", profile),
+        None => "settings.json".to_string(),
+    };
+    Ok(get_base_dir()?.join(file))
+}
+
+/// Profiles with a `settings.<profile>.json` file, sorted.
+pub fn list_profiles() -> Result<Vec<String>> {
+    let mut profiles: Vec<String> = std::fs::read_dir(get_base_dir()?)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            let profile = name.strip_prefix("settings.")?.strip_suffix(".json")?;
+            (!profile.is_empty()).then(|| profile.to_string())
+        })
+        .collect();
+    profiles.sort();
+    Ok(profiles)
 }
 
 /// Load settings from ~/.tinyvegeta/settings.json
@@ -38,12 +87,12 @@ pub fn load_settings() -> Result<Settings> {
     // Self-heal minimal defaults for existing installs that predate
     // default team/board provisioning.
     if ensure_default_team_and_board(&mut settings) {
-        let updated = serde_json::to_string_pretty(&settings)?;
-        std::fs::write(&path, updated)?;
+        atomic_write(&path, serde_json::to_string_pretty(&settings)?)?;
         tracing::info!("Applied default team/board provisioning to {}", path.display());
     }
 
     validate_settings(&settings)?;
+    apply_secret_env_overrides(&mut settings);
 
     tracing::debug!("Loaded settings from {}", path.display());
     Ok(settings)
@@ -138,6 +187,108 @@ fn validate_settings(settings: &Settings) -> Result<()> {
     Ok(())
 }
 
+/// Environment variables overriding secret settings, with the field each sets.
+/// Overrides are applied by [`load_settings`] and never saved.
+pub const SECRET_ENV_VARS: [(&str, &str); 7] = [
+    ("TINYVEGETA_TELEGRAM_TOKEN", "channels.telegram.bot_token"),
+    ("TINYVEGETA_TELEGRAM_WEBHOOK_SECRET", "channels.telegram.webhook_secret"),
+    ("TINYVEGETA_DISCORD_TOKEN", "channels.discord.bot_token"),
+    ("TINYVEGETA_ANTHROPIC_API_KEY", "models.anthropic.api_key"),
+    ("TINYVEGETA_OPENAI_API_KEY", "models.openai.api_key"),
+    ("TINYVEGETA_GROK_API_KEY", "models.grok.api_key"),
+    ("TINYVEGETA_WEB_API_TOKEN", "web.api_token"),
+];
+
+/// Secret fields of `settings`, in [`SECRET_ENV_VARS`] order.
+fn secret_fields(settings: &mut Settings) -> [&mut Option<String>; 7] {
+    [
+        &mut settings.channels.telegram.bot_token,
+        &mut settings.channels.telegram.webhook_secret,
+        &mut settings.channels.discord.bot_token,
+        &mut settings.models.anthropic.api_key,
+        &mut settings.models.openai.api_key,
+        &mut settings.models.grok.api_key,
+        &mut settings.web.api_token,
+    ]
+}
+
+fn secret_env_value(var: &str) -> Option<String> {
+    std::env::var(var).ok().filter(|v| !v.trim().is_empty())
+}
+
+/// Overlay secrets from [`SECRET_ENV_VARS`]; set variables win over the file.
+fn apply_secret_env_overrides(settings: &mut Settings) {
+    for ((var, _), field) in SECRET_ENV_VARS.iter().zip(secret_fields(settings)) {
+        if let Some(value) = secret_env_value(var) {
+            *field = Some(value);
+        }
+    }
+}
+
+/// Put back the on-disk value of every secret still holding its environment
+/// override, so overrides never reach the file. Secrets changed since loading
+/// (e.g. by `setup`) are kept.
+fn strip_secret_env_overrides(settings: &mut Settings, on_disk: &mut Settings) {
+    let fields = SECRET_ENV_VARS
+        .iter()
+        .zip(secret_fields(settings))
+        .zip(secret_fields(on_disk));
+    for (((var, _), field), disk_field) in fields {
+        if secret_env_value(var).is_some_and(|v| field.as_deref() == Some(v.as_str())) {
+            *field = disk_field.take();
+        }
+    }
+}
+
+/// Save settings to ~/.tinyvegeta/settings.json, replacing the file atomically.
+/// Secrets taken from the environment are not written.
+pub fn save_settings(settings: &Settings) -> Result<()> {
+    let path = get_settings_path()?;
+    let mut to_write = settings.clone();
+    let mut on_disk: Settings = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    strip_secret_env_overrides(&mut to_write, &mut on_disk);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    atomic_write(&path, serde_json::to_string_pretty(&to_write)?)
+}
+
+/// Replace `path` with `bytes` so readers see the old or the new contents,
+/// never a partial file: write `<path>.tmp`, fsync it, then rename it over
+/// `path`.
+pub fn atomic_write(path: &Path, bytes: impl AsRef<[u8]>) -> Result<()> {
+    use std::io::Write;
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(bytes.as_ref())?;
+        file.sync_all()?;
+    }
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        // Windows can refuse to rename over an existing file (for instance
+        // one another process has open); the complete new contents are in
+        // the temp file, so remove the target and retry once.
+        if !cfg!(windows) || !path.exists() {
+            return Err(e.into());
+        }
+        std::fs::remove_file(path)?;
+        std::fs::rename(&tmp, path)?;
+    }
+    // Persist the rename itself.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().and_then(|d| std::fs::File::open(d).ok()) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
 /// Load settings or return default if not found.
 pub fn load_settings_or_default() -> Settings {
     load_settings().unwrap_or_else(|e| {
@@ -154,9 +305,70 @@ pub struct Workspace {
 }
 
 /// Channel configuration.
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ChannelConfig {
     pub bot_token: Option<String>,
+    /// Longest single message; longer responses are sent as numbered parts.
+    #[serde(default = "default_max_message_chars")]
+    pub max_message_chars: usize,
+    /// Messages one sender may queue per minute; `0` disables the limit.
+    #[serde(default = "default_rate_limit_per_minute")]
+    pub rate_limit_per_minute: u32,
+    /// Sender ids exempt from `rate_limit_per_minute`.
+    #[serde(default)]
+    pub rate_limit_exempt_ids: Vec<String>,
+    /// How the bot receives updates.
+    #[serde(default)]
+    pub mode: TelegramMode,
+    /// Public HTTPS URL Telegram posts updates to in webhook mode, ending in
+    /// `/telegram/webhook`.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Address the webhook listener binds, behind the public URL.
+    #[serde(default = "default_webhook_listen")]
+    pub webhook_listen: String,
+    /// Secret Telegram must echo in `X-Telegram-Bot-Api-Secret-Token`; a
+    /// random one is registered on each start when unset.
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
+}
+
+/// How the Telegram bot receives updates.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TelegramMode {
+    /// Long-poll `getUpdates`.
+    #[default]
+    Polling,
+    /// Register `webhook_url` with `setWebhook` and serve it.
+    Webhook,
+}
+
+fn default_max_message_chars() -> usize {
+    4000
+}
+
+fn default_rate_limit_per_minute() -> u32 {
+    20
+}
+
+fn default_webhook_listen() -> String {
+    "0.0.0.0:8443".to_string()
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            bot_token: None,
+            max_message_chars: default_max_message_chars(),
+            rate_limit_per_minute: default_rate_limit_per_minute(),
+            rate_limit_exempt_ids: Vec::new(),
+            mode: TelegramMode::default(),
+            webhook_url: None,
+            webhook_listen: default_webhook_listen(),
+            webhook_secret: None,
+        }
+    }
 }
 
 /// Channels configuration.
@@ -165,10 +377,50 @@ pub struct Channels {
     pub enabled: Vec<String>,
     #[serde(default)]
     pub telegram: ChannelConfig,
+    #[serde(default)]
+    pub discord: DiscordConfig,
+}
+
+impl Channels {
+    /// Whether `channel` is listed in `enabled`.
+    pub fn is_enabled(&self, channel: &str) -> bool {
+        self.enabled.iter().any(|c| c == channel)
+    }
+}
+
+/// Discord bot settings. The bot polls the allowed channels, so it needs the
+/// Message Content intent enabled in the Discord developer portal.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DiscordConfig {
+    pub bot_token: Option<String>,
+    /// Servers whose channels are read; empty allows any server.
+    #[serde(default)]
+    pub allowed_guild_ids: Vec<String>,
+    /// Channels the bot reads messages from.
+    #[serde(default)]
+    pub allowed_channel_ids: Vec<String>,
+    /// Longest single message; Discord rejects more than 2000 characters.
+    #[serde(default = "default_discord_max_message_chars")]
+    pub max_message_chars: usize,
+}
+
+fn default_discord_max_message_chars() -> usize {
+    2000
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            bot_token: None,
+            allowed_guild_ids: Vec::new(),
+            allowed_channel_ids: Vec::new(),
+            max_message_chars: default_discord_max_message_chars(),
+        }
+    }
 }
 
 /// Agent configuration.
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AgentConfig {
     pub name: Option<String>,
     pub provider: Option<String>,
@@ -176,6 +428,166 @@ pub struct AgentConfig {
     pub working_directory: Option<PathBuf>,
     #[serde(default)]
     pub is_sovereign: bool,
+    /// Tell the agent who it is talking to (sender name, channel, local time).
+    /// Off by default for privacy.
+    #[serde(default)]
+    pub sender_context: bool,
+    /// Disabled agents keep their config, memory and teams but receive no
+    /// messages and sit out board discussions.
+    #[serde(default = "default_agent_enabled")]
+    pub enabled: bool,
+}
+
+pub(crate) fn default_agent_enabled() -> bool {
+    true
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            name: None,
+            provider: None,
+            model: None,
+            working_directory: None,
+            is_sovereign: false,
+            sender_context: false,
+            enabled: default_agent_enabled(),
+        }
+    }
+}
+
+/// Reject an agent whose provider is not one of
+/// [`PROVIDER_NAMES`](crate::providers::PROVIDER_NAMES), or whose model is
+/// blank for an HTTP provider. CLI providers may leave the model as
+/// `"default"`; an unset or empty provider, and an unset model, fall back to
+/// `models.*`.
+pub fn validate_agent(agent: &AgentConfig) -> Result<()> {
+    let Some(provider) = agent.provider.as_deref().filter(|p| !p.is_empty()) else {
+        return Ok(());
+    };
+    if !crate::providers::PROVIDER_NAMES.contains(&provider) {
+        return Err(Error::Config(format!(
+            "unknown provider '{}' (valid providers: {})",
+            provider,
+            crate::providers::PROVIDER_NAMES.join(", ")
+        )));
+    }
+    if !crate::providers::is_cli_provider(provider) && agent.model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+        return Err(Error::Config(format!("provider '{}' needs a model name", provider)));
+    }
+    Ok(())
+}
+
+/// An agent to add with [`create_agent`]; unset fields take the CLI defaults.
+#[derive(Debug, Clone, Default)]
+pub struct NewAgent {
+    pub id: String,
+    pub name: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub working_directory: Option<PathBuf>,
+}
+
+/// Normalise a new agent id and check it is usable: non-empty, made of
+/// `[a-z0-9_-]`, and not already taken by an agent or a team.
+pub fn validate_new_agent_id(settings: &Settings, id: &str) -> Result<String> {
+    let id = id.trim().to_lowercase();
+    if id.is_empty() {
+        return Err(Error::Config("Agent ID is required".to_string()));
+    }
+    if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(Error::Config(format!(
+            "Invalid agent ID '{}': use letters, digits, '-' or '_'",
+            id
+        )));
+    }
+    if settings.agents.contains_key(&id) {
+        return Err(Error::AlreadyExists(format!("Agent already exists: {}", id)));
+    }
+    if settings.teams.contains_key(&id) {
+        return Err(Error::AlreadyExists(format!("Agent ID conflicts with team ID: {}", id)));
+    }
+    Ok(id)
+}
+
+/// Add an agent the same way from every interface: validate the id and
+/// provider ([`validate_agent`]), create its working directory, save
+/// settings and scaffold its context files.
+pub fn create_agent(settings: &mut Settings, new: NewAgent) -> Result<(String, AgentConfig)> {
+    let id = validate_new_agent_id(settings, &new.id)?;
+    let workdir = new
+        .working_directory
+        .unwrap_or_else(|| crate::board::resolve_workspace_root(settings).join(&id));
+
+    let non_empty = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let agent = AgentConfig {
+        name: Some(non_empty(new.name).unwrap_or_else(|| id.clone())),
+        provider: Some(non_empty(new.provider).unwrap_or_else(|| settings.models.provider.clone())),
+        model: Some(non_empty(new.model).unwrap_or_else(|| "default".to_string())),
+        working_directory: Some(workdir.clone()),
+        is_sovereign: false,
+        sender_context: false,
+        enabled: true,
+    };
+    validate_agent(&agent)?;
+    std::fs::create_dir_all(&workdir)?;
+    settings.agents.insert(id.clone(), agent.clone());
+    save_settings(settings)?;
+    // Scaffold after saving so templates can use the agent's name.
+    crate::context::init_agent_context(&id, &workdir)?;
+    Ok((id, agent))
+}
+
+/// Context files [`clone_agent`] copies from the source agent.
+const CLONED_CONTEXT_FILES: &[&str] = &["SOUL.md", "MEMORY.md", "AGENTS.md"];
+
+/// Add `new_id` as a copy of agent `source_id`: same config (provider,
+/// model, flags), a fresh working directory under the workspace with the
+/// source's SOUL.md, MEMORY.md and AGENTS.md, and, with `copy_memory`, its
+/// agent-scoped memory apart from `interaction.*`. Missing context files are
+/// scaffolded from the defaults.
+pub fn clone_agent(
+    settings: &mut Settings,
+    source_id: &str,
+    new_id: &str,
+    copy_memory: bool,
+) -> Result<(String, AgentConfig)> {
+    let source = settings
+        .agents
+        .get(source_id)
+        .cloned()
+        .ok_or_else(|| Error::NotFound(format!("agent {}", source_id)))?;
+    validate_agent(&source)?;
+    let id = validate_new_agent_id(settings, new_id)?;
+    let workdir = crate::board::resolve_workspace_root(settings).join(&id);
+    std::fs::create_dir_all(&workdir)?;
+    if let Some(source_dir) = source.working_directory.as_ref() {
+        for name in CLONED_CONTEXT_FILES {
+            let from = source_dir.join(name);
+            if from.is_file() {
+                std::fs::copy(&from, workdir.join(name))?;
+            }
+        }
+    }
+
+    let agent = AgentConfig {
+        name: Some(id.clone()),
+        working_directory: Some(workdir.clone()),
+        ..source
+    };
+    settings.agents.insert(id.clone(), agent.clone());
+    save_settings(settings)?;
+    crate::context::init_agent_context(&id, &workdir)?;
+
+    if copy_memory {
+        let mut store = crate::memory::store::load_store(&crate::memory::MemoryScope::Agent, Some(source_id))?;
+        store.entries.retain(|key, _| !key.starts_with("interaction."));
+        for entry in store.entries.values_mut() {
+            entry.scope_id = Some(id.clone());
+        }
+        crate::memory::store::save_store(&crate::memory::MemoryScope::Agent, Some(&id), &store)?;
+    }
+    Ok((id, agent))
 }
 
 /// Team configuration.
@@ -195,7 +607,7 @@ pub struct ProviderModel {
 }
 
 /// Models configuration.
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Models {
     #[serde(default)]
     pub provider: String,
@@ -207,6 +619,104 @@ pub struct Models {
     pub grok: ProviderModel,
     #[serde(default)]
     pub ollama: ProviderModel,
+    /// Estimated cost (USD) per 1k prompt tokens, used by `agent usage`.
+    #[serde(default)]
+    pub cost_per_1k_prompt_tokens: Option<f64>,
+    /// Estimated cost (USD) per 1k completion tokens, used by `agent usage`.
+    #[serde(default)]
+    pub cost_per_1k_completion_tokens: Option<f64>,
+    /// Retries of transient HTTP provider errors (network, 429, 5xx).
+    #[serde(default = "default_models_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_models_max_retries() -> u32 {
+    2
+}
+
+impl Default for Models {
+    fn default() -> Self {
+        Self {
+            provider: String::new(),
+            openai: ProviderModel::default(),
+            anthropic: ProviderModel::default(),
+            grok: ProviderModel::default(),
+            ollama: ProviderModel::default(),
+            cost_per_1k_prompt_tokens: None,
+            cost_per_1k_completion_tokens: None,
+            max_retries: default_models_max_retries(),
+        }
+    }
+}
+
+/// Ollama provider options.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OllamaOptions {
+    /// Pull missing models automatically before retrying a completion.
+    #[serde(default)]
+    pub auto_pull: bool,
+}
+
+/// Provider circuit breaker options.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CircuitBreakerOptions {
+    /// Consecutive failures that open a provider's circuit; `0` disables it.
+    #[serde(default = "default_circuit_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Seconds an open circuit rejects work before the provider is tried again.
+    #[serde(default = "default_circuit_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+fn default_circuit_failure_threshold() -> u32 {
+    3
+}
+
+fn default_circuit_cooldown_secs() -> u64 {
+    300
+}
+
+impl Default for CircuitBreakerOptions {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_circuit_failure_threshold(),
+            cooldown_secs: default_circuit_cooldown_secs(),
+        }
+    }
+}
+
+/// Provider runtime options.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Providers {
+    #[serde(default)]
+    pub ollama: OllamaOptions,
+    #[serde(default)]
+    pub circuit: CircuitBreakerOptions,
+}
+
+/// Web server configuration.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Web {
+    /// Bearer token required for `/api/*` routes. Unset leaves the API open.
+    #[serde(default)]
+    pub api_token: Option<String>,
+
+    /// How long an `Idempotency-Key` and its response are remembered.
+    #[serde(default = "default_web_idempotency_window_secs")]
+    pub idempotency_window_secs: u64,
+}
+
+fn default_web_idempotency_window_secs() -> u64 {
+    24 * 60 * 60
+}
+
+impl Default for Web {
+    fn default() -> Self {
+        Self {
+            api_token: None,
+            idempotency_window_secs: default_web_idempotency_window_secs(),
+        }
+    }
 }
 
 /// Pairing configuration.
@@ -217,6 +727,9 @@ pub struct Pairing {
     pub approved_senders: Option<Vec<ApprovedSender>>,
     pub pending_senders: Option<Vec<PendingSender>>,
     pub soul_owner_sender_id: Option<String>,
+    /// Sender ids approved on first contact, without a pairing code.
+    #[serde(default)]
+    pub auto_approve_ids: Vec<String>,
 }
 
 fn default_pairing_mode() -> String {
@@ -241,90 +754,368 @@ pub struct PendingSender {
 }
 
 /// Monitoring configuration.
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Monitoring {
     #[serde(default = "default_heartbeat_interval")]
     pub heartbeat_interval: u64,
+
+    /// Days rotated log files are kept; older ones are deleted on startup (0 keeps all).
+    #[serde(default = "default_log_retention_days")]
+    pub log_retention_days: u64,
+
+    /// Size at which the log file is rotated, besides the daily rotation.
+    #[serde(default = "default_log_rotate_bytes")]
+    pub log_rotate_bytes: u64,
+
+    /// Heartbeat runs a full `VACUUM` once the sqlite db exceeds this many MB (0 disables).
+    #[serde(default = "default_sqlite_vacuum_mb")]
+    pub sqlite_vacuum_mb: u64,
+
+    /// Hours between light sqlite maintenance runs (`incremental_vacuum` +
+    /// `ANALYZE`) from the heartbeat (0 disables).
+    #[serde(default)]
+    pub sqlite_maintenance_hours: u64,
+
+    /// Daily window in which autonomous actions (doctor --fix, schedules,
+    /// sovereign cycles) are held back; message processing continues.
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+
+    /// A heartbeat health score below this alerts the SOUL owner (or every
+    /// approved sender) on Telegram (0 disables).
+    #[serde(default = "default_health_alert_threshold")]
+    pub health_alert_threshold: i32,
+
+    /// Minimum minutes between two health alerts.
+    #[serde(default = "default_alert_cooldown_minutes")]
+    pub alert_cooldown_minutes: u64,
 }
 
-fn default_heartbeat_interval() -> u64 {
-    3600
+impl Monitoring {
+    /// Whether `now` falls within `quiet_hours`.
+    pub fn in_quiet_hours(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.quiet_hours.as_ref().is_some_and(|q| q.contains(now))
+    }
 }
 
-/// Board configuration.
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
-pub struct Board {
-    pub team_id: Option<String>,
-    pub autonomous: Option<bool>,
-    pub schedules: Option<Vec<BoardSchedule>>,
+/// A daily `start`..`end` window; an `end` before `start` spans midnight.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct QuietHours {
+    /// `HH:MM`, inclusive.
+    pub start: String,
+    /// `HH:MM`, exclusive.
+    pub end: String,
+    /// `local` (the host's zone), `UTC`, or a fixed offset such as `+02:00`.
+    #[serde(default = "default_quiet_hours_timezone")]
+    pub timezone: String,
 }
 
-/// Routing configuration.
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
-pub struct Routing {
-    pub default_agent: Option<String>,
+fn default_quiet_hours_timezone() -> String {
+    "local".to_string()
 }
 
-/// Sovereign runtime configuration.
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct Sovereign {
-    #[serde(default = "default_sovereign_enabled")]
-    pub enabled: bool,
-    pub constitution_path: Option<PathBuf>,
-    #[serde(default)]
-    pub protected_files: Vec<String>,
-    #[serde(default = "default_sovereign_loop_sleep_seconds")]
-    pub loop_sleep_seconds: u64,
-    #[serde(default = "default_sovereign_max_actions_per_cycle")]
-    pub max_actions_per_cycle: u32,
-    #[serde(default = "default_sovereign_max_self_modifications_per_hour")]
-    pub max_self_modifications_per_hour: u32,
-    #[serde(default = "default_sovereign_allow_tool_install")]
-    pub allow_tool_install: bool,
-    #[serde(default = "default_sovereign_allow_self_modify")]
-    pub allow_self_modify: bool,
+impl QuietHours {
+    /// Whether `now` is inside the window. Invalid settings never match;
+    /// `config validate` reports them.
+    pub fn contains(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        let Ok((start, end, offset)) = self.parse() else {
+            return false;
+        };
+        let time = match offset {
+            Some(offset) => now.with_timezone(&offset).time(),
+            None => now.with_timezone(&chrono::Local).time(),
+        };
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+
+    /// Start, end and the fixed offset (`None` for the local zone).
+    pub fn parse(&self) -> std::result::Result<(chrono::NaiveTime, chrono::NaiveTime, Option<chrono::FixedOffset>), String> {
+        let time = |t: &str| {
+            chrono::NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .map_err(|_| format!("invalid time '{}', expected HH:MM", t))
+        };
+        Ok((time(&self.start)?, time(&self.end)?, parse_timezone(&self.timezone)?))
+    }
 }
 
-fn default_sovereign_enabled() -> bool {
-    false
+fn parse_timezone(tz: &str) -> std::result::Result<Option<chrono::FixedOffset>, String> {
+    let tz = tz.trim();
+    if tz.is_empty() || tz.eq_ignore_ascii_case("local") {
+        return Ok(None);
+    }
+    if tz.eq_ignore_ascii_case("utc") || tz == "Z" {
+        return Ok(chrono::FixedOffset::east_opt(0));
+    }
+    let invalid = || format!("invalid timezone '{}', expected local, UTC or +HH:MM", tz);
+    let (sign, rest) = tz
+        .strip_prefix('+')
+        .map(|r| (1, r))
+        .or_else(|| tz.strip_prefix('-').map(|r| (-1, r)))
+        .ok_or_else(invalid)?;
+    let (h, m) = rest.split_once(':').ok_or_else(invalid)?;
+    let (h, m): (i32, i32) = (h.parse().map_err(|_| invalid())?, m.parse().map_err(|_| invalid())?);
+    if !(0..=14).contains(&h) || !(0..60).contains(&m) {
+        return Err(invalid());
+    }
+    chrono::FixedOffset::east_opt(sign * (h * 3600 + m * 60))
+        .map(Some)
+        .ok_or_else(invalid)
 }
 
-fn default_sovereign_loop_sleep_seconds() -> u64 {
-    20
+fn default_heartbeat_interval() -> u64 {
+    3600
 }
 
-fn default_sovereign_max_actions_per_cycle() -> u32 {
-    3
+fn default_log_retention_days() -> u64 {
+    7
 }
 
-fn default_sovereign_max_self_modifications_per_hour() -> u32 {
-    6
+fn default_log_rotate_bytes() -> u64 {
+    10 * 1024 * 1024
 }
 
-fn default_sovereign_allow_tool_install() -> bool {
-    true
+fn default_sqlite_vacuum_mb() -> u64 {
+    100
 }
 
-fn default_sovereign_allow_self_modify() -> bool {
-    true
+fn default_health_alert_threshold() -> i32 {
+    60
 }
 
-impl Default for Sovereign {
+fn default_alert_cooldown_minutes() -> u64 {
+    60
+}
+
+impl Default for Monitoring {
     fn default() -> Self {
         Self {
-            enabled: default_sovereign_enabled(),
-            constitution_path: None,
-            protected_files: Vec::new(),
-            loop_sleep_seconds: default_sovereign_loop_sleep_seconds(),
-            max_actions_per_cycle: default_sovereign_max_actions_per_cycle(),
-            max_self_modifications_per_hour: default_sovereign_max_self_modifications_per_hour(),
-            allow_tool_install: default_sovereign_allow_tool_install(),
-            allow_self_modify: default_sovereign_allow_self_modify(),
+            heartbeat_interval: default_heartbeat_interval(),
+            log_retention_days: default_log_retention_days(),
+            log_rotate_bytes: default_log_rotate_bytes(),
+            sqlite_vacuum_mb: default_sqlite_vacuum_mb(),
+            sqlite_maintenance_hours: 0,
+            quiet_hours: None,
+            health_alert_threshold: default_health_alert_threshold(),
+            alert_cooldown_minutes: default_alert_cooldown_minutes(),
         }
     }
 }
 
-/// Board schedule.
+/// Board configuration.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Board {
+    pub team_id: Option<String>,
+    pub autonomous: Option<bool>,
+    pub schedules: Option<Vec<BoardSchedule>>,
+    /// Members asked at once during a board discussion.
+    #[serde(default = "default_board_max_concurrency")]
+    pub max_concurrency: usize,
+}
+
+fn default_board_max_concurrency() -> usize {
+    4
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            team_id: None,
+            autonomous: None,
+            schedules: None,
+            max_concurrency: default_board_max_concurrency(),
+        }
+    }
+}
+
+/// Routing configuration.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Routing {
+    pub default_agent: Option<String>,
+    /// What to do when a message targets an `@id` that is neither an agent nor a team.
+    #[serde(default)]
+    pub unknown_target: UnknownTargetPolicy,
+    /// Agents tried in order when `default_agent` is unset or missing.
+    #[serde(default)]
+    pub fallback_agents: Vec<String>,
+    /// Checked by priority before intent routing for messages without an `@target`.
+    #[serde(default)]
+    pub rules: Vec<RoutingRule>,
+}
+
+impl Routing {
+    /// Rules in the order they are tried: highest `priority` first, equal
+    /// priorities in configured order.
+    pub fn rules_by_priority(&self) -> Vec<&RoutingRule> {
+        let mut rules: Vec<&RoutingRule> = self.rules.iter().collect();
+        rules.sort_by_key(|r| std::cmp::Reverse(r.priority));
+        rules
+    }
+}
+
+/// Sends messages matching `pattern` to `agent`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RoutingRule {
+    /// Regex matched case-insensitively against the message text.
+    pub pattern: String,
+    pub agent: String,
+    /// Higher priorities are tried first.
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl RoutingRule {
+    /// The compiled pattern; `None` if it is not a valid regex.
+    pub fn regex(&self) -> Option<regex::Regex> {
+        self.compile().ok()
+    }
+
+    fn compile(&self) -> std::result::Result<regex::Regex, regex::Error> {
+        regex::RegexBuilder::new(&self.pattern).case_insensitive(true).build()
+    }
+}
+
+/// Handling of messages addressed to an unknown agent or team.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownTargetPolicy {
+    /// Enqueue silently; the daemon falls back to the default agent.
+    #[default]
+    Ignore,
+    /// Enqueue, but tell the sender the target was not found.
+    Warn,
+    /// Refuse to enqueue.
+    Reject,
+}
+
+/// Prompt-injection scanning of incoming channel messages.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct InjectionConfig {
+    #[serde(default)]
+    pub mode: InjectionMode,
+    /// Additional regexes, checked alongside the built-in patterns.
+    #[serde(default)]
+    pub extra_patterns: Vec<String>,
+}
+
+/// What happens to a message that matches an injection pattern.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectionMode {
+    /// No scanning.
+    #[default]
+    Off,
+    /// Log and tag the message; the agent is told to treat it as untrusted.
+    Flag,
+    /// As `flag`, and strip the matched text.
+    Sanitize,
+    /// Park the message in `queue/held` until an admin releases it.
+    Hold,
+}
+
+/// Sovereign runtime configuration.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Sovereign {
+    #[serde(default = "default_sovereign_enabled")]
+    pub enabled: bool,
+    pub constitution_path: Option<PathBuf>,
+    #[serde(default)]
+    pub protected_files: Vec<String>,
+    #[serde(default = "default_sovereign_loop_sleep_seconds")]
+    pub loop_sleep_seconds: u64,
+    #[serde(default = "default_sovereign_max_actions_per_cycle")]
+    pub max_actions_per_cycle: u32,
+    #[serde(default = "default_sovereign_max_self_modifications_per_hour")]
+    pub max_self_modifications_per_hour: u32,
+    #[serde(default = "default_sovereign_allow_tool_install")]
+    pub allow_tool_install: bool,
+    #[serde(default = "default_sovereign_allow_self_modify")]
+    pub allow_self_modify: bool,
+    /// Allow the loop to create new agents via `replicate_agent`.
+    #[serde(default = "default_sovereign_allow_replication")]
+    pub allow_replication: bool,
+    /// Maximum number of sovereign-created agents.
+    #[serde(default = "default_sovereign_max_agents")]
+    pub max_agents: u32,
+    /// Extra regexes blocking shell actions, merged with the built-in dangerous patterns.
+    #[serde(default)]
+    pub shell_denylist: Vec<String>,
+    /// When non-empty, every command's first token must be one of these programs.
+    #[serde(default)]
+    pub shell_allowlist: Vec<String>,
+    /// Queue planned actions under `sovereign/pending/` until approved.
+    #[serde(default = "default_sovereign_require_approval")]
+    pub require_approval: bool,
+    /// Reject file writes and shell `cd`s that resolve outside the agent's working directory.
+    #[serde(default = "default_sovereign_confine_to_workspace")]
+    pub confine_to_workspace: bool,
+}
+
+fn default_sovereign_enabled() -> bool {
+    false
+}
+
+fn default_sovereign_loop_sleep_seconds() -> u64 {
+    20
+}
+
+fn default_sovereign_max_actions_per_cycle() -> u32 {
+    3
+}
+
+fn default_sovereign_max_self_modifications_per_hour() -> u32 {
+    6
+}
+
+fn default_sovereign_allow_tool_install() -> bool {
+    true
+}
+
+fn default_sovereign_allow_self_modify() -> bool {
+    true
+}
+
+fn default_sovereign_allow_replication() -> bool {
+    false
+}
+
+fn default_sovereign_max_agents() -> u32 {
+    3
+}
+
+fn default_sovereign_require_approval() -> bool {
+    false
+}
+
+fn default_sovereign_confine_to_workspace() -> bool {
+    true
+}
+
+impl Default for Sovereign {
+    fn default() -> Self {
+        Self {
+            enabled: default_sovereign_enabled(),
+            constitution_path: None,
+            protected_files: Vec::new(),
+            loop_sleep_seconds: default_sovereign_loop_sleep_seconds(),
+            max_actions_per_cycle: default_sovereign_max_actions_per_cycle(),
+            max_self_modifications_per_hour: default_sovereign_max_self_modifications_per_hour(),
+            allow_tool_install: default_sovereign_allow_tool_install(),
+            allow_self_modify: default_sovereign_allow_self_modify(),
+            allow_replication: default_sovereign_allow_replication(),
+            max_agents: default_sovereign_max_agents(),
+            require_approval: default_sovereign_require_approval(),
+            confine_to_workspace: default_sovereign_confine_to_workspace(),
+            shell_denylist: Vec::new(),
+            shell_allowlist: Vec::new(),
+        }
+    }
+}
+
+/// Board schedule.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BoardSchedule {
     pub id: String,
@@ -334,6 +1125,227 @@ pub struct BoardSchedule {
     pub agent_id: Option<String>,
     pub sender_id: Option<String>,
     pub enabled: bool,
+    /// Optional cron expression; when set it takes precedence over `time`.
+    #[serde(default)]
+    pub cron: Option<String>,
+}
+
+/// Per-conversation dialogue history injected into prompts.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConversationConfig {
+    /// User/agent exchanges kept and injected; `0` disables history.
+    #[serde(default = "default_conversation_history_turns")]
+    pub history_turns: usize,
+    /// Cap on injected history characters.
+    #[serde(default = "default_conversation_max_history_chars")]
+    pub max_history_chars: usize,
+}
+
+fn default_conversation_history_turns() -> usize {
+    6
+}
+
+fn default_conversation_max_history_chars() -> usize {
+    6000
+}
+
+impl Default for ConversationConfig {
+    fn default() -> Self {
+        Self {
+            history_turns: default_conversation_history_turns(),
+            max_history_chars: default_conversation_max_history_chars(),
+        }
+    }
+}
+
+/// Agent context scaffolding.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ContextConfig {
+    /// Directory of `SOUL.md`/`MEMORY.md`/... templates used instead of the
+    /// built-in ones; `{agent_id}` and `{agent_name}` are substituted.
+    #[serde(default)]
+    pub templates_dir: Option<PathBuf>,
+}
+
+/// How much response text is kept in memory records and snippets.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MemoryConfig {
+    /// Per-agent snippet of delegation results appended to a leader's reply.
+    #[serde(default = "default_memory_snippet_chars")]
+    pub snippet_chars: usize,
+    /// Response text stored as `interaction.last_response`.
+    #[serde(default = "default_memory_response_chars")]
+    pub response_chars: usize,
+    /// Delegation output stored in team `delegation.<id>` records.
+    #[serde(default = "default_memory_delegation_chars")]
+    pub delegation_chars: usize,
+    /// Per-day decay of importance when ranking retrieved memory; `0` disables it.
+    #[serde(default = "default_memory_decay_rate")]
+    pub decay_rate: f32,
+    /// Memory candidates injected into each prompt, per scope.
+    #[serde(default)]
+    pub injection_limits: InjectionLimits,
+    /// Hours `interaction.last_*` entries live after each write; `0` keeps them forever.
+    #[serde(default = "default_memory_interaction_ttl_hours")]
+    pub interaction_ttl_hours: u64,
+    /// Days daily `auto-*` memory snapshots are kept; `0` keeps them forever.
+    #[serde(default = "default_memory_snapshot_retention_days")]
+    pub snapshot_retention_days: u64,
+    /// Keep each entry's earlier values (see `memory history`).
+    #[serde(default)]
+    pub track_history: bool,
+}
+
+impl MemoryConfig {
+    /// TTL for interaction entries, as passed to `Memory::set_with_ttl`.
+    pub fn interaction_ttl_ms(&self) -> Option<i64> {
+        (self.interaction_ttl_hours > 0).then(|| self.interaction_ttl_hours as i64 * 3_600_000)
+    }
+}
+
+/// Retrieved memory entries injected per scope.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InjectionLimits {
+    #[serde(default = "default_injection_limit_global")]
+    pub global: usize,
+    #[serde(default = "default_injection_limit_agent")]
+    pub agent: usize,
+    #[serde(default = "default_injection_limit_team")]
+    pub team: usize,
+}
+
+fn default_injection_limit_global() -> usize {
+    4
+}
+
+fn default_injection_limit_agent() -> usize {
+    6
+}
+
+fn default_injection_limit_team() -> usize {
+    6
+}
+
+impl Default for InjectionLimits {
+    fn default() -> Self {
+        Self {
+            global: default_injection_limit_global(),
+            agent: default_injection_limit_agent(),
+            team: default_injection_limit_team(),
+        }
+    }
+}
+
+fn default_memory_snippet_chars() -> usize {
+    700
+}
+
+fn default_memory_response_chars() -> usize {
+    2000
+}
+
+fn default_memory_delegation_chars() -> usize {
+    1500
+}
+
+fn default_memory_decay_rate() -> f32 {
+    0.01
+}
+
+fn default_memory_interaction_ttl_hours() -> u64 {
+    72
+}
+
+fn default_memory_snapshot_retention_days() -> u64 {
+    14
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            snippet_chars: default_memory_snippet_chars(),
+            response_chars: default_memory_response_chars(),
+            delegation_chars: default_memory_delegation_chars(),
+            decay_rate: default_memory_decay_rate(),
+            injection_limits: InjectionLimits::default(),
+            interaction_ttl_hours: default_memory_interaction_ttl_hours(),
+            snapshot_retention_days: default_memory_snapshot_retention_days(),
+            track_history: false,
+        }
+    }
+}
+
+/// How `start`/`stop` run the daemon.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DaemonConfig {
+    #[serde(default)]
+    pub backend: DaemonBackend,
+}
+
+/// Process that hosts `start-internal`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonBackend {
+    /// A detached tmux session that can be attached to.
+    #[default]
+    Tmux,
+    /// A background child process tracked by `daemon.pid`; needs no tmux.
+    Process,
+}
+
+/// Queue processor tuning.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QueueConfig {
+    /// Messages processed at once; one conversation still runs serially.
+    #[serde(default = "default_queue_max_concurrency")]
+    pub max_concurrency: usize,
+
+    /// Messages in `processing/` longer than this with no running task are
+    /// requeued by the queue processor's periodic pass (0 disables).
+    #[serde(default = "default_queue_orphan_timeout_secs")]
+    pub orphan_timeout_secs: u64,
+
+    /// Heartbeat warns about queue pressure above this many queued messages.
+    #[serde(default = "default_queue_pressure_warn_threshold")]
+    pub pressure_warn_threshold: usize,
+
+    /// Largest message text accepted into the queue, in bytes (0 disables).
+    #[serde(default = "default_queue_max_message_bytes")]
+    pub max_message_bytes: usize,
+}
+
+impl QueueConfig {
+    /// `orphan_timeout_secs` as a duration; `None` when disabled.
+    pub fn orphan_timeout(&self) -> Option<std::time::Duration> {
+        (self.orphan_timeout_secs > 0).then(|| std::time::Duration::from_secs(self.orphan_timeout_secs))
+    }
+}
+
+fn default_queue_max_concurrency() -> usize {
+    3
+}
+
+fn default_queue_orphan_timeout_secs() -> u64 {
+    15 * 60
+}
+
+fn default_queue_pressure_warn_threshold() -> usize {
+    50
+}
+
+fn default_queue_max_message_bytes() -> usize {
+    256 * 1024
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: default_queue_max_concurrency(),
+            orphan_timeout_secs: default_queue_orphan_timeout_secs(),
+            pressure_warn_threshold: default_queue_pressure_warn_threshold(),
+            max_message_bytes: default_queue_max_message_bytes(),
+        }
+    }
 }
 
 /// TinyVegeta settings.
@@ -354,6 +1366,9 @@ pub struct Settings {
     #[serde(default)]
     pub models: Models,
 
+    #[serde(default)]
+    pub providers: Providers,
+
     #[serde(default)]
     pub pairing: Pairing,
 
@@ -368,6 +1383,27 @@ pub struct Settings {
 
     #[serde(default)]
     pub sovereign: Sovereign,
+
+    #[serde(default)]
+    pub web: Web,
+
+    #[serde(default)]
+    pub memory: MemoryConfig,
+
+    #[serde(default)]
+    pub conversation: ConversationConfig,
+
+    #[serde(default)]
+    pub context: ContextConfig,
+
+    #[serde(default)]
+    pub injection: InjectionConfig,
+
+    #[serde(default)]
+    pub queue: QueueConfig,
+
+    #[serde(default)]
+    pub daemon: DaemonConfig,
 }
 
 impl Default for Settings {
@@ -378,11 +1414,650 @@ impl Default for Settings {
             agents: HashMap::new(),
             teams: HashMap::default(),
             models: Models::default(),
+            providers: Providers::default(),
             pairing: Pairing::default(),
             monitoring: Monitoring::default(),
             board: Board::default(),
             routing: Routing::default(),
             sovereign: Sovereign::default(),
+            web: Web::default(),
+            memory: MemoryConfig::default(),
+            conversation: ConversationConfig::default(),
+            context: ContextConfig::default(),
+            injection: InjectionConfig::default(),
+            queue: QueueConfig::default(),
+            daemon: DaemonConfig::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Whether `id` is a configured agent that is not disabled.
+    pub fn is_agent_enabled(&self, id: &str) -> bool {
+        self.agents.get(id).is_some_and(|a| a.enabled)
+    }
+
+    /// Append a routing rule after checking its pattern compiles, its agent
+    /// exists and no rule has the same pattern.
+    pub fn add_routing_rule(&mut self, rule: RoutingRule) -> Result<()> {
+        if let Err(e) = rule.compile() {
+            return Err(Error::Config(format!("invalid pattern '{}': {}", rule.pattern, e)));
+        }
+        if !self.agents.contains_key(&rule.agent) {
+            return Err(Error::Config(format!("unknown agent: {}", rule.agent)));
+        }
+        if self.routing.rules.iter().any(|r| r.pattern == rule.pattern) {
+            return Err(Error::AlreadyExists(format!("routing rule /{}/", rule.pattern)));
+        }
+        self.routing.rules.push(rule);
+        Ok(())
+    }
+
+    /// Ids configured as both an agent and a team (sorted).
+    ///
+    /// Routing resolves agents first, so such a team can never be addressed.
+    pub fn conflicting_agent_team_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .agents
+            .keys()
+            .filter(|id| self.teams.contains_key(*id))
+            .cloned()
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// A copy with every secret (see [`SECRET_ENV_VARS`]) that is set masked.
+    pub fn redacted(&self) -> Settings {
+        let mut settings = self.clone();
+        for field in secret_fields(&mut settings).into_iter().filter(|f| f.is_some()) {
+            *field = Some("********".to_string());
+        }
+        settings
+    }
+
+    /// Check cross-references and values that deserialization cannot catch.
+    /// Errors are sorted by path.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let mut push = |kind, path: String, message: String| {
+            errors.push(ValidationError { kind, path, message })
+        };
+        let known_provider = |name: &str| crate::providers::PROVIDER_NAMES.contains(&name);
+
+        if !self.models.provider.is_empty() && !known_provider(&self.models.provider) {
+            push(
+                ValidationKind::UnknownProvider,
+                "/models/provider".to_string(),
+                format!("unknown provider '{}'", self.models.provider),
+            );
+        }
+
+        let workspace_root = crate::board::resolve_workspace_root(self);
+        for (id, agent) in &self.agents {
+            if let Some(provider) = agent.provider.as_deref().filter(|p| !known_provider(p)) {
+                push(
+                    ValidationKind::UnknownProvider,
+                    format!("/agents/{}/provider", pointer_token(id)),
+                    format!("unknown provider '{}'", provider),
+                );
+            }
+            if let Some(dir) = agent.working_directory.as_ref().filter(|d| !d.starts_with(&workspace_root)) {
+                push(
+                    ValidationKind::OutsideWorkspace,
+                    format!("/agents/{}/working_directory", pointer_token(id)),
+                    format!("'{}' is not under workspace root '{}'", dir.display(), workspace_root.display()),
+                );
+            }
+        }
+
+        for (id, team) in &self.teams {
+            if let Some(leader) = team.leader_agent.as_deref().filter(|l| !team.agents.iter().any(|a| a == l)) {
+                push(
+                    ValidationKind::LeaderNotMember,
+                    format!("/teams/{}/leader_agent", pointer_token(id)),
+                    format!("leader '{}' is not a member of team '{}'", leader, id),
+                );
+            }
+        }
+
+        if let Some(team_id) = self.board.team_id.as_deref().filter(|t| !self.teams.contains_key(*t)) {
+            push(
+                ValidationKind::UnknownTeam,
+                "/board/team_id".to_string(),
+                format!("team '{}' does not exist", team_id),
+            );
+        }
+
+        if let Some(agent_id) = self.routing.default_agent.as_deref().filter(|a| !self.agents.contains_key(*a)) {
+            push(
+                ValidationKind::UnknownAgent,
+                "/routing/default_agent".to_string(),
+                format!("agent '{}' does not exist", agent_id),
+            );
+        }
+
+        for (i, agent_id) in self.routing.fallback_agents.iter().enumerate() {
+            if !self.agents.contains_key(agent_id) {
+                push(
+                    ValidationKind::UnknownAgent,
+                    format!("/routing/fallback_agents/{}", i),
+                    format!("agent '{}' does not exist", agent_id),
+                );
+            }
+        }
+        for (i, rule) in self.routing.rules.iter().enumerate() {
+            if !self.agents.contains_key(&rule.agent) {
+                push(
+                    ValidationKind::UnknownAgent,
+                    format!("/routing/rules/{}/agent", i),
+                    format!("agent '{}' does not exist", rule.agent),
+                );
+            }
+            if let Err(e) = regex::Regex::new(&rule.pattern) {
+                push(
+                    ValidationKind::InvalidPattern,
+                    format!("/routing/rules/{}/pattern", i),
+                    format!("invalid regex '{}': {}", rule.pattern, e),
+                );
+            }
+            if let Some(first) = self.routing.rules[..i].iter().position(|r| r.pattern == rule.pattern) {
+                push(
+                    ValidationKind::DuplicateRule,
+                    format!("/routing/rules/{}", i),
+                    format!("pattern '{}' is already used by rule {}, so this rule never matches", rule.pattern, first),
+                );
+            }
+        }
+
+        for (i, schedule) in self.board.schedules.iter().flatten().enumerate() {
+            match schedule.cron.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+                Some(cron) => {
+                    if let Err(e) = crate::heartbeat::scheduler::parse_cron_expr(cron) {
+                        push(
+                            ValidationKind::InvalidSchedule,
+                            format!("/board/schedules/{}/cron", i),
+                            format!("invalid cron '{}': {}", cron, e),
+                        );
+                    }
+                }
+                None => {
+                    if crate::board::normalize_schedule_time(&schedule.time).is_err() {
+                        push(
+                            ValidationKind::InvalidSchedule,
+                            format!("/board/schedules/{}/time", i),
+                            format!("invalid time '{}', expected HH:MM", schedule.time),
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(Err(e)) = self.monitoring.quiet_hours.as_ref().map(QuietHours::parse) {
+            push(ValidationKind::InvalidSchedule, "/monitoring/quiet_hours".to_string(), e);
         }
+
+        errors.sort_by(|a, b| a.path.cmp(&b.path));
+        errors
+    }
+}
+
+/// Kind of problem found by [`Settings::validate`].
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationKind {
+    UnknownProvider,
+    LeaderNotMember,
+    UnknownTeam,
+    UnknownAgent,
+    InvalidSchedule,
+    OutsideWorkspace,
+    InvalidPattern,
+    DuplicateRule,
+}
+
+/// One settings problem, located by a JSON pointer into `settings.json`.
+#[derive(Serialize, Clone, Debug)]
+pub struct ValidationError {
+    pub kind: ValidationKind,
+    pub path: String,
+    pub message: String,
+}
+
+/// Escape a key for use as a JSON pointer token (RFC 6901).
+fn pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+    use std::sync::{Mutex, MutexGuard};
+
+    static HOME_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Points `HOME` at a temp dir for the guard's lifetime.
+    ///
+    /// Tests touching `~/.tinyvegeta` must hold one, since `HOME` is process-wide.
+    pub(crate) struct TempHome {
+        dir: tempfile::TempDir,
+        prev: Option<OsString>,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl TempHome {
+        pub(crate) fn new() -> Self {
+            let lock = HOME_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let dir = tempfile::tempdir().unwrap();
+            let prev = std::env::var_os("HOME");
+            std::env::set_var("HOME", dir.path());
+            std::fs::create_dir_all(dir.path().join(".tinyvegeta")).unwrap();
+            Self { dir, prev, _lock: lock }
+        }
+
+        /// The `~/.tinyvegeta` directory inside the temp home.
+        pub(crate) fn tinyvegeta_dir(&self) -> PathBuf {
+            self.dir.path().join(".tinyvegeta")
+        }
+    }
+
+    impl Drop for TempHome {
+        fn drop(&mut self) {
+            match self.prev.take() {
+                Some(prev) => std::env::set_var("HOME", prev),
+                None => std::env::remove_var("HOME"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        atomic_write, clone_agent, create_agent, get_home_dir, get_settings_path, list_profiles, load_settings, save_settings,
+        validate_agent, AgentConfig, BoardSchedule, NewAgent, QuietHours, RoutingRule, Settings, TeamConfig, ValidationKind,
+        PROFILE_ENV,
+    };
+    use crate::config::test_support::TempHome;
+    use std::path::PathBuf;
+
+    fn valid_settings() -> Settings {
+        let mut settings = Settings::default();
+        settings.workspace.path = Some(PathBuf::from("/ws"));
+        settings.models.provider = "claude".to_string();
+        settings.agents.insert(
+            "coder".to_string(),
+            AgentConfig {
+                provider: Some("ollama".to_string()),
+                working_directory: Some(PathBuf::from("/ws/coder")),
+                ..Default::default()
+            },
+        );
+        settings.teams.insert(
+            "dev".to_string(),
+            TeamConfig {
+                name: "Dev".to_string(),
+                agents: vec!["coder".to_string()],
+                leader_agent: Some("coder".to_string()),
+            },
+        );
+        settings.board.team_id = Some("dev".to_string());
+        settings.routing.default_agent = Some("coder".to_string());
+        settings
+    }
+
+    fn schedule(time: &str, cron: Option<&str>) -> BoardSchedule {
+        BoardSchedule {
+            id: "s".to_string(),
+            schedule_type: "digest".to_string(),
+            time: time.to_string(),
+            team_id: None,
+            agent_id: None,
+            sender_id: None,
+            enabled: true,
+            cron: cron.map(String::from),
+        }
+    }
+
+    fn single(settings: &Settings) -> (ValidationKind, String) {
+        let errors = settings.validate();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        (errors[0].kind, errors[0].path.clone())
+    }
+
+    #[test]
+    fn valid_settings_have_no_errors() {
+        let mut settings = valid_settings();
+        settings.board.schedules = Some(vec![schedule("09:00", None), schedule("bogus", Some("0 9 * * 1-5"))]);
+        assert!(settings.validate().is_empty(), "{:?}", settings.validate());
+    }
+
+    #[test]
+    fn unknown_providers_are_reported() {
+        let mut settings = valid_settings();
+        settings.agents.get_mut("coder").unwrap().provider = Some("gpt".to_string());
+        assert_eq!(single(&settings), (ValidationKind::UnknownProvider, "/agents/coder/provider".to_string()));
+
+        let mut settings = valid_settings();
+        settings.models.provider = "nope".to_string();
+        assert_eq!(single(&settings), (ValidationKind::UnknownProvider, "/models/provider".to_string()));
+    }
+
+    #[test]
+    fn leader_must_be_team_member() {
+        let mut settings = valid_settings();
+        settings.teams.get_mut("dev").unwrap().leader_agent = Some("boss".to_string());
+        assert_eq!(single(&settings), (ValidationKind::LeaderNotMember, "/teams/dev/leader_agent".to_string()));
+    }
+
+    #[test]
+    fn board_team_must_exist() {
+        let mut settings = valid_settings();
+        settings.board.team_id = Some("ops".to_string());
+        assert_eq!(single(&settings), (ValidationKind::UnknownTeam, "/board/team_id".to_string()));
+    }
+
+    #[test]
+    fn default_agent_must_exist() {
+        let mut settings = valid_settings();
+        settings.routing.default_agent = Some("ghost".to_string());
+        assert_eq!(single(&settings), (ValidationKind::UnknownAgent, "/routing/default_agent".to_string()));
+    }
+
+    #[test]
+    fn routing_rules_and_fallbacks_are_checked() {
+        let rule = |pattern: &str, agent: &str| RoutingRule {
+            pattern: pattern.to_string(),
+            agent: agent.to_string(),
+            priority: 0,
+        };
+        let mut settings = valid_settings();
+        settings.routing.fallback_agents = vec!["coder".to_string(), "ghost".to_string()];
+        settings.routing.rules = vec![
+            rule("(?i)deploy", "coder"),
+            rule("invoice", "billing"),
+            rule("[unclosed", "coder"),
+            rule("(?i)deploy", "coder"),
+        ];
+        let found: Vec<(ValidationKind, String)> =
+            settings.validate().into_iter().map(|e| (e.kind, e.path)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (ValidationKind::UnknownAgent, "/routing/fallback_agents/1".to_string()),
+                (ValidationKind::UnknownAgent, "/routing/rules/1/agent".to_string()),
+                (ValidationKind::InvalidPattern, "/routing/rules/2/pattern".to_string()),
+                (ValidationKind::DuplicateRule, "/routing/rules/3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn added_routing_rules_are_checked_up_front() {
+        let rule = |pattern: &str, agent: &str| RoutingRule {
+            pattern: pattern.to_string(),
+            agent: agent.to_string(),
+            priority: 0,
+        };
+        let mut settings = valid_settings();
+        settings.add_routing_rule(rule("deploy|infra", "coder")).unwrap();
+
+        let err = settings.add_routing_rule(rule("[unclosed", "coder")).unwrap_err();
+        assert!(err.to_string().contains("invalid pattern"), "{}", err);
+        assert!(settings.add_routing_rule(rule("invoice", "ghost")).is_err());
+        assert!(settings.add_routing_rule(rule("deploy|infra", "coder")).is_err());
+        assert_eq!(settings.routing.rules, vec![rule("deploy|infra", "coder")]);
+        assert!(settings.validate().is_empty());
+    }
+
+    #[test]
+    fn schedule_times_and_crons_must_parse() {
+        let mut settings = valid_settings();
+        settings.board.schedules = Some(vec![schedule("09:00", None), schedule("25:00", None)]);
+        assert_eq!(single(&settings), (ValidationKind::InvalidSchedule, "/board/schedules/1/time".to_string()));
+
+        settings.board.schedules = Some(vec![schedule("09:00", Some("not a cron"))]);
+        assert_eq!(single(&settings), (ValidationKind::InvalidSchedule, "/board/schedules/0/cron".to_string()));
+    }
+
+    #[test]
+    fn quiet_hours_span_midnight_in_their_timezone() {
+        let at = |s: &str| chrono::DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&chrono::Utc);
+        let quiet = |start: &str, end: &str, tz: &str| QuietHours {
+            start: start.to_string(),
+            end: end.to_string(),
+            timezone: tz.to_string(),
+        };
+
+        let night = quiet("22:00", "06:00", "UTC");
+        assert!(night.contains(at("2026-01-01T23:30:00Z")));
+        assert!(night.contains(at("2026-01-01T05:59:00Z")));
+        assert!(!night.contains(at("2026-01-01T06:00:00Z")));
+        assert!(!night.contains(at("2026-01-01T12:00:00Z")));
+
+        // 20:30 UTC is 22:30 at +02:00.
+        let offset = quiet("22:00", "23:00", "+02:00");
+        assert!(offset.contains(at("2026-01-01T20:30:00Z")));
+        assert!(!offset.contains(at("2026-01-01T22:30:00Z")));
+
+        let mut settings = valid_settings();
+        settings.monitoring.quiet_hours = Some(night);
+        assert!(settings.validate().is_empty());
+        assert!(settings.monitoring.in_quiet_hours(at("2026-01-01T01:00:00Z")));
+        for bad in [
+            quiet("22:00", "6am", "UTC"),
+            quiet("22:00", "06:00", "Europe/Paris"),
+            quiet("22:00", "06:00", "\u{2212}02:00"),
+            quiet("22:00", "06:00", "+-1:00"),
+        ] {
+            assert!(!bad.contains(at("2026-01-01T23:00:00Z")));
+            settings.monitoring.quiet_hours = Some(bad);
+            assert_eq!(single(&settings), (ValidationKind::InvalidSchedule, "/monitoring/quiet_hours".to_string()));
+        }
+    }
+
+    #[test]
+    fn working_directories_must_be_under_workspace_root() {
+        let mut settings = valid_settings();
+        settings.agents.insert(
+            "a/b".to_string(),
+            AgentConfig {
+                working_directory: Some(PathBuf::from("/tmp/elsewhere")),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            single(&settings),
+            (ValidationKind::OutsideWorkspace, "/agents/a~1b/working_directory".to_string())
+        );
+    }
+
+    fn write_settings_file(settings: &Settings) {
+        let path = get_settings_path().unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, serde_json::to_string_pretty(settings).unwrap()).unwrap();
+    }
+
+    fn settings_on_disk() -> Settings {
+        serde_json::from_str(&std::fs::read_to_string(get_settings_path().unwrap()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn env_secrets_override_file_but_are_never_saved() {
+        let _home = TempHome::new();
+        let mut file = valid_settings();
+        file.channels.telegram.bot_token = Some("file-token".to_string());
+        write_settings_file(&file);
+
+        std::env::set_var("TINYVEGETA_TELEGRAM_TOKEN", "env-token");
+        std::env::set_var("TINYVEGETA_ANTHROPIC_API_KEY", "env-key");
+        let loaded = load_settings();
+        std::env::remove_var("TINYVEGETA_ANTHROPIC_API_KEY");
+        let mut settings = loaded.unwrap();
+        assert_eq!(settings.channels.telegram.bot_token.as_deref(), Some("env-token"));
+        assert_eq!(settings.models.anthropic.api_key.as_deref(), Some("env-key"));
+
+        // An unrelated change is saved; the overridden secret keeps its file value.
+        settings.routing.default_agent = None;
+        save_settings(&settings).unwrap();
+        let disk = settings_on_disk();
+        assert!(disk.routing.default_agent.is_none());
+        assert_eq!(disk.channels.telegram.bot_token.as_deref(), Some("file-token"));
+
+        // Explicitly changing an overridden secret is saved.
+        settings.channels.telegram.bot_token = Some("new-token".to_string());
+        save_settings(&settings).unwrap();
+        std::env::remove_var("TINYVEGETA_TELEGRAM_TOKEN");
+        assert_eq!(settings_on_disk().channels.telegram.bot_token.as_deref(), Some("new-token"));
+
+        // Without the variable the file value is used again.
+        assert_eq!(load_settings().unwrap().channels.telegram.bot_token.as_deref(), Some("new-token"));
+    }
+
+    #[test]
+    fn redacted_masks_only_set_secrets() {
+        let mut settings = valid_settings();
+        settings.models.grok.api_key = Some("xai-secret".to_string());
+        settings.web.api_token = Some("web-secret".to_string());
+        let redacted = serde_json::to_string(&settings.redacted()).unwrap();
+        assert!(!redacted.contains("xai-secret") && !redacted.contains("web-secret"));
+        assert_eq!(settings.redacted().models.grok.api_key.as_deref(), Some("********"));
+        assert!(settings.redacted().channels.telegram.bot_token.is_none());
+    }
+
+    #[test]
+    fn profiles_namespace_settings_and_state() {
+        let home = TempHome::new();
+        let base = home.tinyvegeta_dir();
+        assert_eq!(get_settings_path().unwrap(), base.join("settings.json"));
+        assert_eq!(get_home_dir().unwrap(), base);
+
+        let mut default = valid_settings();
+        default.channels.telegram.bot_token = Some("prod".to_string());
+        save_settings(&default).unwrap();
+
+        std::env::set_var(PROFILE_ENV, "dev");
+        let dev_paths = (get_settings_path(), get_home_dir());
+        let missing = load_settings();
+        let mut dev = valid_settings();
+        dev.channels.telegram.bot_token = Some("dev".to_string());
+        let saved = save_settings(&dev);
+        let dev_token = load_settings().map(|s| s.channels.telegram.bot_token);
+        let enqueued = crate::core::Queue::enqueue(crate::core::queue::MessageData::new("cli", "cli", "cli", "hi"), 0);
+        std::env::set_var(PROFILE_ENV, "../etc");
+        let invalid = get_home_dir();
+        std::env::remove_var(PROFILE_ENV);
+
+        assert_eq!(dev_paths.0.unwrap(), base.join("settings.dev.json"));
+        assert_eq!(dev_paths.1.unwrap(), base.join("profiles").join("dev"));
+        assert!(missing.is_err(), "a profile does not read settings.json");
+        saved.unwrap();
+        assert_eq!(dev_token.unwrap().as_deref(), Some("dev"));
+        assert!(invalid.is_err());
+        enqueued.unwrap();
+        assert!(crate::core::Queue::incoming().unwrap().is_empty(), "queues are per profile");
+
+        // The default profile is untouched.
+        assert_eq!(load_settings().unwrap().channels.telegram.bot_token.as_deref(), Some("prod"));
+        assert_eq!(list_profiles().unwrap(), vec!["dev".to_string()]);
+    }
+
+    #[test]
+    fn interrupted_atomic_writes_leave_the_original_intact() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        atomic_write(&path, "{\"tasks\": []}").unwrap();
+
+        // A crash after a partial temp write, before the rename.
+        let tmp = dir.path().join("tasks.json.tmp");
+        std::fs::write(&tmp, "{\"tas").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"tasks\": []}");
+
+        // The next write replaces both the stale temp file and the original.
+        atomic_write(&path, "{\"tasks\": [1]}").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"tasks\": [1]}");
+        assert!(!tmp.exists());
+    }
+
+    #[test]
+    fn agent_configs_are_checked_before_saving() {
+        let agent = |provider: Option<&str>, model: Option<&str>| AgentConfig {
+            provider: provider.map(String::from),
+            model: model.map(String::from),
+            ..Default::default()
+        };
+
+        for accepted in [
+            agent(Some("claude"), Some("default")),
+            agent(Some("codex"), Some("")),
+            agent(Some("ollama"), Some("llama3.3")),
+            agent(Some("grok"), None),
+            agent(None, None),
+            agent(Some(""), Some("default")),
+        ] {
+            validate_agent(&accepted).unwrap();
+        }
+
+        let unknown = validate_agent(&agent(Some("gpt"), Some("gpt-4o"))).unwrap_err().to_string();
+        assert!(unknown.contains("unknown provider 'gpt'"), "{}", unknown);
+        assert!(unknown.contains("claude, codex, cline, opencode, ollama, grok, anthropic-http"), "{}", unknown);
+        let blank = validate_agent(&agent(Some("ollama"), Some("  "))).unwrap_err().to_string();
+        assert!(blank.contains("provider 'ollama' needs a model name"), "{}", blank);
+    }
+
+    #[test]
+    fn temp_home_points_home_at_a_fresh_dir_and_removes_it() {
+        let dir = {
+            let home = TempHome::new();
+            assert_eq!(get_home_dir().unwrap(), home.tinyvegeta_dir());
+            assert!(home.tinyvegeta_dir().is_dir());
+            assert!(!get_settings_path().unwrap().exists());
+            home.tinyvegeta_dir()
+        };
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn clone_agent_copies_config_context_and_memory() {
+        use crate::memory::{Memory, MemoryScope};
+        let home = crate::config::test_support::TempHome::new();
+        let mut settings = Settings::default();
+        settings.workspace.path = Some(home.tinyvegeta_dir().join("workspace"));
+        settings.teams.insert("ops".to_string(), Default::default());
+        let (_, source) = create_agent(
+            &mut settings,
+            NewAgent {
+                id: "coder".to_string(),
+                provider: Some("codex".to_string()),
+                model: Some("gpt-5".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let source_dir = source.working_directory.unwrap();
+        std::fs::write(source_dir.join("SOUL.md"), "Ships small diffs.").unwrap();
+        std::fs::write(source_dir.join("AGENTS.md"), "Run tests first.").unwrap();
+        Memory::set("style", "terse", MemoryScope::Agent, Some("coder")).unwrap();
+        Memory::set("interaction.last_user", "{}", MemoryScope::Agent, Some("coder")).unwrap();
+
+        let (id, clone) = clone_agent(&mut settings, "coder", "Reviewer", true).unwrap();
+        assert_eq!(id, "reviewer");
+        assert_eq!((clone.provider.as_deref(), clone.model.as_deref()), (Some("codex"), Some("gpt-5")));
+        let dir = clone.working_directory.clone().unwrap();
+        assert_eq!(dir, home.tinyvegeta_dir().join("workspace").join("reviewer"));
+        assert_eq!(std::fs::read_to_string(dir.join("SOUL.md")).unwrap(), "Ships small diffs.");
+        assert_eq!(std::fs::read_to_string(dir.join("AGENTS.md")).unwrap(), "Run tests first.");
+        assert!(dir.join("MEMORY.md").exists());
+        let saved = load_settings().unwrap();
+        assert_eq!(saved.agents["reviewer"].model.as_deref(), Some("gpt-5"));
+
+        let copied = Memory::get("style", MemoryScope::Agent, Some("reviewer")).unwrap().unwrap();
+        assert_eq!((copied.value.as_str(), copied.scope_id.as_deref()), ("terse", Some("reviewer")));
+        assert!(Memory::get("interaction.last_user", MemoryScope::Agent, Some("reviewer")).unwrap().is_none());
+
+        assert!(clone_agent(&mut settings, "coder", "reviewer", false).is_err());
+        assert!(clone_agent(&mut settings, "coder", "ops", false).is_err());
+        assert!(clone_agent(&mut settings, "ghost", "other", false).is_err());
     }
 }
@@ -2,7 +2,7 @@
 //!
 //! Loads identity/memory files to build context for AI providers.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::config::get_home_dir;
 use crate::error::Error;
@@ -359,62 +359,113 @@ fn create_default_agent_soul_extension(agent_id: &str) -> String {
     )
 }
 
-/// Initialize context files for a new agent.
-pub fn init_agent_context(agent_id: &str, working_dir: &PathBuf) -> Result<(), Error> {
-    std::fs::create_dir_all(working_dir)?;
-    if let Some(workspace_root) = working_dir.parent().map(std::path::Path::to_path_buf) {
-        ensure_workspace_context_files(&workspace_root)?;
-    }
+/// Template overrides from `context.templates_dir`.
+struct ContextTemplates {
+    dir: Option<PathBuf>,
+    agent_name: String,
+}
 
-    let soul_path = working_dir.join("SOUL.md");
-    let memory_path = working_dir.join("MEMORY.md");
-    let brain_path = working_dir.join("BRAIN.md");
-    let identity_path = working_dir.join("IDENTITY.md");
-    let user_path = working_dir.join("USER.md");
-    let tools_path = working_dir.join("TOOLS.md");
-    let heartbeat_path = working_dir.join("HEARTBEAT.md");
-    let clients_path = working_dir.join("CLIENTS.md");
-    let playbook_path = working_dir.join("PLAYBOOK.md");
-    let agent_soul_extra_path = working_dir.join("AGENT_SOUL.md");
-
-    if !soul_path.exists() {
-        std::fs::write(&soul_path, create_default_soul(agent_id))?;
-        tracing::info!("Created default SOUL.md at {}", soul_path.display());
+impl ContextTemplates {
+    fn load(agent_id: &str) -> Self {
+        let settings = crate::config::load_settings().ok();
+        let dir = settings.as_ref().and_then(|s| s.context.templates_dir.clone());
+        let agent_name = settings
+            .as_ref()
+            .and_then(|s| s.agents.get(agent_id))
+            .and_then(|a| a.name.clone())
+            .unwrap_or_else(|| agent_id.to_string());
+        Self { dir, agent_name }
     }
 
-    if !memory_path.exists() {
-        std::fs::write(&memory_path, create_default_memory())?;
-        tracing::info!("Created default MEMORY.md at {}", memory_path.display());
+    /// Use `<templates_dir>/<name>` when present, else the built-in default.
+    fn render(&self, agent_id: &str, name: &str, builtin: String) -> String {
+        let Some(dir) = &self.dir else {
+            return builtin;
+        };
+        let path = dir.join(name);
+        match std::fs::read_to_string(&path) {
+            Ok(template) => template
+                .replace("{agent_id}", agent_id)
+                .replace("{agent_name}", &self.agent_name),
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("Failed to read template {}: {}", path.display(), e);
+                }
+                builtin
+            }
+        }
     }
+}
 
-    if !brain_path.exists() {
-        std::fs::write(&brain_path, create_default_brain())?;
-        tracing::info!("Created default BRAIN.md at {}", brain_path.display());
-    }
+/// Per-agent context files with their default contents.
+fn default_agent_context_files(agent_id: &str, templates: &ContextTemplates) -> Vec<(&'static str, String)> {
+    vec![
+        ("SOUL.md", create_default_soul(agent_id)),
+        ("MEMORY.md", create_default_memory()),
+        ("BRAIN.md", create_default_brain()),
+        ("IDENTITY.md", create_default_identity()),
+        ("USER.md", create_default_user()),
+        ("TOOLS.md", create_default_tools()),
+        ("HEARTBEAT.md", create_default_heartbeat()),
+        ("CLIENTS.md", create_default_clients()),
+        ("PLAYBOOK.md", create_default_playbook()),
+        ("AGENT_SOUL.md", create_default_agent_soul_extension(agent_id)),
+    ]
+    .into_iter()
+    .map(|(name, builtin)| (name, templates.render(agent_id, name, builtin)))
+    .collect()
+}
 
-    if !identity_path.exists() {
-        std::fs::write(&identity_path, create_default_identity())?;
-    }
-    if !user_path.exists() {
-        std::fs::write(&user_path, create_default_user())?;
-    }
-    if !tools_path.exists() {
-        std::fs::write(&tools_path, create_default_tools())?;
-    }
-    if !heartbeat_path.exists() {
-        std::fs::write(&heartbeat_path, create_default_heartbeat())?;
-    }
-    if !clients_path.exists() {
-        std::fs::write(&clients_path, create_default_clients())?;
-    }
-    if !playbook_path.exists() {
-        std::fs::write(&playbook_path, create_default_playbook())?;
-    }
-    if !agent_soul_extra_path.exists() {
-        std::fs::write(&agent_soul_extra_path, create_default_agent_soul_extension(agent_id))?;
+/// Initialize context files for a new agent.
+pub fn init_agent_context(agent_id: &str, working_dir: &Path) -> Result<(), Error> {
+    scaffold_agent_context(agent_id, working_dir, false)?;
+    Ok(())
+}
+
+/// Result of [`scaffold_agent_context`].
+#[derive(Debug, Default)]
+pub struct ContextScaffold {
+    pub created: Vec<&'static str>,
+    /// Files reset to defaults, with the backup of their previous contents.
+    pub replaced: Vec<(&'static str, PathBuf)>,
+    pub kept: Vec<&'static str>,
+}
+
+/// Create missing context files for an agent.
+///
+/// Contents come from `context.templates_dir` when it has a file of the same
+/// name, otherwise from the built-in defaults. Existing files are kept
+/// unless `force` is set, in which case each one is copied to
+/// `<name>.<timestamp>.bak` before being reset to its default.
+pub fn scaffold_agent_context(
+    agent_id: &str,
+    working_dir: &Path,
+    force: bool,
+) -> Result<ContextScaffold, Error> {
+    std::fs::create_dir_all(working_dir)?;
+    if let Some(workspace_root) = working_dir.parent().map(Path::to_path_buf) {
+        ensure_workspace_context_files(&workspace_root)?;
     }
 
-    Ok(())
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let templates = ContextTemplates::load(agent_id);
+    let mut result = ContextScaffold::default();
+    for (name, content) in default_agent_context_files(agent_id, &templates) {
+        let path = working_dir.join(name);
+        if !path.exists() {
+            std::fs::write(&path, content)?;
+            tracing::info!("Created default {} at {}", name, path.display());
+            result.created.push(name);
+        } else if force {
+            let backup = working_dir.join(format!("{}.{}.bak", name, stamp));
+            std::fs::copy(&path, &backup)?;
+            std::fs::write(&path, content)?;
+            result.replaced.push((name, backup));
+        } else {
+            result.kept.push(name);
+        }
+    }
+    Ok(result)
 }
 
 fn ensure_workspace_context_files(workspace_root: &PathBuf) -> Result<(), Error> {
@@ -440,3 +491,55 @@ fn ensure_workspace_context_files(workspace_root: &PathBuf) -> Result<(), Error>
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaffold_keeps_existing_files_unless_forced() {
+        let _home = crate::config::test_support::TempHome::new();
+        let ws = tempfile::tempdir().unwrap();
+        let wd = ws.path().join("coder");
+        std::fs::create_dir_all(&wd).unwrap();
+        std::fs::write(wd.join("SOUL.md"), "custom soul").unwrap();
+
+        let first = scaffold_agent_context("coder", &wd, false).unwrap();
+        assert_eq!(first.kept, vec!["SOUL.md"]);
+        assert!(first.created.contains(&"MEMORY.md"));
+        assert_eq!(std::fs::read_to_string(wd.join("SOUL.md")).unwrap(), "custom soul");
+
+        let forced = scaffold_agent_context("coder", &wd, true).unwrap();
+        assert!(forced.created.is_empty());
+        let (_, backup) = forced.replaced.iter().find(|(name, _)| *name == "SOUL.md").unwrap();
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), "custom soul");
+        assert_ne!(std::fs::read_to_string(wd.join("SOUL.md")).unwrap(), "custom soul");
+    }
+
+    #[test]
+    fn scaffold_uses_configured_templates() {
+        let home = crate::config::test_support::TempHome::new();
+        let templates = tempfile::tempdir().unwrap();
+        std::fs::write(
+            templates.path().join("SOUL.md"),
+            "# {agent_name}\n\nAgent id: {agent_id}\n",
+        )
+        .unwrap();
+        let settings = serde_json::json!({
+            "agents": { "coder": { "name": "Code Smith" } },
+            "context": { "templates_dir": templates.path() },
+        });
+        std::fs::write(home.tinyvegeta_dir().join("settings.json"), settings.to_string()).unwrap();
+
+        let ws = tempfile::tempdir().unwrap();
+        let wd = ws.path().join("coder");
+        scaffold_agent_context("coder", &wd, false).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(wd.join("SOUL.md")).unwrap(),
+            "# Code Smith\n\nAgent id: coder\n"
+        );
+        // Files without a template fall back to the built-in default.
+        assert_eq!(std::fs::read_to_string(wd.join("MEMORY.md")).unwrap(), create_default_memory());
+    }
+}
@@ -5,10 +5,17 @@
 //! - Tracking active conversations
 //! - Pending mentions within conversations
 //! - Conversation completion detection
+//! - Persisted per-agent dialogue history
 
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::config::get_home_dir;
+use crate::core::text::truncate_chars;
+use crate::error::Error;
+
 /// A conversation tracks messages and pending mentions.
 #[derive(Debug, Clone)]
 pub struct Conversation {
@@ -232,10 +239,165 @@ impl From<&Conversation> for ConversationState {
     }
 }
 
+/// One persisted dialogue message.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryTurn {
+    /// `user` or `agent`
+    pub role: String,
+    pub content: String,
+    pub timestamp: i64,
+}
+
+/// Dialogue history stored per agent and conversation under
+/// `~/.tinyvegeta/conversations/<agent_id>/<conversation_id>.json`.
+pub struct ConversationHistory;
+
+impl ConversationHistory {
+    fn agent_dir(agent_id: &str) -> Result<PathBuf, Error> {
+        Ok(get_home_dir()?.join("conversations").join(sanitize(agent_id)))
+    }
+
+    fn path(agent_id: &str, conversation_id: &str) -> Result<PathBuf, Error> {
+        Ok(Self::agent_dir(agent_id)?.join(format!("{}.json", sanitize(conversation_id))))
+    }
+
+    /// Load the stored history, oldest first. Missing or unreadable files yield no history.
+    pub fn load(agent_id: &str, conversation_id: &str) -> Result<Vec<HistoryTurn>, Error> {
+        let path = Self::path(agent_id, conversation_id)?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?).unwrap_or_default())
+    }
+
+    /// Append a user message and the agent's reply, keeping the last `keep_turns` exchanges.
+    pub fn append(
+        agent_id: &str,
+        conversation_id: &str,
+        user: &str,
+        response: &str,
+        keep_turns: usize,
+    ) -> Result<(), Error> {
+        let mut turns = Self::load(agent_id, conversation_id)?;
+        let now = now_timestamp();
+        turns.push(HistoryTurn { role: "user".to_string(), content: user.to_string(), timestamp: now });
+        turns.push(HistoryTurn { role: "agent".to_string(), content: response.to_string(), timestamp: now });
+        let excess = turns.len().saturating_sub(keep_turns * 2);
+        turns.drain(..excess);
+
+        let path = Self::path(agent_id, conversation_id)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(&turns)?)?;
+        Ok(())
+    }
+
+    /// Drop every stored conversation of an agent. Returns how many were removed.
+    pub fn clear_agent(agent_id: &str) -> Result<usize, Error> {
+        let dir = Self::agent_dir(agent_id)?;
+        if !dir.exists() {
+            return Ok(0);
+        }
+        let count = std::fs::read_dir(&dir)?.count();
+        std::fs::remove_dir_all(dir)?;
+        Ok(count)
+    }
+
+    /// Render the most recent messages as a prompt block of at most `max_chars`
+    /// characters, dropping the oldest first.
+    pub fn render(turns: &[HistoryTurn], agent_id: &str, max_chars: usize) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let mut used = 0;
+        for turn in turns.iter().rev() {
+            let speaker = if turn.role == "user" {
+                "User".to_string()
+            } else {
+                format!("@{}", agent_id)
+            };
+            let line = format!("{}: {}", speaker, turn.content.trim());
+            let len = line.chars().count() + 1;
+            if used + len > max_chars {
+                if lines.is_empty() {
+                    lines.push(truncate_chars(&line, max_chars).to_string());
+                }
+                break;
+            }
+            used += len;
+            lines.push(line);
+        }
+        lines.reverse();
+        lines.join("\n")
+    }
+}
+
+/// File-name-safe encoding of an id. Injective, so distinct conversation ids
+/// never share a history file: other bytes (including `_`) become `_xx`.
+fn sanitize(id: &str) -> String {
+    let mut out = String::with_capacity(id.len());
+    for b in id.bytes() {
+        if b.is_ascii_alphanumeric() || b == b'-' {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("_{:02x}", b));
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn history_accumulates_and_keeps_last_k_turns() {
+        let _home = crate::config::test_support::TempHome::new();
+
+        for i in 1..=5 {
+            ConversationHistory::append("coder", "telegram-42", &format!("q{}", i), &format!("a{}", i), 3).unwrap();
+        }
+        let turns = ConversationHistory::load("coder", "telegram-42").unwrap();
+        let contents: Vec<&str> = turns.iter().map(|t| t.content.as_str()).collect();
+        assert_eq!(contents, vec!["q3", "a3", "q4", "a4", "q5", "a5"]);
+        assert!(ConversationHistory::load("coder", "other").unwrap().is_empty());
+
+        let block = ConversationHistory::render(&turns, "coder", 1000);
+        assert!(block.starts_with("User: q3\n@coder: a3"));
+        let capped = ConversationHistory::render(&turns, "coder", 20);
+        assert_eq!(capped, "User: q5\n@coder: a5");
+
+        assert_eq!(ConversationHistory::clear_agent("coder").unwrap(), 1);
+        assert!(ConversationHistory::load("coder", "telegram-42").unwrap().is_empty());
+    }
+
+    #[test]
+    fn senders_in_one_chat_have_isolated_histories() {
+        let _home = crate::config::test_support::TempHome::new();
+
+        let mut alice = crate::core::MessageData::new("telegram", "Alice", "111", "hi");
+        alice.response_chat_id = Some(-100500);
+        let mut bob = crate::core::MessageData::new("telegram", "Bob", "222", "hi");
+        bob.response_chat_id = Some(-100500);
+        let mut alice_dm = alice.clone();
+        alice_dm.response_chat_id = Some(111);
+        assert_ne!(alice.conversation_scope(), bob.conversation_scope());
+        assert_ne!(alice.conversation_scope(), alice_dm.conversation_scope());
+
+        ConversationHistory::append("coder", &alice.conversation_scope(), "alice secret", "noted", 6).unwrap();
+        ConversationHistory::append("coder", &bob.conversation_scope(), "bob question", "answer", 6).unwrap();
+
+        let alice_turns = ConversationHistory::load("coder", &alice.conversation_scope()).unwrap();
+        let bob_turns = ConversationHistory::load("coder", &bob.conversation_scope()).unwrap();
+        assert_eq!(alice_turns[0].content, "alice secret");
+        assert_eq!(bob_turns.len(), 2);
+        assert!(bob_turns.iter().all(|t| !t.content.contains("alice")));
+        assert!(ConversationHistory::load("coder", &alice_dm.conversation_scope()).unwrap().is_empty());
+
+        // Ids differing only in characters that are not file-name safe stay apart.
+        assert_ne!(sanitize("a:b"), sanitize("a_b"));
+        assert_ne!(sanitize("a_3ab"), sanitize("a:b"));
+    }
+
     #[test]
     fn test_conversation() {
         let mut conv = Conversation::new("conv1", "user123", "telegram", "Hello");
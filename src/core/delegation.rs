@@ -0,0 +1,199 @@
+//! Correlated teammate delegations.
+//!
+//! A leader's first mention starts a delegation tree identified by a
+//! correlation ID that every handoff inherits. Each branch's result is
+//! recorded in the delegation's own task-scope store (`delegation-{id}`),
+//! which keeps it out of prompt memory and Global pruning; once no branch
+//! is outstanding the results are consolidated into one reply and the store
+//! is removed.
+
+use std::time::Duration;
+
+use crate::core::text::truncate_chars;
+use crate::error::Error;
+use crate::memory::lock::{acquire_lock, LockHandle};
+use crate::memory::store::{get_memory_file, load_store, save_store, MemoryStore};
+use crate::memory::{ensure_memory_dirs, Memory, MemoryEntry, MemoryScope};
+
+/// Agents still owing a result, one entry per branch.
+const PENDING_KEY: &str = "_pending";
+
+/// Every agent that was handed a branch, in handoff order.
+const AGENTS_KEY: &str = "_agents";
+
+fn store_id(correlation_id: &str) -> String {
+    format!("delegation-{}", correlation_id)
+}
+
+fn result_key(agent: &str) -> String {
+    format!("result.{}", agent)
+}
+
+fn read_list(store: &MemoryStore, key: &str) -> Vec<String> {
+    store
+        .get(key)
+        .map(|e| {
+            e.value
+                .split(',')
+                .filter(|a| !a.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn write_list(store: &mut MemoryStore, id: &str, key: &str, items: &[String]) {
+    store.set(MemoryEntry::new(key, &items.join(","), MemoryScope::Task, Some(id.to_string())));
+}
+
+/// Branches finish on parallel workers; wait briefly for the store lock
+/// rather than failing like a plain memory write.
+fn lock_store(id: &str) -> Result<LockHandle, Error> {
+    ensure_memory_dirs()?;
+    let path = get_memory_file(&MemoryScope::Task, Some(id))?;
+    let mut attempts = 0;
+    loop {
+        match acquire_lock(&path) {
+            Ok(lock) => return Ok(lock),
+            Err(e) if attempts >= 100 => return Err(e),
+            Err(_) => {
+                attempts += 1;
+                std::thread::sleep(Duration::from_millis(20));
+            }
+        }
+    }
+}
+
+/// Register branches handed to `agents` under `correlation_id`.
+pub fn expect(correlation_id: &str, agents: &[String]) -> Result<(), Error> {
+    if agents.is_empty() {
+        return Ok(());
+    }
+    let id = store_id(correlation_id);
+    let _lock = lock_store(&id)?;
+    let mut store = load_store(&MemoryScope::Task, Some(&id))?;
+
+    let mut pending = read_list(&store, PENDING_KEY);
+    pending.extend(agents.iter().cloned());
+    write_list(&mut store, &id, PENDING_KEY, &pending);
+
+    let mut all = read_list(&store, AGENTS_KEY);
+    for agent in agents {
+        if !all.contains(agent) {
+            all.push(agent.clone());
+        }
+    }
+    write_list(&mut store, &id, AGENTS_KEY, &all);
+    save_store(&MemoryScope::Task, Some(&id), &store)
+}
+
+/// Number of branches still outstanding.
+pub fn pending(correlation_id: &str) -> usize {
+    load_store(&MemoryScope::Task, Some(&store_id(correlation_id)))
+        .map(|store| read_list(&store, PENDING_KEY).len())
+        .unwrap_or(0)
+}
+
+/// Record `agent`'s result for one of its branches.
+///
+/// Register any follow-up branches with [`expect`] first, so the tree is
+/// not considered complete too early. Returns every `(agent, result)` in
+/// handoff order once this was the last outstanding branch, and removes the
+/// delegation's store.
+pub fn record(correlation_id: &str, agent: &str, output: &str) -> Result<Option<Vec<(String, String)>>, Error> {
+    let id = store_id(correlation_id);
+    let lock = lock_store(&id)?;
+    let mut store = load_store(&MemoryScope::Task, Some(&id))?;
+
+    let key = result_key(agent);
+    let value = match store.get(&key) {
+        Some(previous) if !previous.value.is_empty() => format!("{}\n\n{}", previous.value, output),
+        _ => output.to_string(),
+    };
+    store.set(MemoryEntry::new(&key, &value, MemoryScope::Task, Some(id.clone())));
+
+    let mut pending = read_list(&store, PENDING_KEY);
+    match pending.iter().position(|a| a == agent) {
+        Some(pos) => {
+            pending.remove(pos);
+        }
+        None => tracing::warn!("Unexpected delegation result {} from @{}", correlation_id, agent),
+    }
+    if !pending.is_empty() {
+        write_list(&mut store, &id, PENDING_KEY, &pending);
+        save_store(&MemoryScope::Task, Some(&id), &store)?;
+        return Ok(None);
+    }
+
+    let results = read_list(&store, AGENTS_KEY)
+        .into_iter()
+        .filter_map(|agent| {
+            let value = store.get(&result_key(&agent))?.value.clone();
+            Some((agent, value))
+        })
+        .collect();
+    drop(lock);
+    Memory::clear(MemoryScope::Task, Some(&id))?;
+    Ok(Some(results))
+}
+
+/// One message summarising every branch of a delegation, capped at
+/// `max_chars` with each branch getting an equal share.
+pub fn format_summary(task_token: &str, results: &[(String, String)], max_chars: usize) -> String {
+    let header = format!("📋 Task {} team results ({} teammate(s)):", task_token, results.len());
+    let share = max_chars.saturating_sub(header.len()) / results.len().max(1);
+    let mut text = header;
+    for (agent, output) in results {
+        let budget = share.saturating_sub(agent.len() + 8);
+        let body = truncate_chars(output.trim(), budget);
+        let ellipsis = if body.len() < output.trim().len() { "..." } else { "" };
+        text.push_str(&format!("\n\n@{}:\n{}{}", agent, body, ellipsis));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_teammate_fan_out_consolidates_once_both_reply() {
+        let _home = crate::config::test_support::TempHome::new();
+        let cid = "01TEST";
+        expect(cid, &["coder".to_string(), "reviewer".to_string()]).unwrap();
+        assert_eq!(pending(cid), 2);
+
+        assert!(record(cid, "reviewer", "LGTM").unwrap().is_none());
+        assert_eq!(pending(cid), 1);
+
+        // A child handoff registered before the parent's result keeps the tree open.
+        expect(cid, &["reviewer".to_string()]).unwrap();
+        assert!(record(cid, "coder", "patched").unwrap().is_none());
+        let done = record(cid, "reviewer", "re-checked").unwrap().unwrap();
+        assert_eq!(
+            done,
+            vec![
+                ("coder".to_string(), "patched".to_string()),
+                ("reviewer".to_string(), "LGTM\n\nre-checked".to_string()),
+            ]
+        );
+        // The consolidated delegation leaves nothing behind, least of all in Global memory.
+        assert!(!get_memory_file(&MemoryScope::Task, Some("delegation-01TEST")).unwrap().exists());
+        assert_eq!(pending(cid), 0);
+        assert!(Memory::list(MemoryScope::Global, None, None).unwrap().is_empty());
+
+        let summary = format_summary("abc123", &done, 4000);
+        assert!(summary.starts_with("📋 Task abc123 team results (2 teammate(s)):"));
+        assert!(summary.contains("@coder:\npatched"));
+        assert!(summary.contains("@reviewer:\nLGTM"));
+    }
+
+    #[test]
+    fn summary_shares_the_length_cap_between_branches() {
+        let long = "x".repeat(5000);
+        let results = vec![("a".to_string(), long.clone()), ("b".to_string(), long)];
+        let summary = format_summary("t", &results, 1000);
+        assert!(summary.chars().count() <= 1000);
+        assert!(summary.contains("@b:"));
+    }
+}
@@ -0,0 +1,68 @@
+//! Path of the running `tinyvegeta` binary, used to invoke itself.
+//!
+//! The path is resolved once at startup: after an upgrade replaces the binary
+//! on disk, `current_exe()` can no longer be trusted, and a bare `tinyvegeta`
+//! only works when it happens to be on `PATH`.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::error::{Error, Result};
+
+static SELF_EXE: OnceLock<std::result::Result<PathBuf, String>> = OnceLock::new();
+
+/// Resolve and remember the executable path. Called once from `main`;
+/// later calls return the stored result.
+pub fn init() -> Result<&'static Path> {
+    self_exe()
+}
+
+/// The executable path resolved at startup, or why it could not be resolved.
+pub fn self_exe() -> Result<&'static Path> {
+    SELF_EXE
+        .get_or_init(|| {
+            let path = std::env::current_exe()
+                .map_err(|e| format!("Cannot determine the tinyvegeta executable path: {}", e))?;
+            validate(path)
+        })
+        .as_deref()
+        .map_err(|e| Error::Other(e.clone()))
+}
+
+/// [`self_exe`] as a UTF-8 string, for shell command lines.
+pub fn self_exe_str() -> Result<&'static str> {
+    let path = self_exe()?;
+    path.to_str()
+        .ok_or_else(|| Error::Other(format!("tinyvegeta executable path is not valid UTF-8: {}", path.display())))
+}
+
+fn validate(path: PathBuf) -> std::result::Result<PathBuf, String> {
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(format!(
+            "tinyvegeta executable {} does not exist; run it by its full path or reinstall",
+            path.display()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{self_exe, validate};
+
+    #[test]
+    fn missing_executables_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("tinyvegeta");
+        let err = validate(missing.clone()).unwrap_err();
+        assert!(err.contains(&missing.display().to_string()));
+        assert!(validate(dir.path().to_path_buf()).is_err());
+
+        std::fs::write(&missing, b"").unwrap();
+        assert_eq!(validate(missing.clone()).unwrap(), missing);
+
+        // The test binary itself resolves.
+        assert!(self_exe().unwrap().is_file());
+    }
+}
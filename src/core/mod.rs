@@ -6,7 +6,14 @@
 //! - Conversation tracking
 
 pub mod conversation;
+pub mod delegation;
+pub mod exe;
+pub mod injection;
+pub mod prompt;
 pub mod queue;
 pub mod routing;
+pub mod task_store;
+pub mod text;
+pub mod worker;
 
 pub use queue::{MessageData, Queue};
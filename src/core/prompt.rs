@@ -0,0 +1,137 @@
+//! Prompt assembly shared by message processing and `context explain`.
+//!
+//! An agent's prompt is its loaded context files, a runtime block describing
+//! where it runs, the memory entries retrieved for the message, then the
+//! user block (history, message context and the message itself).
+
+use std::path::PathBuf;
+
+use crate::config::Settings;
+use crate::context::AgentContext;
+use crate::memory::{Memory, MemoryScope};
+use crate::task::RoutedTask;
+
+/// Agent, workspace and team an agent is acting for.
+pub fn runtime_context_block(
+    settings: &Settings,
+    agent_id: &str,
+    working_dir: Option<&PathBuf>,
+    team_id: Option<&str>,
+) -> String {
+    let workdir = working_dir
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "<none>".to_string());
+    let workspace_root = settings
+        .workspace
+        .path
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "<none>".to_string());
+    let board_id = settings
+        .board
+        .team_id
+        .as_deref()
+        .unwrap_or("<none>");
+    let team = team_id.unwrap_or("<none>");
+    format!(
+        "- agent_id: {}\n- working_directory: {}\n- workspace_root: {}\n- team_id: {}\n- board_id: {}",
+        agent_id, workdir, workspace_root, team, board_id
+    )
+}
+
+/// `runtime_block` followed by how the message was routed.
+pub fn with_task_route(runtime_block: &str, task: &RoutedTask) -> String {
+    format!(
+        "{}\n- task_intent: {}\n- task_priority: {}\n- task_deadline: {}\n- routed_owner: {}\n- route_reason: {}",
+        runtime_block,
+        task.intent,
+        task.priority,
+        task.deadline.as_deref().unwrap_or("<none>"),
+        task.owner,
+        task.reason
+    )
+}
+
+/// Global, agent and team memory entries relevant to `query`, one per line,
+/// within `memory.injection_limits`.
+pub fn memory_context_block(
+    settings: &Settings,
+    agent_id: &str,
+    team_id: Option<&str>,
+    query: &str,
+) -> String {
+    let limits = &settings.memory.injection_limits;
+    let mut lines = Vec::new();
+
+    if let Ok(entries) = Memory::relevant(query, MemoryScope::Global, None, limits.global, settings.memory.decay_rate) {
+        for e in entries {
+            lines.push(format!("[global] {} = {}", e.key, e.value.chars().take(220).collect::<String>()));
+        }
+    }
+    if let Ok(entries) = Memory::relevant(query, MemoryScope::Agent, Some(agent_id), limits.agent, settings.memory.decay_rate) {
+        // `interaction.*` holds whoever spoke last; per-sender history covers dialogue.
+        for e in entries.into_iter().filter(|e| !e.key.starts_with("interaction.")) {
+            lines.push(format!("[agent:{}] {} = {}", agent_id, e.key, e.value.chars().take(220).collect::<String>()));
+        }
+    }
+    if let Some(team) = team_id {
+        if let Ok(entries) = Memory::relevant(query, MemoryScope::Team, Some(team), limits.team, settings.memory.decay_rate) {
+            for e in entries {
+                lines.push(format!("[team:{}] {} = {}", team, e.key, e.value.chars().take(220).collect::<String>()));
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// The full prompt sent to the provider.
+pub fn assemble_prompt(context: &AgentContext, runtime_block: &str, memory_block: &str, user_block: &str) -> String {
+    let mut sections = Vec::new();
+    if context.has_context() {
+        sections.push(context.build_system_prompt());
+    }
+    sections.push(format!("## Runtime Context\n{}", runtime_block));
+    if !memory_block.is_empty() {
+        sections.push(format!("## Retrieved Memory Context\n{}", memory_block));
+    }
+    sections.push(user_block.to_string());
+    sections.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Board, Routing, Workspace};
+
+    #[test]
+    fn runtime_context_contains_workspace_and_agent_path() {
+        let mut settings = Settings::default();
+        settings.workspace = Workspace {
+            path: Some(PathBuf::from("/tmp/ws")),
+            name: Some("ws".to_string()),
+        };
+        settings.board = Board {
+            team_id: Some("board".to_string()),
+            autonomous: Some(true),
+            schedules: None,
+            ..Default::default()
+        };
+        settings.routing = Routing {
+            default_agent: Some("assistant".to_string()),
+            ..Default::default()
+        };
+
+        let block = runtime_context_block(
+            &settings,
+            "assistant",
+            Some(&PathBuf::from("/tmp/ws/assistant")),
+            Some("board"),
+        );
+
+        assert!(block.contains("agent_id: assistant"));
+        assert!(block.contains("working_directory: /tmp/ws/assistant"));
+        assert!(block.contains("workspace_root: /tmp/ws"));
+        assert!(block.contains("team_id: board"));
+    }
+}
@@ -7,9 +7,12 @@
 //! - Mention tag extraction ([@agent: message])
 
 use regex::Regex;
+use serde::Serialize;
 use std::collections::HashMap;
 
-use crate::config::{AgentConfig, Settings, TeamConfig};
+use crate::config::{AgentConfig, RoutingRule, Settings, TeamConfig};
+use crate::core::queue::MessageData;
+use crate::task::{RoutedTask, TaskRouter};
 
 /// Parse agent routing from message prefix.
 ///
@@ -130,17 +133,66 @@ fn extract_shared_context(response: &str) -> String {
     context
 }
 
-/// Find the first team that contains an agent.
+/// The team an agent acts for, and why it was chosen.
+#[derive(Debug, Clone)]
+pub struct TeamSelection {
+    pub team_id: String,
+    pub team: TeamConfig,
+    /// Which rule picked the team (`preferred`, `leader` or `member`).
+    pub reason: &'static str,
+    /// Every team the agent belongs to, sorted by id.
+    pub candidates: Vec<String>,
+}
+
+/// Resolve the team an agent acts for.
+///
+/// Agents may belong to several teams, so the choice is deterministic:
+/// 1. the first of `preferred` (e.g. the conversation's team, then the
+///    board team) that the agent belongs to;
+/// 2. otherwise the team the agent leads with the smallest id;
+/// 3. otherwise the team containing the agent with the smallest id.
+pub fn resolve_team_for_agent(
+    agent_id: &str,
+    preferred: &[&str],
+    teams: &HashMap<String, TeamConfig>,
+) -> Option<TeamSelection> {
+    let mut candidates: Vec<&String> = teams
+        .iter()
+        .filter(|(_, team)| team.agents.iter().any(|a| a == agent_id))
+        .map(|(id, _)| id)
+        .collect();
+    candidates.sort();
+
+    let (team_id, reason) = if let Some(id) = preferred
+        .iter()
+        .find(|p| candidates.iter().any(|c| c.as_str() == **p))
+    {
+        (id.to_string(), "preferred")
+    } else if let Some(id) = candidates
+        .iter()
+        .find(|id| teams[id.as_str()].leader_agent.as_deref() == Some(agent_id))
+    {
+        (id.to_string(), "leader")
+    } else {
+        (candidates.first()?.to_string(), "member")
+    };
+
+    Some(TeamSelection {
+        team: teams[&team_id].clone(),
+        team_id,
+        reason,
+        candidates: candidates.into_iter().cloned().collect(),
+    })
+}
+
+/// Find the team an agent acts for when no team is preferred.
+///
+/// See [`resolve_team_for_agent`] for how multi-team agents are resolved.
 pub fn find_team_for_agent(
     agent_id: &str,
     teams: &HashMap<String, TeamConfig>,
 ) -> Option<(String, TeamConfig)> {
-    for (team_id, team) in teams {
-        if team.agents.contains(&agent_id.to_string()) {
-            return Some((team_id.clone(), team.clone()));
-        }
-    }
-    None
+    resolve_team_for_agent(agent_id, &[], teams).map(|s| (s.team_id, s.team))
 }
 
 /// Check if a mentioned ID is a valid teammate of the current agent.
@@ -158,7 +210,7 @@ pub fn is_teammate(
 
     mentioned_id != current_agent_id
         && team.agents.contains(&mentioned_id.to_string())
-        && agents.contains_key(mentioned_id)
+        && agents.get(mentioned_id).is_some_and(|a| a.enabled)
 }
 
 /// Resolve an agent ID from various routing formats.
@@ -172,11 +224,12 @@ pub fn resolve_routing_target(
     agents: &HashMap<String, AgentConfig>,
 ) -> Vec<String> {
     let mut results = Vec::new();
+    let enabled = |id: &str| agents.get(id).is_some_and(|a| a.enabled);
 
     // Check if it's a team
     if let Some(team) = teams.get(target) {
         if let Some(leader) = &team.leader_agent {
-            if agents.contains_key(leader) {
+            if enabled(leader) {
                 results.push(leader.clone());
             }
         }
@@ -187,7 +240,7 @@ pub fn resolve_routing_target(
     if target.contains(',') {
         for t in target.split(',') {
             let t = t.trim();
-            if agents.contains_key(t) {
+            if enabled(t) {
                 results.push(t.to_string());
             }
         }
@@ -195,33 +248,117 @@ pub fn resolve_routing_target(
     }
 
     // Check if it's a single agent
-    if agents.contains_key(target) {
+    if enabled(target) {
         results.push(target.to_string());
     }
 
     results
 }
 
-/// Get the default agent from settings.
+/// The agent a queued message is processed by, with the task routing behind it.
+#[derive(Debug, Clone)]
+pub struct MessageRoute {
+    pub agent_id: String,
+    pub task: RoutedTask,
+}
+
+/// Every step of resolving the agent for a message, as reported by
+/// `routing explain`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteTrace {
+    /// The message's explicit `@target`, if any.
+    pub mention: Option<String>,
+    /// What the mention named: `agent`, `team` or `unknown`.
+    pub mention_kind: Option<&'static str>,
+    /// The `routing.rules` entry that picked the owner (messages without a mention).
+    pub matched_rule: Option<RoutingRule>,
+    pub task: RoutedTask,
+    /// Agent the message is processed by.
+    pub agent_id: String,
+    /// The disabled agent the message was rerouted away from.
+    pub rerouted_from: Option<String>,
+}
+
+/// Resolve the agent a queued message is processed by; see [`route_message`].
+pub fn resolve_target_agent(msg: &MessageData, settings: &Settings) -> RouteTrace {
+    trace_route(&msg.message, msg.agent.as_deref(), settings)
+}
+
+/// Route a queued message. An explicit `target` agent wins, a team target goes
+/// to its leader and an unknown one to the default agent; messages without a
+/// target follow `routing.rules`, then intent routing. A disabled agent's
+/// messages go to the default agent; the route only names a disabled (or
+/// missing) agent when no agent is enabled.
+pub fn route_message(message: &str, target: Option<&str>, settings: &Settings) -> MessageRoute {
+    let trace = trace_route(message, target, settings);
+    MessageRoute {
+        agent_id: trace.agent_id,
+        task: trace.task,
+    }
+}
+
+fn trace_route(message: &str, target: Option<&str>, settings: &Settings) -> RouteTrace {
+    let task = TaskRouter::route(message, settings, target);
+    let default_agent = || get_default_agent(settings).unwrap_or_else(|| "assistant".to_string());
+    let (mention_kind, agent_id) = match target {
+        Some(target) if settings.agents.contains_key(target) => (Some("agent"), target.to_string()),
+        Some(target) => match settings.teams.get(target) {
+            Some(team) => (Some("team"), team.leader_agent.clone().unwrap_or_else(default_agent)),
+            None => (Some("unknown"), default_agent()),
+        },
+        None => (None, task.owner.clone()),
+    };
+    let (agent_id, rerouted_from) = if settings.agents.get(&agent_id).is_some_and(|a| !a.enabled) {
+        let fallback = default_agent();
+        tracing::info!("Agent @{} is disabled; rerouting to @{}", agent_id, fallback);
+        (fallback, Some(agent_id))
+    } else {
+        (agent_id, None)
+    };
+    RouteTrace {
+        mention: target.map(String::from),
+        mention_kind,
+        matched_rule: target
+            .is_none()
+            .then(|| crate::task::matching_rule(message, settings).cloned())
+            .flatten(),
+        task,
+        agent_id,
+        rerouted_from,
+    }
+}
+
+/// Get the default agent from settings: `routing.default_agent`, then the
+/// first existing `routing.fallback_agents` entry, then well-known ids.
+/// Disabled agents are skipped.
 pub fn get_default_agent(settings: &Settings) -> Option<String> {
     if let Some(id) = settings.routing.default_agent.as_deref() {
-        if settings.agents.contains_key(id) {
+        if settings.is_agent_enabled(id) {
             return Some(id.to_string());
         }
     }
 
-    if settings.agents.contains_key("assistant") {
+    if let Some(id) = settings.routing.fallback_agents.iter().find(|id| settings.is_agent_enabled(id)) {
+        return Some(id.clone());
+    }
+
+    if settings.is_agent_enabled("assistant") {
         return Some("assistant".to_string());
     }
-    if settings.agents.contains_key("tinyvegeta") {
+    if settings.is_agent_enabled("tinyvegeta") {
         return Some("tinyvegeta".to_string());
     }
-    if settings.agents.contains_key("tiny-vegeta") {
+    if settings.is_agent_enabled("tiny-vegeta") {
         return Some("tiny-vegeta".to_string());
     }
 
     // Stable fallback.
-    let mut ids: Vec<String> = settings.agents.keys().cloned().collect();
+    let mut ids: Vec<String> = settings
+        .agents
+        .iter()
+        .filter(|(_, a)| a.enabled)
+        .map(|(id, _)| id.clone())
+        .collect();
     ids.sort();
     ids.into_iter().next()
 }
@@ -281,6 +418,58 @@ mod tests {
         assert_eq!(result.unwrap().0, "dev");
     }
 
+    fn team(agents: &[&str], leader: Option<&str>) -> TeamConfig {
+        TeamConfig {
+            name: String::new(),
+            agents: agents.iter().map(|a| a.to_string()).collect(),
+            leader_agent: leader.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_multi_team_membership_is_deterministic() {
+        let mut teams = HashMap::new();
+        teams.insert("zeta".to_string(), team(&["coder", "writer"], Some("coder")));
+        teams.insert("beta".to_string(), team(&["coder", "reviewer"], Some("reviewer")));
+        teams.insert("alpha".to_string(), team(&["coder", "ops", "reviewer"], Some("ops")));
+        teams.insert("solo".to_string(), team(&["ops"], None));
+
+        // Without a preference the team the agent leads wins.
+        let picked = resolve_team_for_agent("coder", &[], &teams).unwrap();
+        assert_eq!(picked.team_id, "zeta");
+        assert_eq!(picked.reason, "leader");
+        assert_eq!(picked.candidates, vec!["alpha", "beta", "zeta"]);
+
+        // Otherwise the smallest team id, however the map iterates.
+        let picked = resolve_team_for_agent("writer", &[], &teams).unwrap();
+        assert_eq!((picked.team_id.as_str(), picked.reason), ("zeta", "member"));
+        assert_eq!(find_team_for_agent("reviewer", &teams).unwrap().0, "beta");
+        teams.get_mut("beta").unwrap().leader_agent = None;
+        for _ in 0..10 {
+            assert_eq!(find_team_for_agent("reviewer", &teams).unwrap().0, "alpha");
+        }
+
+        // The conversation's team beats the board team; non-member teams are skipped.
+        let picked = resolve_team_for_agent("coder", &["solo", "beta", "alpha"], &teams).unwrap();
+        assert_eq!((picked.team_id.as_str(), picked.reason), ("beta", "preferred"));
+        assert!(resolve_team_for_agent("nobody", &["alpha"], &teams).is_none());
+    }
+
+    #[test]
+    fn test_teammates_are_scoped_to_selected_team() {
+        let mut teams = HashMap::new();
+        teams.insert("dev".to_string(), team(&["coder", "reviewer"], Some("coder")));
+        teams.insert("ops".to_string(), team(&["coder", "sre"], Some("sre")));
+        let mut agents = HashMap::new();
+        for id in ["coder", "reviewer", "sre"] {
+            agents.insert(id.to_string(), AgentConfig::default());
+        }
+
+        let picked = resolve_team_for_agent("coder", &["ops"], &teams).unwrap();
+        assert!(is_teammate("sre", "coder", &picked.team_id, &teams, &agents));
+        assert!(!is_teammate("reviewer", "coder", &picked.team_id, &teams, &agents));
+    }
+
     #[test]
     fn test_team_routing_to_leader() {
         let mut teams = HashMap::new();
@@ -298,4 +487,90 @@ mod tests {
         let out = resolve_routing_target("board", &teams, &agents);
         assert_eq!(out, vec!["assistant".to_string()]);
     }
+
+    #[test]
+    fn default_agent_falls_back_in_order() {
+        let mut settings = Settings::default();
+        for id in ["assistant", "ops"] {
+            settings.agents.insert(id.to_string(), Default::default());
+        }
+        settings.routing.default_agent = Some("gone".to_string());
+        settings.routing.fallback_agents = vec!["missing".to_string(), "ops".to_string()];
+        assert_eq!(get_default_agent(&settings).as_deref(), Some("ops"));
+
+        settings.routing.fallback_agents.clear();
+        assert_eq!(get_default_agent(&settings).as_deref(), Some("assistant"));
+    }
+
+    #[test]
+    fn disabled_agents_are_skipped_by_routing() {
+        let mut settings = Settings::default();
+        for id in ["assistant", "coder", "reviewer"] {
+            settings.agents.insert(id.to_string(), AgentConfig::default());
+        }
+        settings.teams.insert("dev".to_string(), team(&["coder", "reviewer"], Some("coder")));
+        settings.routing.default_agent = Some("coder".to_string());
+        settings.agents.get_mut("coder").unwrap().enabled = false;
+
+        assert_eq!(get_default_agent(&settings).as_deref(), Some("assistant"));
+        assert_eq!(route_message("hi", Some("coder"), &settings).agent_id, "assistant");
+        assert_eq!(route_message("hi", Some("dev"), &settings).agent_id, "assistant");
+        assert_eq!(route_message("fix the bug in the code", None, &settings).agent_id, "assistant");
+        assert_eq!(route_message("hi", Some("reviewer"), &settings).agent_id, "reviewer");
+        assert!(resolve_routing_target("dev", &settings.teams, &settings.agents).is_empty());
+        assert!(!is_teammate("coder", "reviewer", "dev", &settings.teams, &settings.agents));
+
+        // With nobody enabled the route names the disabled agent and processing rejects it.
+        for agent in settings.agents.values_mut() {
+            agent.enabled = false;
+        }
+        assert_eq!(get_default_agent(&settings), None);
+        assert!(!settings.is_agent_enabled(&route_message("hi", Some("coder"), &settings).agent_id));
+    }
+
+    #[test]
+    fn resolve_target_agent_explains_mentions_teams_and_fallbacks() {
+        let mut settings = Settings::default();
+        for id in ["assistant", "coder", "reviewer"] {
+            settings.agents.insert(id.to_string(), AgentConfig::default());
+        }
+        settings.teams.insert(
+            "dev".to_string(),
+            TeamConfig {
+                name: "Dev".to_string(),
+                agents: vec!["coder".to_string(), "reviewer".to_string()],
+                leader_agent: Some("reviewer".to_string()),
+            },
+        );
+        let resolve = |message: &str, target: Option<&str>, settings: &Settings| {
+            let mut msg = MessageData::new("cli", "cli", "cli", message);
+            msg.agent = target.map(String::from);
+            resolve_target_agent(&msg, settings)
+        };
+
+        let trace = resolve("fix the login bug", Some("coder"), &settings);
+        assert_eq!((trace.mention_kind, trace.agent_id.as_str()), (Some("agent"), "coder"));
+        assert!(trace.matched_rule.is_none());
+
+        let trace = resolve("status please", Some("dev"), &settings);
+        assert_eq!((trace.mention_kind, trace.agent_id.as_str()), (Some("team"), "reviewer"));
+
+        let trace = resolve("status please", Some("ghost"), &settings);
+        assert_eq!((trace.mention_kind, trace.agent_id.as_str()), (Some("unknown"), "assistant"));
+
+        settings.routing.rules.push(RoutingRule {
+            pattern: "invoice".to_string(),
+            agent: "reviewer".to_string(),
+            priority: 0,
+        });
+        let trace = resolve("check this invoice", None, &settings);
+        assert_eq!(trace.matched_rule.as_ref().map(|r| r.agent.as_str()), Some("reviewer"));
+        assert_eq!(trace.agent_id, "reviewer");
+
+        settings.agents.get_mut("reviewer").unwrap().enabled = false;
+        let trace = resolve("check this invoice", None, &settings);
+        assert_eq!((trace.agent_id.as_str(), trace.rerouted_from), ("assistant", None));
+        let trace = resolve("status please", Some("reviewer"), &settings);
+        assert_eq!((trace.agent_id.as_str(), trace.rerouted_from.as_deref()), ("assistant", Some("reviewer")));
+    }
 }
@@ -0,0 +1,471 @@
+//! Persistent task store (`~/.tinyvegeta/tasks.json`) shared by CLI and web API.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{get_home_dir, Settings};
+use crate::error::Error;
+use crate::heartbeat::tasks::{Task, TaskPriority, TaskSpawner};
+
+/// Serializes the load-modify-save of [`begin_task`], [`finish_task`] and
+/// [`cancel_task`] so concurrent runs do not overwrite each other's results.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A stored task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRecord {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub agent_id: Option<String>,
+    pub priority: String,
+    pub status: String,
+    pub tags: Vec<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    /// Ids of tasks that must be `completed` before this one can start.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+impl TaskRecord {
+    /// Create a pending task. Unknown priorities fall back to `medium`.
+    pub fn new(
+        title: &str,
+        description: Option<String>,
+        agent_id: Option<String>,
+        priority: Option<&str>,
+        tags: Vec<String>,
+    ) -> Self {
+        let now = chrono::Utc::now().timestamp_millis();
+        Self {
+            id: ulid::Ulid::new().to_string(),
+            title: title.to_string(),
+            description,
+            agent_id,
+            priority: priority
+                .unwrap_or("medium")
+                .parse::<TaskPriority>()
+                .unwrap_or(TaskPriority::Medium)
+                .to_string(),
+            status: "pending".to_string(),
+            tags,
+            created_at: now,
+            updated_at: now,
+            output: None,
+            error: None,
+            depends_on: Vec::new(),
+        }
+    }
+
+    /// Build a runnable heartbeat task assigned to `agent_id`.
+    pub fn to_task(&self, agent_id: &str) -> Task {
+        let mut task = Task::new(&self.title)
+            .with_agent(agent_id)
+            .with_priority(self.priority.parse::<TaskPriority>().unwrap_or(TaskPriority::Medium));
+        if let Some(desc) = &self.description {
+            task = task.with_description(desc);
+        }
+        for tag in &self.tags {
+            task = task.with_tag(tag);
+        }
+        task
+    }
+}
+
+/// All stored tasks.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TaskStore {
+    pub tasks: Vec<TaskRecord>,
+}
+
+impl TaskStore {
+    /// Find a task by id.
+    pub fn get(&self, id: &str) -> Option<&TaskRecord> {
+        self.tasks.iter().find(|t| t.id == id)
+    }
+
+    /// Find a task by id for update.
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut TaskRecord> {
+        self.tasks.iter_mut().find(|t| t.id == id)
+    }
+
+    /// Add a task after checking its dependencies.
+    ///
+    /// Fails with `NotFound` for a dependency that does not exist and
+    /// `Config` when the dependencies would form a cycle.
+    pub fn add(&mut self, record: TaskRecord) -> Result<(), Error> {
+        for dep in &record.depends_on {
+            if dep != &record.id && self.get(dep).is_none() {
+                return Err(Error::NotFound(format!("dependency task {}", dep)));
+            }
+        }
+        if let Some(cycle) = self.dependency_cycle(&record.id, &record.depends_on) {
+            return Err(Error::Config(format!("dependency cycle: {}", cycle.join(" -> "))));
+        }
+        self.tasks.push(record);
+        Ok(())
+    }
+
+    /// A path `id -> ... -> id` through `depends_on` and the stored tasks'
+    /// dependencies, if one exists.
+    fn dependency_cycle(&self, id: &str, depends_on: &[String]) -> Option<Vec<String>> {
+        let mut stack: Vec<Vec<String>> = depends_on.iter().map(|d| vec![id.to_string(), d.clone()]).collect();
+        let mut seen = std::collections::HashSet::new();
+        while let Some(path) = stack.pop() {
+            let last = path.last().cloned().unwrap_or_default();
+            if last == id {
+                return Some(path);
+            }
+            if !seen.insert(last.clone()) {
+                continue;
+            }
+            for dep in self.get(&last).map(|t| t.depends_on.as_slice()).unwrap_or_default() {
+                let mut next = path.clone();
+                next.push(dep.clone());
+                stack.push(next);
+            }
+        }
+        None
+    }
+
+    /// Dependencies of `record` that are not `completed` yet; a dependency
+    /// that was deleted counts as outstanding.
+    pub fn outstanding_dependencies(&self, record: &TaskRecord) -> Vec<String> {
+        record
+            .depends_on
+            .iter()
+            .filter(|dep| self.get(dep).is_none_or(|t| t.status != "completed"))
+            .cloned()
+            .collect()
+    }
+
+    /// Status to display: `blocked` for a pending task with outstanding
+    /// dependencies, the stored status otherwise.
+    pub fn display_status<'a>(&self, record: &'a TaskRecord) -> &'a str {
+        if record.status == "pending" && !self.outstanding_dependencies(record).is_empty() {
+            "blocked"
+        } else {
+            &record.status
+        }
+    }
+}
+
+/// Path of the task store file.
+pub fn tasks_file_path() -> Result<std::path::PathBuf, Error> {
+    Ok(get_home_dir()?.join("tasks.json"))
+}
+
+/// Load the task store; a missing or unreadable file yields an empty store.
+pub fn load_task_store() -> Result<TaskStore, Error> {
+    let path = tasks_file_path()?;
+    if !path.exists() {
+        return Ok(TaskStore::default());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+/// Save the task store.
+pub fn save_task_store(store: &TaskStore) -> Result<(), Error> {
+    let path = tasks_file_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    crate::config::atomic_write(&path, serde_json::to_string_pretty(store)?)
+}
+
+/// Mark task `id` as running and build the task to run on its agent (its
+/// own, else the default agent).
+///
+/// Fails with `NotFound` for unknown tasks, `AlreadyExists` when the task is
+/// already running, `Blocked` while its dependencies are not completed and
+/// `Config` when the agent does not exist.
+pub fn begin_task(id: &str, settings: &Settings) -> Result<(TaskRecord, Task), Error> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = load_task_store()?;
+    let outstanding = store
+        .get(id)
+        .map(|r| store.outstanding_dependencies(r))
+        .ok_or_else(|| Error::NotFound(format!("task {}", id)))?;
+    if !outstanding.is_empty() {
+        return Err(Error::Blocked(format!(
+            "task {} waits on unfinished dependencies: {}",
+            id,
+            outstanding.join(", ")
+        )));
+    }
+    let record = store
+        .get_mut(id)
+        .ok_or_else(|| Error::NotFound(format!("task {}", id)))?;
+    if record.status == "running" {
+        return Err(Error::AlreadyExists(format!("task {} is already running", id)));
+    }
+
+    let agent_id = record
+        .agent_id
+        .clone()
+        .or_else(|| crate::core::routing::get_default_agent(settings))
+        .unwrap_or_else(|| "assistant".to_string());
+    if !settings.agents.contains_key(&agent_id) {
+        return Err(Error::Config(format!("assigned agent not found: {}", agent_id)));
+    }
+
+    record.status = "running".to_string();
+    record.updated_at = chrono::Utc::now().timestamp_millis();
+    let task = record.to_task(&agent_id);
+    let snapshot = record.clone();
+    save_task_store(&store)?;
+    Ok((snapshot, task))
+}
+
+/// Record the result of a run started with [`begin_task`].
+///
+/// Reloads the store so edits made while the task ran are not clobbered; a
+/// task cancelled or removed meanwhile keeps that state and yields `None`.
+pub fn finish_task(id: &str, result: &Result<String, Error>) -> Result<Option<TaskRecord>, Error> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = load_task_store()?;
+    let Some(record) = store.get_mut(id).filter(|r| r.status == "running") else {
+        return Ok(None);
+    };
+    match result {
+        Ok(out) => {
+            record.status = "completed".to_string();
+            record.output = Some(out.clone());
+            record.error = None;
+        }
+        Err(e) => {
+            record.status = "failed".to_string();
+            record.error = Some(e.to_string());
+        }
+    }
+    record.updated_at = chrono::Utc::now().timestamp_millis();
+    let snapshot = record.clone();
+    save_task_store(&store)?;
+    Ok(Some(snapshot))
+}
+
+/// Mark task `id` cancelled, recording `reason` as its error when given.
+/// Returns `None` for unknown tasks.
+pub fn cancel_task(id: &str, reason: Option<String>) -> Result<Option<TaskRecord>, Error> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = load_task_store()?;
+    let Some(record) = store.get_mut(id) else {
+        return Ok(None);
+    };
+    record.status = "cancelled".to_string();
+    if reason.is_some() {
+        record.error = reason;
+    }
+    record.updated_at = chrono::Utc::now().timestamp_millis();
+    let snapshot = record.clone();
+    save_task_store(&store)?;
+    Ok(Some(snapshot))
+}
+
+/// Outcome of [`run_tasks`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BatchSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Selected tasks whose dependencies never completed.
+    pub blocked: Vec<String>,
+}
+
+/// Ids of stored tasks with status `status` (`blocked` matches pending tasks
+/// with outstanding dependencies) and, if given, assigned to `agent`.
+pub fn select_tasks(store: &TaskStore, status: &str, agent: Option<&str>) -> Vec<String> {
+    store
+        .tasks
+        .iter()
+        .filter(|t| t.status.eq_ignore_ascii_case(status) || store.display_status(t).eq_ignore_ascii_case(status))
+        .filter(|t| agent.is_none_or(|a| t.agent_id.as_deref() == Some(a)))
+        .map(|t| t.id.clone())
+        .collect()
+}
+
+/// Run the tasks `ids`, up to `max` at a time, saving each result as it
+/// finishes and passing it to `report`.
+///
+/// Tasks run in waves: a task waiting on another selected task starts once
+/// that one has completed; tasks still waiting when nothing else can run
+/// are left untouched and listed in [`BatchSummary::blocked`].
+pub async fn run_tasks(
+    ids: Vec<String>,
+    settings: &Settings,
+    max: usize,
+    mut report: impl FnMut(&str, &Result<String, Error>),
+) -> Result<BatchSummary, Error> {
+    let mut summary = BatchSummary::default();
+    let mut remaining = ids;
+    loop {
+        let store = load_task_store()?;
+        let (ready, waiting): (Vec<String>, Vec<String>) = remaining.into_iter().partition(|id| {
+            store
+                .get(id)
+                .is_some_and(|r| store.outstanding_dependencies(r).is_empty())
+        });
+        remaining = waiting;
+        if ready.is_empty() {
+            break;
+        }
+
+        let mut running = tokio::task::JoinSet::new();
+        let mut ready = ready.into_iter();
+        loop {
+            while running.len() < max.max(1) {
+                let Some(id) = ready.next() else { break };
+                let settings = settings.clone();
+                running.spawn(async move {
+                    let result = match begin_task(&id, &settings) {
+                        Ok((_, task)) => {
+                            let result = TaskSpawner::spawn_task(&task, &settings).await;
+                            finish_task(&id, &result).map(|_| result)
+                        }
+                        Err(e) => Ok(Err(e)),
+                    };
+                    (id, result)
+                });
+            }
+            let Some(joined) = running.join_next().await else { break };
+            let (id, result) = joined.map_err(|e| Error::Other(format!("task run panicked: {}", e)))?;
+            let result = result?;
+            if result.is_ok() {
+                summary.succeeded += 1;
+            } else {
+                summary.failed += 1;
+            }
+            report(&id, &result);
+        }
+    }
+    summary.blocked = remaining;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        begin_task, cancel_task, finish_task, load_task_store, run_tasks, save_task_store, select_tasks, BatchSummary, TaskRecord,
+        TaskStore,
+    };
+    use crate::config::{test_support::TempHome, AgentConfig, Settings};
+    use crate::error::Error;
+
+    fn task(title: &str, depends_on: &[&str]) -> TaskRecord {
+        let mut record = TaskRecord::new(title, None, Some("coder".to_string()), None, Vec::new());
+        record.depends_on = depends_on.iter().map(|d| d.to_string()).collect();
+        record
+    }
+
+    #[test]
+    fn tasks_wait_for_their_dependencies() {
+        let _home = TempHome::new();
+        let mut settings = Settings::default();
+        settings.agents.insert("coder".to_string(), AgentConfig::default());
+
+        let build = task("build", &[]);
+        let deploy = task("deploy", &[&build.id]);
+        let (build_id, deploy_id) = (build.id.clone(), deploy.id.clone());
+        let mut store = TaskStore::default();
+        store.add(build).unwrap();
+        store.add(deploy).unwrap();
+        assert_eq!(store.display_status(store.get(&deploy_id).unwrap()), "blocked");
+        save_task_store(&store).unwrap();
+
+        let err = begin_task(&deploy_id, &settings).unwrap_err();
+        assert!(matches!(err, Error::Blocked(ref m) if m.contains(&build_id)), "{}", err);
+
+        begin_task(&build_id, &settings).unwrap();
+        finish_task(&build_id, &Ok("built".to_string())).unwrap();
+        let store = load_task_store().unwrap();
+        let deploy = store.get(&deploy_id).unwrap();
+        assert!(store.outstanding_dependencies(deploy).is_empty());
+        assert_eq!(store.display_status(deploy), "pending");
+        begin_task(&deploy_id, &settings).unwrap();
+    }
+
+    #[test]
+    fn cancelled_runs_keep_their_cancellation() {
+        let _home = TempHome::new();
+        let mut settings = Settings::default();
+        settings.agents.insert("coder".to_string(), AgentConfig::default());
+        let record = task("long job", &[]);
+        let id = record.id.clone();
+        let mut store = TaskStore::default();
+        store.add(record).unwrap();
+        save_task_store(&store).unwrap();
+
+        begin_task(&id, &settings).unwrap();
+        let cancelled = cancel_task(&id, Some("stopped by user".to_string())).unwrap().unwrap();
+        assert_eq!((cancelled.status.as_str(), cancelled.error.as_deref()), ("cancelled", Some("stopped by user")));
+        assert!(finish_task(&id, &Ok("late".to_string())).unwrap().is_none());
+        assert_eq!(load_task_store().unwrap().get(&id).unwrap().status, "cancelled");
+        assert!(cancel_task("missing", None).unwrap().is_none());
+    }
+
+    #[test]
+    fn dependency_cycles_and_unknown_dependencies_are_rejected() {
+        let mut store = TaskStore::default();
+        let a = task("a", &[]);
+        let mut b = task("b", &[&a.id]);
+        let a_id = a.id.clone();
+        // `a` was edited by hand to wait on `b`, which does not exist yet.
+        store.tasks.push(TaskRecord { depends_on: vec![b.id.clone()], ..a });
+        let err = store.add(b.clone()).unwrap_err();
+        assert!(matches!(err, Error::Config(ref m) if m.contains("cycle")), "{}", err);
+
+        b.depends_on = vec![b.id.clone()];
+        assert!(matches!(store.add(b.clone()), Err(Error::Config(_))));
+        b.depends_on = vec!["missing".to_string()];
+        assert!(matches!(store.add(b), Err(Error::NotFound(_))));
+        assert_eq!(store.tasks.len(), 1);
+        assert_eq!(store.tasks[0].id, a_id);
+    }
+
+    #[tokio::test]
+    async fn batch_runs_drain_pending_tasks_in_dependency_order() {
+        use axum::{routing::post, Json, Router};
+
+        let _home = TempHome::new();
+        let chat = Router::new().route(
+            "/api/chat",
+            post(|| async { Json(serde_json::json!({ "message": { "role": "assistant", "content": "done" } })) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, chat).await.unwrap() });
+
+        let mut settings = Settings::default();
+        settings.models.provider = "ollama".to_string();
+        settings.models.ollama.base_url = Some(url);
+        settings.agents.insert("coder".to_string(), AgentConfig::default());
+
+        let first = task("first", &[]);
+        let second = task("second", &[&first.id]);
+        let third = task("third", &[]);
+        let mut store = TaskStore::default();
+        for t in [first, second, third] {
+            store.add(t).unwrap();
+        }
+        save_task_store(&store).unwrap();
+
+        let ids = select_tasks(&store, "pending", Some("coder"));
+        assert_eq!(ids.len(), 3);
+        assert!(select_tasks(&store, "pending", Some("other")).is_empty());
+
+        let mut finished = Vec::new();
+        let summary = run_tasks(ids, &settings, 2, |id, _| finished.push(id.to_string())).await.unwrap();
+        assert_eq!(summary, BatchSummary { succeeded: 3, failed: 0, blocked: Vec::new() });
+        // `second` waits for `first`, so it finishes last.
+        assert_eq!(finished.last(), Some(&store.tasks[1].id));
+
+        let store = load_task_store().unwrap();
+        for t in &store.tasks {
+            assert_eq!((t.status.as_str(), t.output.as_deref()), ("completed", Some("done")), "{}", t.title);
+        }
+    }
+}
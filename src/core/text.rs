@@ -0,0 +1,292 @@
+//! String helpers shared by the CLI, daemon and channel handlers.
+
+/// Longest prefix of `s` holding at most `max_chars` characters.
+///
+/// Unlike `&s[..n]` this never splits a multi-byte character, so it cannot
+/// panic on emoji or non-Latin text. Returns `s` unchanged when it is short enough.
+pub fn truncate_chars(s: &str, max_chars: usize) -> &str {
+    match s.char_indices().nth(max_chars) {
+        Some((idx, _)) => &s[..idx],
+        None => s,
+    }
+}
+
+/// Telegram's limit on the length of one message, in characters.
+pub const TELEGRAM_MESSAGE_LIMIT: usize = 4096;
+
+/// Room kept in each part for the `(i/n)` counter line.
+const COUNTER_RESERVE: usize = 16;
+
+/// Split `text` into Telegram-sized messages; see [`split_numbered`].
+pub fn split_for_telegram(text: &str) -> Vec<String> {
+    split_numbered(text, TELEGRAM_MESSAGE_LIMIT)
+}
+
+/// Split `text` into parts of at most `max_chars` characters, each prefixed
+/// with an `(i/n)` counter line when there is more than one.
+///
+/// Parts break at paragraph, then line, then word boundaries, and never inside
+/// a code fence or a `*`/`_`/`` ` `` entity unless a single line leaves no choice.
+pub fn split_numbered(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.min(TELEGRAM_MESSAGE_LIMIT);
+    if text.chars().count() <= max_chars {
+        return vec![text.to_string()];
+    }
+    let parts = split_markdown(text, max_chars.saturating_sub(COUNTER_RESERVE).max(1));
+    let total = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, part)| format!("{}\n{}", chunk_counter(i + 1, total), part))
+        .collect()
+}
+
+/// `(n/total)` label for one part of a split message.
+pub fn chunk_counter(n: usize, total: usize) -> String {
+    format!("({}/{})", n, total)
+}
+
+fn char_len(s: &str) -> usize {
+    s.chars().count()
+}
+
+/// Fenced code blocks and paragraphs (with their trailing blank lines).
+fn markdown_blocks(text: &str) -> Vec<String> {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    let mut after_blank = false;
+    for line in text.lines() {
+        let is_fence = line.trim_start().starts_with("```");
+        let blank = line.trim().is_empty();
+        if in_fence {
+            current.push(line);
+            if is_fence {
+                in_fence = false;
+                blocks.push(current.join("\n"));
+                current.clear();
+            }
+            continue;
+        }
+        if (is_fence || (after_blank && !blank)) && !current.is_empty() {
+            blocks.push(current.join("\n"));
+            current.clear();
+        }
+        current.push(line);
+        in_fence = is_fence;
+        after_blank = blank;
+    }
+    if !current.is_empty() {
+        blocks.push(current.join("\n"));
+    }
+    blocks
+}
+
+/// Pack whole blocks into parts, splitting only blocks that cannot fit alone.
+fn split_markdown(text: &str, max_chars: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for block in markdown_blocks(text) {
+        for piece in fit_block(&block, max_chars) {
+            if !current.is_empty() && char_len(&current) + 1 + char_len(&piece) > max_chars {
+                parts.push(current.trim_end().to_string());
+                current.clear();
+            }
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(&piece);
+        }
+    }
+    if !current.trim().is_empty() {
+        parts.push(current.trim_end().to_string());
+    }
+    parts
+}
+
+/// Break one block into pieces of at most `max_chars`. Code fences are split
+/// between lines and each piece is re-fenced, so every piece stays balanced.
+fn fit_block(block: &str, max_chars: usize) -> Vec<String> {
+    if char_len(block) <= max_chars {
+        return vec![block.to_string()];
+    }
+    let lines: Vec<&str> = block.lines().collect();
+    let fenced = lines.first().is_some_and(|l| l.trim_start().starts_with("```"));
+    if !fenced {
+        return pack_lines(lines.iter().flat_map(|l| split_line(l, max_chars)), max_chars, "", "");
+    }
+
+    let open = lines[0];
+    let closed = lines.len() > 1 && lines[lines.len() - 1].trim_start().starts_with("```");
+    let body = &lines[1..if closed { lines.len() - 1 } else { lines.len() }];
+    let overhead = char_len(open) + 5;
+    if overhead >= max_chars {
+        // No room to re-fence; fall back to plain line splitting.
+        return pack_lines(lines.iter().flat_map(|l| hard_split(l, max_chars)), max_chars, "", "");
+    }
+    let room = max_chars - overhead;
+    let prefix = format!("{}\n", open);
+    pack_lines(body.iter().flat_map(|l| hard_split(l, room)), max_chars, &prefix, "\n```")
+}
+
+/// Greedily join lines into pieces of at most `max_chars`, each wrapped in
+/// `prefix`/`suffix`.
+fn pack_lines(lines: impl Iterator<Item = String>, max_chars: usize, prefix: &str, suffix: &str) -> Vec<String> {
+    let overhead = char_len(prefix) + char_len(suffix);
+    let mut pieces = Vec::new();
+    let mut current: Option<String> = None;
+    for line in lines {
+        if let Some(body) = current.as_mut() {
+            if overhead + char_len(body) + 1 + char_len(&line) <= max_chars {
+                body.push('\n');
+                body.push_str(&line);
+                continue;
+            }
+            pieces.push(format!("{}{}{}", prefix, body, suffix));
+        }
+        current = Some(line);
+    }
+    if let Some(body) = current {
+        pieces.push(format!("{}{}{}", prefix, body, suffix));
+    }
+    pieces
+}
+
+/// Split a prose line at spaces where no `*`, `_` or `` ` `` entity is open,
+/// then at any space, then anywhere.
+fn split_line(line: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest = line;
+    while char_len(rest) > max_chars {
+        let window = truncate_chars(rest, max_chars);
+        let spaces: Vec<usize> = window.match_indices(' ').map(|(i, _)| i).filter(|&i| i > 0).collect();
+        let cut = spaces
+            .iter()
+            .rev()
+            .find(|&&i| entities_closed(&rest[..i]))
+            .or(spaces.last())
+            .copied()
+            .unwrap_or(window.len());
+        pieces.push(rest[..cut].to_string());
+        rest = rest[cut..].trim_start_matches(' ');
+    }
+    pieces.push(rest.to_string());
+    pieces
+}
+
+/// Split a code line anywhere, keeping characters whole.
+fn hard_split(line: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest = line;
+    while char_len(rest) > max_chars {
+        let head = truncate_chars(rest, max_chars);
+        pieces.push(head.to_string());
+        rest = &rest[head.len()..];
+    }
+    pieces.push(rest.to_string());
+    pieces
+}
+
+/// Line diff of `old` against `new` as one unified-style hunk: `-` lines
+/// removed, `+` lines added, ` ` lines shared, under `--- old_label` and
+/// `+++ new_label` headers.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    // lcs[i][j]: longest common subsequence of a[i..] and b[j..].
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n@@ -1,{} +1,{} @@\n", old_label, new_label, a.len(), b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out.push_str(&format!(" {}\n", a[i]));
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push_str(&format!("-{}\n", a[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+{}\n", b[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
+fn entities_closed(text: &str) -> bool {
+    let doubles_closed = ["**", "__"].iter().all(|m| text.matches(*m).count() % 2 == 0);
+    let singles = text.replace("**", "").replace("__", "");
+    doubles_closed && ['*', '_', '`'].iter().all(|m| singles.matches(*m).count() % 2 == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_chars_respects_char_boundaries() {
+        let s = "é世👋".repeat(2000);
+        // `&s[..3500]` would land inside a codepoint and panic.
+        assert!(!s.is_char_boundary(3500));
+        let cut = truncate_chars(&s, 3500);
+        assert_eq!(cut.chars().count(), 3500);
+        assert!(s.starts_with(cut));
+
+        assert_eq!(truncate_chars("👋🌍", 1), "👋");
+        assert_eq!(truncate_chars("short", 10), "short");
+        assert_eq!(truncate_chars("", 0), "");
+    }
+
+    #[test]
+    fn split_keeps_code_fences_and_entities_whole() {
+        let fence = format!("```rust\n{}```", "let x = 1;\n".repeat(12));
+        let text = format!("Intro paragraph.\n\n{}\n\nSome **bold words here** and more.", fence);
+        let parts = split_numbered(&text, 80);
+        assert!(parts.len() > 2);
+        for part in &parts {
+            assert!(part.chars().count() <= 80, "part too long: {:?}", part);
+            assert_eq!(part.matches("```").count() % 2, 0, "unbalanced fence: {:?}", part);
+            assert_eq!(part.matches("**").count() % 2, 0, "split entity: {:?}", part);
+        }
+        assert!(parts[0].ends_with("Intro paragraph."));
+        assert!(parts.iter().any(|p| p.contains("```rust\nlet x = 1;")));
+        let body: String = parts.iter().map(|p| p.replace("```rust\n", "").replace("\n```", "")).collect();
+        assert_eq!(body.matches("let x = 1;").count(), 12);
+
+        let prose = "word ".repeat(30) + "**not split here** tail";
+        let parts = split_numbered(prose.trim(), 60);
+        assert!(parts.iter().all(|p| p.matches("**").count() % 2 == 0));
+    }
+
+    #[test]
+    fn split_parts_are_numbered() {
+        assert_eq!(chunk_counter(1, 3), "(1/3)");
+        assert_eq!(split_for_telegram("short"), vec!["short".to_string()]);
+
+        let text = vec!["line"; 40].join("\n\n");
+        let parts = split_numbered(&text, 50);
+        let total = parts.len();
+        assert!(total > 1);
+        for (i, part) in parts.iter().enumerate() {
+            assert!(part.starts_with(&format!("({}/{})\n", i + 1, total)));
+        }
+        assert_eq!(parts.iter().map(|p| p.matches("line").count()).sum::<usize>(), 40);
+    }
+
+    #[test]
+    fn unified_diff_marks_changed_lines() {
+        let diff = unified_diff("a\nb\nc", "a\nB\nc\nd", "old", "new");
+        assert_eq!(diff, "--- old\n+++ new\n@@ -1,3 +1,4 @@\n a\n-b\n+B\n c\n+d\n");
+        assert_eq!(unified_diff("same", "same", "x", "y"), "--- x\n+++ y\n@@ -1,1 +1,1 @@\n same\n");
+    }
+}
@@ -0,0 +1,237 @@
+//! Bounded concurrent draining of the incoming queue.
+//!
+//! Messages run in parallel up to `max_concurrency`, except that messages of
+//! one conversation run one at a time in queue order. A message is moved to
+//! `processing/` before its task is spawned, so it is never picked up twice,
+//! and removed from there once its own task finishes.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::task::{Id, JoinSet};
+
+use crate::core::queue::{MessageData, Queue};
+use crate::error::Error;
+
+/// Messages sharing this key are processed serially.
+pub fn serialization_key(data: &MessageData) -> String {
+    data.conversation_id
+        .clone()
+        .unwrap_or_else(|| data.conversation_scope())
+}
+
+/// Spawns queue messages onto a bounded [`JoinSet`].
+pub struct QueueWorker<H> {
+    handler: H,
+    max_concurrency: usize,
+    tasks: JoinSet<()>,
+    /// Serialization key and message id of every running task.
+    running: HashMap<Id, (String, String)>,
+}
+
+impl<H, Fut, E> QueueWorker<H>
+where
+    H: Fn(MessageData) -> Fut,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Display + Send + 'static,
+{
+    pub fn new(max_concurrency: usize, handler: H) -> Self {
+        Self {
+            handler,
+            max_concurrency: max_concurrency.max(1),
+            tasks: JoinSet::new(),
+            running: HashMap::new(),
+        }
+    }
+
+    /// Number of messages currently being processed.
+    pub fn in_flight(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Ids of the messages currently being processed.
+    pub fn running_ids(&self) -> HashSet<String> {
+        self.running.values().map(|(_, id)| id.clone()).collect()
+    }
+
+    /// Claim and spawn every incoming message that fits: free capacity and
+    /// no earlier message of its conversation still running or waiting.
+    ///
+    /// Returns how many messages were spawned.
+    pub fn dispatch(&mut self) -> Result<usize, Error> {
+        let mut blocked: HashSet<String> = self.running.values().map(|(key, _)| key.clone()).collect();
+        let mut spawned = 0;
+
+        for msg_file in Queue::incoming()? {
+            if self.tasks.len() >= self.max_concurrency {
+                break;
+            }
+            let key = serialization_key(&msg_file.data);
+            // Later messages of a busy conversation wait their turn.
+            if !blocked.insert(key.clone()) {
+                continue;
+            }
+            if let Err(e) = Queue::mark_processing(&msg_file.id) {
+                tracing::debug!("Skipping message {}: {}", msg_file.id, e);
+                continue;
+            }
+
+            let id = msg_file.id;
+            let message_id = id.clone();
+            let work = (self.handler)(msg_file.data);
+            let handle = self.tasks.spawn(async move {
+                if let Err(e) = work.await {
+                    tracing::error!("Failed to process message {}: {}", id, e);
+                }
+                // Removed even on failure so a broken message is not retried forever.
+                if let Err(e) = Queue::remove_processing(&id) {
+                    tracing::error!("Failed to remove message {}: {}", id, e);
+                }
+            });
+            self.running.insert(handle.id(), (key, message_id));
+            spawned += 1;
+        }
+
+        Ok(spawned)
+    }
+
+    /// Wait up to `timeout` for a running message to finish, then release
+    /// every finished message's conversation.
+    pub async fn reap(&mut self, timeout: Duration) {
+        if self.tasks.is_empty() {
+            tokio::time::sleep(timeout).await;
+            return;
+        }
+        if let Ok(Some(result)) = tokio::time::timeout(timeout, self.tasks.join_next_with_id()).await {
+            self.finish(result);
+        }
+        while let Some(result) = self.tasks.try_join_next_with_id() {
+            self.finish(result);
+        }
+    }
+
+    /// Wait for every running message to finish.
+    #[cfg(test)]
+    pub async fn join_all(&mut self) {
+        while let Some(result) = self.tasks.join_next_with_id().await {
+            self.finish(result);
+        }
+    }
+
+    fn finish(&mut self, result: Result<(Id, ()), tokio::task::JoinError>) {
+        let id = match result {
+            Ok((id, ())) => id,
+            Err(e) => {
+                tracing::error!("Queue task panicked: {}", e);
+                e.id()
+            }
+        };
+        self.running.remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    type Work = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+    /// Stands in for a slow provider call and logs start/end per message.
+    fn slow_provider(log: Arc<Mutex<Vec<String>>>) -> impl Fn(MessageData) -> Work {
+        move |data: MessageData| -> Work {
+            let log = log.clone();
+            Box::pin(async move {
+                log.lock().unwrap().push(format!("start {}", data.message));
+                tokio::time::sleep(Duration::from_millis(150)).await;
+                log.lock().unwrap().push(format!("end {}", data.message));
+                Ok(())
+            })
+        }
+    }
+
+    fn enqueue(message: &str, conversation: &str) {
+        let mut data = MessageData::new("cli", "tester", "tester", message);
+        data.conversation_id = Some(conversation.to_string());
+        Queue::enqueue(data, 0).unwrap();
+        // Distinct timestamps keep FIFO order unambiguous.
+        std::thread::sleep(Duration::from_millis(2));
+    }
+
+    async fn drain(worker: &mut QueueWorker<impl Fn(MessageData) -> Work>) {
+        loop {
+            worker.dispatch().unwrap();
+            if worker.in_flight() == 0 && Queue::incoming().unwrap().is_empty() {
+                break;
+            }
+            worker.reap(Duration::from_millis(20)).await;
+        }
+        worker.join_all().await;
+    }
+
+    #[tokio::test]
+    async fn different_conversations_run_in_parallel() {
+        let _home = crate::config::test_support::TempHome::new();
+        crate::core::queue::ensure_queue_dirs().unwrap();
+        for (msg, conv) in [("a", "c1"), ("b", "c2"), ("c", "c3")] {
+            enqueue(msg, conv);
+        }
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut worker = QueueWorker::new(3, slow_provider(log.clone()));
+        assert_eq!(worker.dispatch().unwrap(), 3);
+        assert!(Queue::incoming().unwrap().is_empty());
+        assert_eq!(Queue::processing().unwrap().len(), 3);
+
+        let started = Instant::now();
+        worker.join_all().await;
+        // Three 150ms calls overlapping finish well under their serial 450ms.
+        assert!(started.elapsed() < Duration::from_millis(400));
+        assert!(Queue::processing().unwrap().is_empty());
+        let log = log.lock().unwrap();
+        assert!(log[..3].iter().all(|e| e.starts_with("start")));
+    }
+
+    #[tokio::test]
+    async fn one_conversation_runs_serially_in_order() {
+        let _home = crate::config::test_support::TempHome::new();
+        crate::core::queue::ensure_queue_dirs().unwrap();
+        enqueue("first", "shared");
+        enqueue("other", "solo");
+        enqueue("second", "shared");
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut worker = QueueWorker::new(3, slow_provider(log.clone()));
+        // "second" waits behind "first" despite free capacity.
+        assert_eq!(worker.dispatch().unwrap(), 2);
+        assert_eq!(Queue::incoming().unwrap().len(), 1);
+        assert_eq!(worker.dispatch().unwrap(), 0);
+
+        drain(&mut worker).await;
+        let log = log.lock().unwrap();
+        let pos = |e: &str| log.iter().position(|l| l == e).unwrap();
+        assert!(pos("end first") < pos("start second"));
+        assert!(pos("start other") < pos("end first"));
+        assert!(Queue::processing().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn concurrency_is_bounded() {
+        let _home = crate::config::test_support::TempHome::new();
+        crate::core::queue::ensure_queue_dirs().unwrap();
+        for (msg, conv) in [("a", "c1"), ("b", "c2"), ("c", "c3")] {
+            enqueue(msg, conv);
+        }
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut worker = QueueWorker::new(2, slow_provider(log.clone()));
+        assert_eq!(worker.dispatch().unwrap(), 2);
+        assert_eq!(worker.in_flight(), 2);
+        drain(&mut worker).await;
+        assert_eq!(log.lock().unwrap().len(), 6);
+    }
+}
@@ -0,0 +1,230 @@
+//! Daemon backends: a tmux session, or a plain background process for
+//! environments without tmux.
+//!
+//! The process backend spawns `start-internal` detached, records its PID in
+//! `~/.tinyvegeta/daemon.pid` and stops it by signalling that PID.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::config::{get_home_dir, DaemonBackend};
+use crate::error::Error;
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// PID file of a process-backend daemon, in the home directory.
+pub const PID_FILE: &str = "daemon.pid";
+
+/// Where a process-backend daemon's stdout/stderr go, in the home directory.
+pub const OUTPUT_FILE: &str = "daemon.out";
+
+/// How long `stop` waits after SIGTERM before sending SIGKILL.
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Path of [`PID_FILE`].
+pub fn pid_file_path() -> Result<PathBuf> {
+    Ok(get_home_dir()?.join(PID_FILE))
+}
+
+/// Start the daemon.
+pub fn start(backend: DaemonBackend, binary_path: &str) -> Result<()> {
+    match backend {
+        DaemonBackend::Tmux => crate::tmux::start_daemon(binary_path),
+        DaemonBackend::Process => {
+            let home = get_home_dir()?;
+            std::fs::create_dir_all(&home)?;
+            let output = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(home.join(OUTPUT_FILE))?;
+            // The profile is passed on through the inherited environment.
+            let mut cmd = Command::new(binary_path);
+            cmd.arg("start-internal")
+                .stdin(Stdio::null())
+                .stdout(output.try_clone()?)
+                .stderr(output);
+            // Own process group, so Ctrl-C in the starting shell does not reach it.
+            #[cfg(unix)]
+            std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+            let pid = spawn_recorded(&pid_file_path()?, cmd)?;
+            tracing::info!("Started TinyVegeta daemon as process {}", pid);
+            Ok(())
+        }
+    }
+}
+
+/// Stop the daemon.
+pub fn stop(backend: DaemonBackend) -> Result<()> {
+    match backend {
+        DaemonBackend::Tmux => crate::tmux::stop_daemon(),
+        DaemonBackend::Process => {
+            let pid = stop_recorded(&pid_file_path()?)?;
+            tracing::info!("Stopped TinyVegeta daemon process {}", pid);
+            Ok(())
+        }
+    }
+}
+
+/// Restart the daemon.
+pub fn restart(backend: DaemonBackend, binary_path: &str) -> Result<()> {
+    // Try to stop first (ignore error if not running)
+    let _ = stop(backend);
+    start(backend, binary_path)
+}
+
+/// Whether the daemon is running.
+pub fn is_running(backend: DaemonBackend) -> Result<bool> {
+    match backend {
+        DaemonBackend::Tmux => crate::tmux::session_exists(),
+        DaemonBackend::Process => Ok(running_pid(&pid_file_path()?)?.is_some()),
+    }
+}
+
+/// Status text for `status` and `/status`.
+pub fn get_status(backend: DaemonBackend) -> Result<String> {
+    match backend {
+        DaemonBackend::Tmux => crate::tmux::get_status(),
+        DaemonBackend::Process => process_status(&pid_file_path()?),
+    }
+}
+
+/// Attach to the daemon's terminal; only the tmux backend has one.
+pub fn attach(backend: DaemonBackend) -> Result<()> {
+    match backend {
+        DaemonBackend::Tmux => crate::tmux::attach(),
+        DaemonBackend::Process => Err(Error::Other(
+            "attach is not supported by the process daemon backend; follow it with 'tinyvegeta logs all'"
+                .to_string(),
+        )),
+    }
+}
+
+/// Spawn `cmd` and record its PID in `pid_file`. Fails while the recorded
+/// process is still running.
+fn spawn_recorded(pid_file: &Path, mut cmd: Command) -> Result<u32> {
+    if let Some(pid) = running_pid(pid_file)? {
+        return Err(Error::AlreadyExists(format!(
+            "daemon process {} is running. Stop it first with 'tinyvegeta stop'.",
+            pid
+        )));
+    }
+    let pid = cmd.spawn()?.id();
+    if let Some(parent) = pid_file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(pid_file, pid.to_string())?;
+    Ok(pid)
+}
+
+/// Send SIGTERM to the process recorded in `pid_file`, then SIGKILL if it is
+/// still alive after [`STOP_TIMEOUT`], and remove the file.
+fn stop_recorded(pid_file: &Path) -> Result<u32> {
+    let Some(pid) = running_pid(pid_file)? else {
+        return Err(Error::NotFound("daemon process. Is TinyVegeta running?".to_string()));
+    };
+    signal(pid, "TERM")?;
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while is_alive(pid) {
+        if Instant::now() >= deadline {
+            signal(pid, "KILL")?;
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    let _ = std::fs::remove_file(pid_file);
+    Ok(pid)
+}
+
+/// The PID in `pid_file` if that process is alive. A stale or unreadable
+/// file is removed.
+fn running_pid(pid_file: &Path) -> Result<Option<u32>> {
+    let content = match std::fs::read_to_string(pid_file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    match content.trim().parse::<u32>() {
+        Ok(pid) if is_alive(pid) => Ok(Some(pid)),
+        _ => {
+            let _ = std::fs::remove_file(pid_file);
+            Ok(None)
+        }
+    }
+}
+
+fn process_status(pid_file: &Path) -> Result<String> {
+    Ok(match running_pid(pid_file)? {
+        Some(pid) => format!("Status: running\nBackend: process (pid {})", pid),
+        None => "Status: stopped".to_string(),
+    })
+}
+
+/// Whether `pid` is a live process. An exited child that was not reaped yet
+/// (a zombie) counts as gone.
+fn is_alive(pid: u32) -> bool {
+    let proc = Path::new("/proc");
+    if proc.is_dir() {
+        return std::fs::read_to_string(proc.join(pid.to_string()).join("stat"))
+            .ok()
+            .and_then(|stat| stat.rsplit_once(')').map(|(_, rest)| rest.trim_start().to_string()))
+            .is_some_and(|rest| !rest.starts_with('Z'));
+    }
+    signal(pid, "0").is_ok()
+}
+
+/// `kill -<sig> <pid>` through the shell builtin, which minimal images have
+/// even without procps.
+fn signal(pid: u32, sig: &str) -> Result<()> {
+    let status = Command::new("sh")
+        .args(["-c", &format!("kill -{} {}", sig, pid)])
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Other(format!("failed to send SIG{} to process {}", sig, pid)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{process_status, running_pid, spawn_recorded, stop_recorded};
+    use crate::error::Error;
+    use std::process::Command;
+
+    fn sleeper() -> Command {
+        let mut cmd = Command::new("sleep");
+        cmd.arg("30");
+        cmd
+    }
+
+    #[test]
+    fn pid_file_tracks_the_daemon_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("daemon.pid");
+        assert_eq!(running_pid(&pid_file).unwrap(), None);
+        assert_eq!(process_status(&pid_file).unwrap(), "Status: stopped");
+
+        let pid = spawn_recorded(&pid_file, sleeper()).unwrap();
+        assert_eq!(std::fs::read_to_string(&pid_file).unwrap(), pid.to_string());
+        assert_eq!(running_pid(&pid_file).unwrap(), Some(pid));
+        assert!(process_status(&pid_file).unwrap().contains(&format!("running\nBackend: process (pid {})", pid)));
+        assert!(matches!(spawn_recorded(&pid_file, sleeper()), Err(Error::AlreadyExists(_))));
+
+        assert_eq!(stop_recorded(&pid_file).unwrap(), pid);
+        assert!(!pid_file.exists());
+        assert_eq!(process_status(&pid_file).unwrap(), "Status: stopped");
+        assert!(matches!(stop_recorded(&pid_file), Err(Error::NotFound(_))));
+    }
+
+    #[test]
+    fn stale_pid_files_are_cleared() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("daemon.pid");
+        for stale in ["4194303", "not a pid"] {
+            std::fs::write(&pid_file, stale).unwrap();
+            assert_eq!(running_pid(&pid_file).unwrap(), None);
+            assert!(!pid_file.exists());
+        }
+    }
+}
@@ -0,0 +1,434 @@
+//! Discord bot client - REST polling version.
+//!
+//! The daemon polls each allowed channel for new messages rather than holding
+//! a gateway connection, so the bot needs the Message Content intent to see
+//! message text.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::{load_settings, DiscordConfig};
+use crate::core::text::split_numbered;
+use crate::error::Error;
+use crate::telegram::pairing::{PairingManager, PairingOutcome};
+
+/// Discord REST API base URL.
+const API_BASE: &str = "https://discord.com/api/v10";
+
+/// Pause between polls of the allowed channels.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Longest message Discord accepts.
+const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+/// A channel message, as returned by the REST API.
+#[derive(Deserialize, Clone, Debug)]
+pub struct DiscordMessage {
+    pub id: String,
+    pub channel_id: String,
+    #[serde(default)]
+    pub content: String,
+    pub author: DiscordUser,
+}
+
+/// Author of a [`DiscordMessage`].
+#[derive(Deserialize, Clone, Debug)]
+pub struct DiscordUser {
+    pub id: String,
+    pub username: String,
+    #[serde(default)]
+    pub global_name: Option<String>,
+    #[serde(default)]
+    pub bot: bool,
+}
+
+impl DiscordUser {
+    fn display_name(&self) -> &str {
+        self.global_name.as_deref().unwrap_or(&self.username)
+    }
+}
+
+#[derive(Deserialize)]
+struct DiscordChannel {
+    #[serde(default)]
+    guild_id: Option<String>,
+}
+
+/// The Discord calls the bot makes; implemented over HTTP by
+/// [`HttpDiscordClient`].
+#[async_trait]
+pub trait DiscordClient: Send + Sync {
+    /// Messages in `channel_id` newer than `after` (the latest ones when
+    /// `None`), oldest first.
+    async fn fetch_messages(&self, channel_id: &str, after: Option<&str>) -> Result<Vec<DiscordMessage>, Error>;
+
+    /// Guild (server) the channel belongs to; `None` for direct messages.
+    async fn channel_guild(&self, channel_id: &str) -> Result<Option<String>, Error>;
+
+    /// Post `text` to `channel_id`, as a reply to `reply_to` when given.
+    async fn send_message(&self, channel_id: &str, reply_to: Option<&str>, text: &str) -> Result<(), Error>;
+}
+
+/// [`DiscordClient`] over the Discord REST API.
+pub struct HttpDiscordClient {
+    client: Client,
+    token: String,
+    base_url: String,
+}
+
+impl HttpDiscordClient {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            token: token.into(),
+            base_url: API_BASE.to_string(),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        let response = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .header("Authorization", format!("Bot {}", self.token))
+            .send()
+            .await
+            .map_err(|e| Error::Discord(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::Discord(format!("GET {} failed: {}", path, status)));
+        }
+        response.json().await.map_err(|e| Error::Discord(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl DiscordClient for HttpDiscordClient {
+    async fn fetch_messages(&self, channel_id: &str, after: Option<&str>) -> Result<Vec<DiscordMessage>, Error> {
+        let path = match after {
+            Some(after) => format!("/channels/{}/messages?after={}&limit=100", channel_id, after),
+            None => format!("/channels/{}/messages?limit=1", channel_id),
+        };
+        // The API lists newest first.
+        let mut messages: Vec<DiscordMessage> = self.get(&path).await?;
+        messages.reverse();
+        Ok(messages)
+    }
+
+    async fn channel_guild(&self, channel_id: &str) -> Result<Option<String>, Error> {
+        let channel: DiscordChannel = self.get(&format!("/channels/{}", channel_id)).await?;
+        Ok(channel.guild_id)
+    }
+
+    async fn send_message(&self, channel_id: &str, reply_to: Option<&str>, text: &str) -> Result<(), Error> {
+        // Agent output must never ping @everyone or a role.
+        let mut body = json!({ "content": text, "allowed_mentions": { "parse": [] } });
+        if let Some(message_id) = reply_to {
+            body["message_reference"] = json!({ "message_id": message_id, "fail_if_not_exists": false });
+        }
+        let path = format!("/channels/{}/messages", channel_id);
+        let response = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .header("Authorization", format!("Bot {}", self.token))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| Error::Discord(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::Discord(format!("POST {} failed: {}", path, status)));
+        }
+        Ok(())
+    }
+}
+
+/// Run the Discord bot daemon, polling `channels.discord.allowed_channel_ids`.
+pub async fn run_discord_daemon() -> Result<(), Error> {
+    tracing::info!("Starting Discord bot...");
+
+    let settings = load_settings()?;
+    let discord = settings.channels.discord;
+    let token = discord
+        .bot_token
+        .clone()
+        .ok_or_else(|| Error::Discord("No bot token configured".to_string()))?;
+    let client = HttpDiscordClient::new(token);
+
+    let channels = allowed_channels(&client, &discord).await;
+    if channels.is_empty() {
+        return Err(Error::Discord(
+            "No usable channels in channels.discord.allowed_channel_ids".to_string(),
+        ));
+    }
+
+    // Start after the latest message, so history is not replayed on restart.
+    let mut cursors: HashMap<String, Option<String>> = HashMap::new();
+    for channel_id in channels {
+        let latest = match client.fetch_messages(&channel_id, None).await {
+            Ok(messages) => messages.last().map(|m| m.id.clone()),
+            Err(e) => {
+                tracing::warn!("Failed to read Discord channel {}: {}", channel_id, e);
+                None
+            }
+        };
+        cursors.insert(channel_id, latest);
+    }
+    tracing::info!("Discord bot polling {} channel(s)", cursors.len());
+
+    loop {
+        for (channel_id, cursor) in cursors.iter_mut() {
+            if let Err(e) = poll_channel(&client, channel_id, cursor).await {
+                tracing::warn!("Failed to poll Discord channel {}: {}", channel_id, e);
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Configured channels whose guild is allowed. An empty
+/// `allowed_guild_ids` allows every guild.
+async fn allowed_channels(client: &dyn DiscordClient, discord: &DiscordConfig) -> Vec<String> {
+    let mut channels = Vec::new();
+    for channel_id in &discord.allowed_channel_ids {
+        if !discord.allowed_guild_ids.is_empty() {
+            match client.channel_guild(channel_id).await {
+                Ok(Some(guild)) if discord.allowed_guild_ids.contains(&guild) => {}
+                Ok(guild) => {
+                    tracing::warn!(
+                        "Skipping Discord channel {}: guild {} is not allowed",
+                        channel_id,
+                        guild.as_deref().unwrap_or("(none)")
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Skipping Discord channel {}: {}", channel_id, e);
+                    continue;
+                }
+            }
+        }
+        channels.push(channel_id.clone());
+    }
+    channels
+}
+
+/// Handle every message in `channel_id` after `cursor`, advancing it.
+async fn poll_channel(client: &dyn DiscordClient, channel_id: &str, cursor: &mut Option<String>) -> Result<(), Error> {
+    let messages = client.fetch_messages(channel_id, cursor.as_deref()).await?;
+    for message in messages {
+        *cursor = Some(message.id.clone());
+        if let Err(e) = handle_message(client, &message).await {
+            tracing::error!("Failed to handle Discord message {}: {}", message.id, e);
+        }
+    }
+    Ok(())
+}
+
+/// Apply the pairing gate to a channel message and enqueue it.
+async fn handle_message(client: &dyn DiscordClient, msg: &DiscordMessage) -> Result<(), Error> {
+    let text = msg.content.trim();
+    if msg.author.bot || text.is_empty() {
+        return Ok(());
+    }
+    let sender = msg.author.display_name();
+    let sender_id = msg.author.id.as_str();
+    let reply = |text: String| async move { client.send_message(&msg.channel_id, Some(&msg.id), &text).await };
+
+    // Check pairing approval; allowlisted senders are approved on the spot.
+    if !PairingManager::is_approved(sender_id) {
+        if PairingManager::is_pending(sender_id) {
+            return reply("Your request is pending approval.".to_string()).await;
+        }
+        match PairingManager::add_pending(sender_id, sender) {
+            Ok(PairingOutcome::Approved(_)) => {}
+            Ok(PairingOutcome::Pending(code)) => {
+                return reply(format!(
+                    "Welcome! Your pairing code is: {}\n\nApprove with:\ntinyvegeta pairing approve {}",
+                    code, code
+                ))
+                .await;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to add pending sender: {}", e);
+                return Ok(());
+            }
+        }
+    }
+
+    let settings = load_settings().unwrap_or_default();
+    let (target_agent, message) = match crate::core::routing::parse_agent_routing(text) {
+        Some((agent, message)) => (Some(agent), message),
+        None => (None, text.to_string()),
+    };
+
+    let mut message_data = crate::core::MessageData::new("discord", sender, sender_id, &message);
+    message_data.message_id = msg.id.parse().ok();
+    message_data.agent = target_agent.clone();
+    message_data.response_channel = Some("discord".to_string());
+    message_data.response_chat_id = msg.channel_id.parse().ok();
+    message_data.response_message_id = msg.id.parse().ok();
+
+    match crate::core::Queue::enqueue_validated(message_data, &settings) {
+        Ok((id, warning)) => {
+            tracing::info!("Enqueued Discord message {} from {} to agent {:?}", id, sender, target_agent);
+            if let Some(warning) = warning {
+                reply(format!("⚠️ {}.", warning)).await?;
+            }
+            let short_id = id.chars().take(8).collect::<String>();
+            let route = target_agent.unwrap_or_else(|| "default".to_string());
+            reply(format!("📥 Task {} queued for @{}. I’ll reply when it completes.", short_id, route)).await
+        }
+        Err(Error::NotFound(reason)) | Err(Error::Queue(reason)) => reply(format!("❌ {}.", reason)).await,
+        Err(e) => {
+            tracing::error!("Failed to enqueue Discord message: {}", e);
+            reply("Failed to process message.".to_string()).await
+        }
+    }
+}
+
+/// Send `text` to a Discord channel, split into numbered parts of at most
+/// `max_chars` characters; the first part replies to `reply_to` when known.
+pub async fn send_discord_text(
+    client: &dyn DiscordClient,
+    channel_id: &str,
+    reply_to: Option<&str>,
+    text: &str,
+    max_chars: usize,
+) -> Result<(), Error> {
+    let parts = split_numbered(text, max_chars.min(DISCORD_MESSAGE_LIMIT));
+    for (i, part) in parts.iter().enumerate() {
+        client.send_message(channel_id, reply_to.filter(|_| i == 0), part).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{poll_channel, send_discord_text, DiscordClient, DiscordMessage, DiscordUser};
+    use crate::config::test_support::TempHome;
+    use crate::config::{save_settings, Settings};
+    use crate::core::Queue;
+    use crate::error::Error;
+    use crate::telegram::pairing::PairingManager;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Serves a fixed batch of messages and records what is sent.
+    #[derive(Default)]
+    struct FakeDiscord {
+        inbox: Vec<DiscordMessage>,
+        sent: Mutex<Vec<(String, Option<String>, String)>>,
+    }
+
+    #[async_trait]
+    impl DiscordClient for FakeDiscord {
+        async fn fetch_messages(&self, _channel_id: &str, after: Option<&str>) -> Result<Vec<DiscordMessage>, Error> {
+            Ok(self
+                .inbox
+                .iter()
+                .filter(|m| after.is_none_or(|after| m.id.parse::<u64>().unwrap() > after.parse().unwrap()))
+                .cloned()
+                .collect())
+        }
+
+        async fn channel_guild(&self, _channel_id: &str) -> Result<Option<String>, Error> {
+            Ok(Some("1".to_string()))
+        }
+
+        async fn send_message(&self, channel_id: &str, reply_to: Option<&str>, text: &str) -> Result<(), Error> {
+            self.sent.lock().unwrap().push((
+                channel_id.to_string(),
+                reply_to.map(str::to_string),
+                text.to_string(),
+            ));
+            Ok(())
+        }
+    }
+
+    fn message(id: &str, author_id: &str, content: &str, bot: bool) -> DiscordMessage {
+        DiscordMessage {
+            id: id.to_string(),
+            channel_id: "900".to_string(),
+            content: content.to_string(),
+            author: DiscordUser {
+                id: author_id.to_string(),
+                username: "ana".to_string(),
+                global_name: Some("Ana".to_string()),
+                bot,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn approved_messages_are_enqueued_for_a_discord_reply() {
+        let _home = TempHome::new();
+        save_settings(&Settings::default()).unwrap();
+        PairingManager::allow("42").unwrap();
+
+        let discord = FakeDiscord {
+            inbox: vec![
+                message("1001", "42", "@coder fix the build", false),
+                message("1002", "7", "hello", true),
+                message("1003", "42", "   ", false),
+            ],
+            ..Default::default()
+        };
+        let mut cursor = Some("1000".to_string());
+        poll_channel(&discord, "900", &mut cursor).await.unwrap();
+        assert_eq!(cursor.as_deref(), Some("1003"));
+
+        let queued = Queue::incoming().unwrap();
+        assert_eq!(queued.len(), 1);
+        let data = &queued[0].data;
+        assert_eq!(data.channel, "discord");
+        assert_eq!((data.sender.as_str(), data.sender_id.as_str()), ("Ana", "42"));
+        assert_eq!(data.agent.as_deref(), Some("coder"));
+        assert_eq!(data.message, "fix the build");
+        assert_eq!(data.response_channel.as_deref(), Some("discord"));
+        assert_eq!(data.response_chat_id, Some(900));
+        assert_eq!(data.response_message_id, Some(1001));
+
+        let sent = discord.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].1.as_deref(), Some("1001"));
+        assert!(sent[0].2.contains("queued for @coder"));
+    }
+
+    #[tokio::test]
+    async fn unpaired_senders_get_a_code_and_nothing_is_queued() {
+        let _home = TempHome::new();
+        save_settings(&Settings::default()).unwrap();
+
+        let discord = FakeDiscord {
+            inbox: vec![message("5", "99", "hi there", false), message("6", "99", "anyone?", false)],
+            ..Default::default()
+        };
+        let mut cursor = None;
+        poll_channel(&discord, "900", &mut cursor).await.unwrap();
+
+        assert!(Queue::incoming().unwrap().is_empty());
+        assert!(PairingManager::is_pending("99"));
+        let sent = discord.sent.lock().unwrap();
+        assert!(sent[0].2.contains("pairing code"));
+        assert_eq!(sent[1].2, "Your request is pending approval.");
+    }
+
+    #[tokio::test]
+    async fn long_replies_are_split_and_only_the_first_part_replies() {
+        let discord = FakeDiscord::default();
+        let text = "paragraph\n\n".repeat(300);
+        send_discord_text(&discord, "900", Some("1001"), &text, 4000).await.unwrap();
+
+        let sent = discord.sent.lock().unwrap();
+        assert!(sent.len() > 1);
+        assert!(sent.iter().all(|(_, _, part)| part.chars().count() <= 2000));
+        assert_eq!(sent[0].1.as_deref(), Some("1001"));
+        assert!(sent[1..].iter().all(|(_, reply_to, _)| reply_to.is_none()));
+    }
+}
@@ -0,0 +1,5 @@
+//! Discord bot integration.
+
+pub mod client;
+
+pub use client::{run_discord_daemon, send_discord_text, HttpDiscordClient};
@@ -28,6 +28,9 @@ pub enum Error {
     #[error("Telegram error: {0}")]
     Telegram(String),
 
+    #[error("Discord error: {0}")]
+    Discord(String),
+
     #[error("Provider error: {0}")]
     Provider(String),
 
@@ -37,6 +40,12 @@ pub enum Error {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("Blocked: {0}")]
+    Blocked(String),
+
     #[error("{0}")]
     Other(String),
 }
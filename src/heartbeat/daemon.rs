@@ -7,11 +7,11 @@ use std::io::Write;
 use tokio::sync::RwLock;
 use tokio::time::sleep;
 
-use crate::config::{get_home_dir, load_settings, Settings};
+use crate::config::{get_home_dir, load_settings, BoardSchedule, Settings};
 use crate::error::Error;
 use crate::memory::{Memory, MemoryScope};
 
-use super::scheduler::{HeartbeatSchedule, ScheduleManager};
+use super::scheduler::{cron_matches_minute, HeartbeatSchedule, ScheduleManager};
 use super::tasks::TaskSpawner;
 
 /// Heartbeat daemon.
@@ -80,8 +80,12 @@ impl HeartbeatDaemon {
             {
                 let schedules = self.schedules.read().await;
                 let due = schedules.due();
-                
-                for schedule in due {
+                let quiet = self.settings.read().await.monitoring.in_quiet_hours(chrono::Utc::now());
+                if quiet && !due.is_empty() {
+                    tracing::info!("Skipping {} due heartbeat schedule(s): quiet hours", due.len());
+                }
+
+                for schedule in due.into_iter().filter(|_| !quiet) {
                     tracing::debug!("Processing schedule: {}", schedule.id);
                     
                     let settings = self.settings.read().await.clone();
@@ -187,24 +191,50 @@ pub async fn run_single_heartbeat(agent_id: &str) -> Result<String, Error> {
     HeartbeatDaemon::run_heartbeat(agent_id).await
 }
 
-fn should_run_schedule(id: &str, hhmm: &str, schedule_type: &str) -> bool {
-    let now = chrono::Local::now().format("%H:%M").to_string();
-    if hhmm != now {
+fn schedule_cron(schedule: &BoardSchedule) -> Option<&str> {
+    schedule
+        .cron
+        .as_deref()
+        .map(str::trim)
+        .filter(|expr| !expr.is_empty())
+}
+
+/// Dedupe stamp for a schedule run: the minute for cron schedules, the day otherwise.
+fn schedule_run_stamp(schedule: &BoardSchedule) -> String {
+    let now = chrono::Local::now();
+    if schedule_cron(schedule).is_some() {
+        now.format("%Y-%m-%d %H:%M").to_string()
+    } else {
+        now.format("%Y-%m-%d").to_string()
+    }
+}
+
+fn should_run_schedule(schedule: &BoardSchedule) -> bool {
+    let now = chrono::Local::now();
+    if let Some(expr) = schedule_cron(schedule) {
+        match cron_matches_minute(expr, now) {
+            Ok(true) => {}
+            Ok(false) => return false,
+            Err(e) => {
+                tracing::warn!("Board schedule {}: {}", schedule.id, e);
+                return false;
+            }
+        }
+    } else if schedule.time != now.format("%H:%M").to_string() {
         return false;
     }
-    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-    let last_key = format!("board.schedule.last_run.{}", id);
+    let stamp = schedule_run_stamp(schedule);
+    let last_key = format!("board.schedule.last_run.{}", schedule.id);
     match Memory::get(&last_key, MemoryScope::Global, None) {
-        Ok(Some(entry)) => !(entry.value == today && schedule_type != "digest"),
+        Ok(Some(entry)) => !(entry.value == stamp && schedule.schedule_type != "digest"),
         _ => true,
     }
 }
 
-fn mark_schedule_run(id: &str) {
-    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+fn mark_schedule_run(schedule: &BoardSchedule) {
     let _ = Memory::set(
-        &format!("board.schedule.last_run.{}", id),
-        &today,
+        &format!("board.schedule.last_run.{}", schedule.id),
+        &schedule_run_stamp(schedule),
         MemoryScope::Global,
         None,
     );
@@ -222,16 +252,24 @@ fn log_schedule_attempt(id: &str, ok: bool, detail: &str) {
     let _ = Memory::set(&key, &rec.to_string(), MemoryScope::Global, None);
 }
 
+/// Task-scope store for board schedule runs held back by quiet hours, kept
+/// out of Global memory so they never reach prompts.
+const SCHEDULE_STORE_ID: &str = "board-schedules";
+
 async fn execute_board_schedules(settings: &Settings) -> Result<(), Error> {
     let Some(schedules) = settings.board.schedules.as_ref() else {
         return Ok(());
     };
+    let quiet = settings.monitoring.in_quiet_hours(chrono::Utc::now());
 
     for s in schedules {
         if !s.enabled {
             continue;
         }
-        let run_now = should_run_schedule(&s.id, &s.time, &s.schedule_type);
+        // A run that comes due during quiet hours is held and made up afterwards.
+        let deferred_key = format!("board.schedule.deferred.{}", s.id);
+        let deferred = matches!(Memory::get(&deferred_key, MemoryScope::Task, Some(SCHEDULE_STORE_ID)), Ok(Some(_)));
+        let run_now = should_run_schedule(s) || (deferred && !quiet);
         let retry_key = format!("board.schedule.retry.{}", s.id);
         let retries = Memory::get(&retry_key, MemoryScope::Global, None)
             .ok()
@@ -243,6 +281,22 @@ async fn execute_board_schedules(settings: &Settings) -> Result<(), Error> {
         if !run_now && !retry_due {
             continue;
         }
+        if quiet {
+            if !deferred {
+                tracing::info!("Board schedule {} deferred: quiet hours", s.id);
+                Memory::set(
+                    &deferred_key,
+                    &chrono::Utc::now().to_rfc3339(),
+                    MemoryScope::Task,
+                    Some(SCHEDULE_STORE_ID),
+                )?;
+                log_schedule_attempt(&s.id, false, "deferred: quiet hours");
+            }
+            continue;
+        }
+        if deferred {
+            let _ = Memory::delete(&deferred_key, MemoryScope::Task, Some(SCHEDULE_STORE_ID));
+        }
 
         let result = match s.schedule_type.as_str() {
             "daily" => {
@@ -252,7 +306,7 @@ async fn execute_board_schedules(settings: &Settings) -> Result<(), Error> {
                     .or(settings.board.team_id.as_deref())
                     .unwrap_or("board");
                 let topic = format!("Daily board update for {}", chrono::Local::now().format("%Y-%m-%d"));
-                crate::board::run_board_discussion(settings, team_id, &topic, Some(120))
+                crate::board::run_board_discussion(settings, team_id, &topic, Some(120), false, false)
                     .await
                     .map(|_| ())
             }
@@ -263,12 +317,20 @@ async fn execute_board_schedules(settings: &Settings) -> Result<(), Error> {
                     Err(Error::Other("Digest schedule missing agent_id".to_string()))
                 }
             }
+            "rotate_leader" => {
+                let team_id = s
+                    .team_id
+                    .as_deref()
+                    .or(settings.board.team_id.as_deref())
+                    .unwrap_or("board");
+                crate::board::rotate_team_leader(team_id).map(|_| ())
+            }
             _ => Err(Error::Other(format!("Unknown board schedule type: {}", s.schedule_type))),
         };
 
         match result {
             Ok(_) => {
-                mark_schedule_run(&s.id);
+                mark_schedule_run(s);
                 let _ = Memory::set(
                     &format!("board.schedule.retry.{}", s.id),
                     "0",
@@ -426,20 +488,28 @@ async fn run_system_maintenance(settings: &Settings) -> Result<(), Error> {
     let mut warnings: Vec<String> = Vec::new();
     let mut score: i32 = 100;
 
-    run_doctor_fix_if_due(&mut actions, &mut warnings, &mut score)?;
-    check_queue_pressure(&mut actions, &mut warnings, &mut score)?;
-    check_tmux_state(&mut actions, &mut warnings, &mut score)?;
+    run_doctor_fix_if_due(settings, &mut actions, &mut warnings, &mut score)?;
+    check_queue_pressure(settings, &mut actions, &mut warnings, &mut score)?;
+    check_tmux_state(settings, &mut actions, &mut warnings, &mut score)?;
     check_agent_freshness_and_failures(settings, &mut actions, &mut warnings, &mut score)?;
     check_provider_health(settings, &mut actions, &mut warnings, &mut score).await?;
     check_disk_space(&mut actions, &mut warnings, &mut score)?;
-    check_sqlite_health(&mut actions, &mut warnings, &mut score)?;
+    check_sqlite_health(&settings.monitoring, &mut actions, &mut warnings, &mut score)?;
     check_sovereign_runtime(settings, &mut actions, &mut warnings, &mut score)?;
     cleanup_stale_pairing_requests(&mut actions, &mut warnings)?;
     suggest_memory_compaction(&mut actions, &mut warnings)?;
+    run_scheduled_snapshots(settings, &mut actions, &mut warnings)?;
+    check_agent_soul_files(settings, &mut actions, &mut warnings)?;
 
     if score < 0 {
         score = 0;
     }
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    match alert_on_degraded_health(settings, score, &warnings, now_ms) {
+        Ok(0) => {}
+        Ok(sent) => actions.push(format!("health alert queued for {} recipient(s)", sent)),
+        Err(e) => warnings.push(format!("health alert failed: {}", e)),
+    }
     let ts = chrono::Utc::now().to_rfc3339();
     let action_line = if actions.is_empty() {
         "none".to_string()
@@ -467,7 +537,12 @@ async fn run_system_maintenance(settings: &Settings) -> Result<(), Error> {
     Ok(())
 }
 
-fn run_doctor_fix_if_due(actions: &mut Vec<String>, warnings: &mut Vec<String>, score: &mut i32) -> Result<(), Error> {
+fn run_doctor_fix_if_due(
+    settings: &Settings,
+    actions: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+    score: &mut i32,
+) -> Result<(), Error> {
     let now = chrono::Utc::now().timestamp_millis();
     let key = "heartbeat.doctor.last_run_ms";
     let last = Memory::get(key, MemoryScope::Global, None)
@@ -478,8 +553,14 @@ fn run_doctor_fix_if_due(actions: &mut Vec<String>, warnings: &mut Vec<String>,
     if now - last < 3_600_000 {
         return Ok(());
     }
+    // Not recording the run keeps it due, so it runs once quiet hours end.
+    if settings.monitoring.in_quiet_hours(chrono::Utc::now()) {
+        tracing::info!("doctor --fix suppressed: quiet hours");
+        actions.push("doctor --fix deferred (quiet hours)".to_string());
+        return Ok(());
+    }
 
-    let exe = std::env::current_exe().map_err(|e| Error::Other(format!("current_exe: {}", e)))?;
+    let exe = crate::core::exe::self_exe()?;
     let output = std::process::Command::new(exe)
         .arg("doctor")
         .arg("--fix")
@@ -495,24 +576,43 @@ fn run_doctor_fix_if_due(actions: &mut Vec<String>, warnings: &mut Vec<String>,
     Ok(())
 }
 
-fn check_queue_pressure(actions: &mut Vec<String>, warnings: &mut Vec<String>, score: &mut i32) -> Result<(), Error> {
-    let stats = crate::core::Queue::stats()?;
+fn check_queue_pressure(
+    settings: &Settings,
+    actions: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+    score: &mut i32,
+) -> Result<(), Error> {
+    let stats = crate::core::Queue::stats(settings.queue.orphan_timeout())?;
     Memory::set("heartbeat.queue.depth", &stats.total.to_string(), MemoryScope::Global, None)?;
-    if stats.total > 50 {
+    if stats.total > settings.queue.pressure_warn_threshold {
         warnings.push(format!("queue pressure high ({})", stats.total));
         *score -= 12;
     } else {
         actions.push(format!("queue ok ({})", stats.total));
     }
+    if stats.stale_processing > 0 {
+        warnings.push(format!(
+            "{} message(s) in processing past queue.orphan_timeout_secs",
+            stats.stale_processing
+        ));
+        *score -= 5;
+    }
     Ok(())
 }
 
-fn check_tmux_state(actions: &mut Vec<String>, warnings: &mut Vec<String>, score: &mut i32) -> Result<(), Error> {
+fn check_tmux_state(
+    settings: &Settings,
+    actions: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+    score: &mut i32,
+) -> Result<(), Error> {
+    // The process backend has no session to recover; this heartbeat runs inside it.
+    if settings.daemon.backend == crate::config::DaemonBackend::Process {
+        return Ok(());
+    }
     if !crate::tmux::session_exists()? {
-        let exe = std::env::current_exe()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| "tinyvegeta".to_string());
-        if crate::tmux::start_daemon(&exe).is_ok() {
+        let started = crate::core::exe::self_exe_str().and_then(crate::tmux::start_daemon);
+        if started.is_ok() {
             actions.push("tmux recovered via restart".to_string());
         } else {
             warnings.push("tmux session missing and restart failed".to_string());
@@ -574,6 +674,9 @@ async fn check_provider_health(
         let ok = provider.is_available().await;
         if ok {
             actions.push(format!("provider {} ok", provider_name));
+            if crate::providers::circuit::close(&provider_name)? {
+                actions.push(format!("provider {} circuit closed", provider_name));
+            }
         } else {
             warnings.push(format!("provider {} unavailable", provider_name));
             *score -= 8;
@@ -609,7 +712,12 @@ fn check_disk_space(actions: &mut Vec<String>, warnings: &mut Vec<String>, score
     Ok(())
 }
 
-fn check_sqlite_health(actions: &mut Vec<String>, warnings: &mut Vec<String>, score: &mut i32) -> Result<(), Error> {
+fn check_sqlite_health(
+    monitoring: &crate::config::Monitoring,
+    actions: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+    score: &mut i32,
+) -> Result<(), Error> {
     let path = crate::memory::sqlite::sqlite_db_path()?;
     if !path.exists() {
         actions.push("sqlite db not created yet".to_string());
@@ -618,7 +726,7 @@ fn check_sqlite_health(actions: &mut Vec<String>, warnings: &mut Vec<String>, sc
     let meta = std::fs::metadata(&path)?;
     let size_mb = meta.len() / (1024 * 1024);
     Memory::set("heartbeat.sqlite.size_mb", &size_mb.to_string(), MemoryScope::Global, None)?;
-    if size_mb > 100 {
+    if monitoring.sqlite_vacuum_mb > 0 && size_mb > monitoring.sqlite_vacuum_mb {
         match crate::memory::sqlite::vacuum() {
             Ok(_) => actions.push(format!("sqlite vacuum ran ({}MB)", size_mb)),
             Err(e) => {
@@ -629,6 +737,67 @@ fn check_sqlite_health(actions: &mut Vec<String>, warnings: &mut Vec<String>, sc
     } else {
         actions.push(format!("sqlite size {}MB", size_mb));
     }
+    run_sqlite_maintenance_if_due(monitoring.sqlite_maintenance_hours, actions, warnings, score)?;
+    match crate::memory::sqlite::integrity_check() {
+        Ok(problems) if problems.is_empty() => {
+            Memory::set("heartbeat.sqlite.integrity", "ok", MemoryScope::Global, None)?;
+        }
+        Ok(problems) => {
+            Memory::set("heartbeat.sqlite.integrity", "corrupt", MemoryScope::Global, None)?;
+            warnings.push(format!(
+                "sqlite integrity check failed ({}); run `tinyvegeta doctor --fix`",
+                problems.first().cloned().unwrap_or_default()
+            ));
+            *score -= 15;
+        }
+        Err(e) => {
+            warnings.push(format!("sqlite integrity check could not run: {}", e));
+            *score -= 6;
+        }
+    }
+    Ok(())
+}
+
+/// Run `incremental_vacuum` + `ANALYZE` every `interval_hours`, recording the
+/// outcome under `heartbeat.sqlite.maintenance.*`.
+fn run_sqlite_maintenance_if_due(
+    interval_hours: u64,
+    actions: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+    score: &mut i32,
+) -> Result<(), Error> {
+    if interval_hours == 0 {
+        return Ok(());
+    }
+    let now = chrono::Utc::now().timestamp_millis();
+    let key = "heartbeat.sqlite.maintenance.last_run_ms";
+    let last = Memory::get(key, MemoryScope::Global, None)
+        .ok()
+        .flatten()
+        .and_then(|v| v.value.parse::<i64>().ok())
+        .unwrap_or(0);
+    if now - last < interval_hours as i64 * 3_600_000 {
+        return Ok(());
+    }
+
+    let result = match crate::memory::sqlite::maintain() {
+        Ok(report) => {
+            let line = format!(
+                "sqlite maintenance ran (freed {} pages, {}ms)",
+                report.freed_pages, report.duration_ms
+            );
+            actions.push(line.clone());
+            line
+        }
+        Err(e) => {
+            let line = format!("sqlite maintenance failed: {}", e);
+            warnings.push(line.clone());
+            *score -= 4;
+            line
+        }
+    };
+    Memory::set(key, &now.to_string(), MemoryScope::Global, None)?;
+    Memory::set("heartbeat.sqlite.maintenance.last_result", &result, MemoryScope::Global, None)?;
     Ok(())
 }
 
@@ -677,8 +846,7 @@ fn cleanup_stale_pairing_requests(actions: &mut Vec<String>, warnings: &mut Vec<
         removed = before.saturating_sub(pending.len());
     }
     if removed > 0 {
-        let path = crate::config::get_settings_path()?;
-        std::fs::write(path, serde_json::to_string_pretty(&settings)?)?;
+        crate::config::save_settings(&settings)?;
         actions.push(format!("auto-rejected {} stale pairing requests", removed));
     } else {
         warnings.push("no stale pairing requests".to_string());
@@ -708,9 +876,176 @@ fn suggest_memory_compaction(actions: &mut Vec<String>, warnings: &mut Vec<Strin
         }
         Err(e) => warnings.push(format!("memory compact failed: {}", e)),
     }
+    match Memory::gc_all(None) {
+        Ok(reports) => {
+            let removed: usize = reports.iter().map(|r| r.removed).sum();
+            actions.push(format!("memory gc removed={} files={}", removed, reports.len()));
+        }
+        Err(e) => warnings.push(format!("memory gc failed: {}", e)),
+    }
     Ok(())
 }
 
+/// Take the day's `auto-YYYY-MM-DD` memory snapshot and prune old ones.
+fn run_scheduled_snapshots(
+    settings: &Settings,
+    actions: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) -> Result<(), Error> {
+    let key = "heartbeat.snapshot.last_day";
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let last = Memory::get(key, MemoryScope::Global, None)
+        .ok()
+        .flatten()
+        .map(|v| v.value)
+        .unwrap_or_default();
+    if last == today {
+        return Ok(());
+    }
+    match Memory::snapshot_create(&format!("auto-{}", today)) {
+        Ok(info) => {
+            actions.push(format!("memory snapshot {} files={}", info.name, info.files));
+            Memory::set(key, &today, MemoryScope::Global, None)?;
+        }
+        // Today's snapshot was already taken, e.g. before a restart.
+        Err(Error::AlreadyExists(_)) => Memory::set(key, &today, MemoryScope::Global, None)?,
+        Err(e) => warnings.push(format!("memory snapshot failed: {}", e)),
+    }
+    match Memory::snapshot_prune("auto-", settings.memory.snapshot_retention_days) {
+        Ok(removed) if !removed.is_empty() => {
+            actions.push(format!("memory snapshots pruned={}", removed.len()));
+        }
+        Ok(_) => {}
+        Err(e) => warnings.push(format!("memory snapshot prune failed: {}", e)),
+    }
+    Ok(())
+}
+
+/// Notice out-of-band edits to agents' SOUL.md files.
+///
+/// Modification times are kept in `context.soul.<agent>.mtime`. A changed
+/// file is validated and recorded in `context.soul.<agent>.reloaded`; a
+/// deleted one is re-created from the defaults and recorded in
+/// `context.soul.<agent>.repaired`.
+fn check_agent_soul_files(
+    settings: &Settings,
+    actions: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) -> Result<(), Error> {
+    for (agent_id, agent) in &settings.agents {
+        let Some(dir) = agent.working_directory.as_ref() else {
+            continue;
+        };
+        let path = dir.join("SOUL.md");
+        let mtime_key = format!("context.soul.{}.mtime", agent_id);
+        let now = chrono::Utc::now().timestamp_millis().to_string();
+        let stored = Memory::get(&mtime_key, MemoryScope::Global, None)?.map(|e| e.value);
+
+        if !path.exists() {
+            // Only restore a SOUL.md that was seen before; a working directory
+            // that never had one (say, a user's project) is left alone.
+            if stored.is_none() {
+                continue;
+            }
+            if let Err(e) = crate::context::init_agent_context(agent_id, dir) {
+                warnings.push(format!("@{} SOUL.md missing and re-init failed: {}", agent_id, e));
+                continue;
+            }
+            tracing::warn!("SOUL.md of @{} was deleted; restored the default at {}", agent_id, path.display());
+            warnings.push(format!("@{} SOUL.md was deleted, restored default", agent_id));
+            Memory::set(&format!("context.soul.{}.repaired", agent_id), &now, MemoryScope::Global, None)?;
+        }
+
+        let mtime = match std::fs::metadata(&path).and_then(|m| m.modified()) {
+            Ok(modified) => modified
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis().to_string())
+                .unwrap_or_default(),
+            Err(e) => {
+                warnings.push(format!("@{} SOUL.md is unreadable: {}", agent_id, e));
+                continue;
+            }
+        };
+        if stored.as_deref() == Some(mtime.as_str()) {
+            continue;
+        }
+        Memory::set(&mtime_key, &mtime, MemoryScope::Global, None)?;
+        // The first sighting only records a baseline.
+        if stored.is_none() {
+            continue;
+        }
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) if !content.trim().is_empty() => {
+                tracing::info!("SOUL.md of @{} changed; it is picked up on the next message", agent_id);
+                actions.push(format!("@{} SOUL.md reloaded", agent_id));
+                Memory::set(&format!("context.soul.{}.reloaded", agent_id), &now, MemoryScope::Global, None)?;
+            }
+            Ok(_) => warnings.push(format!("@{} SOUL.md is empty", agent_id)),
+            Err(e) => warnings.push(format!("@{} SOUL.md is unreadable: {}", agent_id, e)),
+        }
+    }
+    Ok(())
+}
+
+/// Queue a Telegram alert to the SOUL owner, or every approved sender when
+/// there is none, if `score` is below `monitoring.health_alert_threshold`.
+///
+/// At most one alert goes out per `monitoring.alert_cooldown_minutes`; the
+/// last one is kept in `heartbeat.alert.last_sent`. Returns how many alerts
+/// were queued; none without a Telegram bot token.
+fn alert_on_degraded_health(
+    settings: &Settings,
+    score: i32,
+    warnings: &[String],
+    now_ms: i64,
+) -> Result<usize, Error> {
+    let threshold = settings.monitoring.health_alert_threshold;
+    if threshold <= 0 || score >= threshold {
+        return Ok(0);
+    }
+    let key = "heartbeat.alert.last_sent";
+    let last_sent = Memory::get(key, MemoryScope::Global, None)?
+        .and_then(|e| e.value.parse::<i64>().ok());
+    let cooldown_ms = settings.monitoring.alert_cooldown_minutes as i64 * 60 * 1000;
+    if last_sent.is_some_and(|last| now_ms - last < cooldown_ms) {
+        return Ok(0);
+    }
+    if settings.channels.telegram.bot_token.is_none() {
+        tracing::debug!("Health score {} is below {}, but Telegram is not configured", score, threshold);
+        return Ok(0);
+    }
+
+    // Private Telegram chats share the user's id.
+    let pairing = &settings.pairing;
+    let recipients: Vec<i64> = match pairing.soul_owner_sender_id.as_deref() {
+        Some(owner) => vec![owner],
+        None => pairing
+            .approved_senders
+            .iter()
+            .flatten()
+            .map(|s| s.sender_id.as_str())
+            .collect(),
+    }
+    .into_iter()
+    .filter_map(|id| id.parse().ok())
+    .collect();
+
+    let warning_line = if warnings.is_empty() { "none".to_string() } else { warnings.join("; ") };
+    let text = format!("⚠️ Health degraded: score={}, warnings={}", score, warning_line);
+    for chat_id in &recipients {
+        let mut alert = crate::core::MessageData::new("heartbeat", "heartbeat", "heartbeat", &text);
+        alert.response_channel = Some("telegram".to_string());
+        alert.response_chat_id = Some(*chat_id);
+        crate::core::Queue::enqueue_outgoing(alert)?;
+    }
+    if !recipients.is_empty() {
+        tracing::warn!("Health score {} below {}; alerted {} recipient(s)", score, threshold, recipients.len());
+        Memory::set(key, &now_ms.to_string(), MemoryScope::Global, None)?;
+    }
+    Ok(recipients.len())
+}
+
 fn append_heartbeat_audit(
     ts: &str,
     health_score: i32,
@@ -734,3 +1069,123 @@ fn append_heartbeat_audit(
     writeln!(f, "{}", rec)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{alert_on_degraded_health, check_agent_soul_files, run_scheduled_snapshots};
+    use crate::config::{save_settings, test_support::TempHome, AgentConfig, Settings};
+    use crate::memory::{Memory, MemoryScope};
+
+    #[test]
+    fn scheduled_snapshots_run_once_per_day() {
+        let _home = TempHome::new();
+        let settings = Settings::default();
+        save_settings(&settings).unwrap();
+
+        for _ in 0..2 {
+            let (mut actions, mut warnings) = (Vec::new(), Vec::new());
+            run_scheduled_snapshots(&settings, &mut actions, &mut warnings).unwrap();
+            assert!(warnings.is_empty(), "{:?}", warnings);
+        }
+
+        // Losing the last-run marker does not turn today's snapshot into a warning.
+        Memory::delete("heartbeat.snapshot.last_day", MemoryScope::Global, None).unwrap();
+        let (mut actions, mut warnings) = (Vec::new(), Vec::new());
+        run_scheduled_snapshots(&settings, &mut actions, &mut warnings).unwrap();
+        assert!(warnings.is_empty() && actions.is_empty(), "{:?} {:?}", actions, warnings);
+        assert!(Memory::get("heartbeat.snapshot.last_day", MemoryScope::Global, None).unwrap().is_some());
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let names: Vec<String> = Memory::snapshot_list().unwrap().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec![format!("auto-{}", today)]);
+    }
+
+    #[test]
+    fn edited_and_deleted_soul_files_are_recorded() {
+        let home = TempHome::new();
+        let dir = home.tinyvegeta_dir().join("workspace").join("coder");
+        crate::context::init_agent_context("coder", &dir).unwrap();
+        let soul = dir.join("SOUL.md");
+        let mut settings = Settings::default();
+        settings.agents.insert(
+            "coder".to_string(),
+            AgentConfig { working_directory: Some(dir.clone()), ..Default::default() },
+        );
+        let recorded = |event: &str| {
+            Memory::get(&format!("context.soul.coder.{}", event), MemoryScope::Global, None).unwrap()
+        };
+        let check = || {
+            let (mut actions, mut warnings) = (Vec::new(), Vec::new());
+            check_agent_soul_files(&settings, &mut actions, &mut warnings).unwrap();
+            (actions, warnings)
+        };
+
+        // Baseline, then nothing changed.
+        check();
+        assert_eq!(check(), (Vec::new(), Vec::new()));
+        assert!(recorded("reloaded").is_none());
+
+        std::fs::write(&soul, "# SOUL\nBe terse.").unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&soul).unwrap().set_modified(later).unwrap();
+        let (actions, _) = check();
+        assert_eq!(actions, vec!["@coder SOUL.md reloaded".to_string()]);
+        assert!(recorded("reloaded").is_some());
+
+        std::fs::remove_file(&soul).unwrap();
+        let (_, warnings) = check();
+        assert_eq!(warnings, vec!["@coder SOUL.md was deleted, restored default".to_string()]);
+        assert!(recorded("repaired").is_some());
+        assert!(!std::fs::read_to_string(&soul).unwrap().trim().is_empty());
+    }
+
+    #[test]
+    fn working_directories_without_a_soul_file_are_left_alone() {
+        let home = TempHome::new();
+        let project = home.tinyvegeta_dir().join("projects").join("app");
+        std::fs::create_dir_all(&project).unwrap();
+        let mut settings = Settings::default();
+        settings.agents.insert(
+            "dev".to_string(),
+            AgentConfig { working_directory: Some(project.clone()), ..Default::default() },
+        );
+
+        let (mut actions, mut warnings) = (Vec::new(), Vec::new());
+        check_agent_soul_files(&settings, &mut actions, &mut warnings).unwrap();
+        assert_eq!((actions, warnings), (Vec::new(), Vec::new()));
+        assert_eq!(std::fs::read_dir(&project).unwrap().count(), 0, "nothing is scaffolded");
+        assert_eq!(std::fs::read_dir(project.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn health_alerts_fire_once_per_cooldown() {
+        let _home = TempHome::new();
+        let mut settings = Settings::default();
+        settings.channels.telegram.bot_token = Some("123:test".to_string());
+        settings.pairing.soul_owner_sender_id = Some("42".to_string());
+        settings.monitoring.alert_cooldown_minutes = 30;
+        let minute = 60 * 1000;
+        let warnings = vec!["@coder stale (>30m without success)".to_string()];
+        let alert = |score, at| alert_on_degraded_health(&settings, score, &warnings, at).unwrap();
+
+        assert_eq!(alert(80, 0), 0);
+        // Crossing below 60 alerts once; staying below is quiet.
+        assert_eq!(alert(50, minute), 1);
+        assert_eq!(alert(40, 2 * minute), 0);
+        // Recovering and dropping again within the cooldown stays quiet too.
+        assert_eq!(alert(90, 3 * minute), 0);
+        assert_eq!(alert(55, 4 * minute), 0);
+        assert_eq!(alert(55, 31 * minute), 1);
+
+        let queued = crate::core::Queue::outgoing().unwrap();
+        assert_eq!(queued.len(), 2);
+        let data = &queued[0].data;
+        assert_eq!((data.response_channel.as_deref(), data.response_chat_id), (Some("telegram"), Some(42)));
+        assert!(data.message.starts_with("⚠️ Health degraded: score="));
+        assert!(data.message.contains("warnings=@coder stale"));
+
+        // Without Telegram there is nobody to alert, and nothing to warn about.
+        settings.channels.telegram.bot_token = None;
+        assert_eq!(alert_on_degraded_health(&settings, 10, &warnings, 90 * minute).unwrap(), 0);
+    }
+}
@@ -4,7 +4,7 @@
 use cron::Schedule;
 use std::str::FromStr;
 use tokio::time::sleep;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Local, Timelike, Utc};
 
 /// Heartbeat schedule.
 #[derive(Debug, Clone)]
@@ -228,3 +228,103 @@ pub fn default_heartbeat_schedule() -> HeartbeatSchedule {
 pub fn default_daily_schedule(time: &str) -> Result<HeartbeatSchedule, String> {
     HeartbeatSchedule::daily(time)
 }
+
+/// Parse a cron expression.
+///
+/// Accepts standard 5-field crontab syntax (`min hour dom month dow`, with
+/// Sunday as 0 or 7) as well as the 6/7-field seconds-first syntax understood
+/// by the `cron` crate.
+pub fn parse_cron_expr(expr: &str) -> Result<Schedule, String> {
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    let normalized = if fields.len() == 5 {
+        format!(
+            "0 {} {} {} {} {}",
+            fields[0],
+            fields[1],
+            fields[2],
+            fields[3],
+            crontab_dow_to_names(fields[4])
+        )
+    } else {
+        fields.join(" ")
+    };
+    Schedule::from_str(&normalized).map_err(|e| format!("Invalid cron expression '{}': {}", expr, e))
+}
+
+/// Map crontab day-of-week numbers (0/7 = Sunday) to names, since the `cron`
+/// crate numbers days 1-7 starting at Sunday. Step values (`*/2`) are kept.
+fn crontab_dow_to_names(field: &str) -> String {
+    const NAMES: [&str; 8] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT", "SUN"];
+    let mut out = String::new();
+    let mut after_slash = false;
+    let mut digits = String::new();
+    let flush = |digits: &mut String, out: &mut String, after_slash: bool| {
+        if digits.is_empty() {
+            return;
+        }
+        match digits.parse::<usize>() {
+            Ok(n) if !after_slash && n < NAMES.len() => out.push_str(NAMES[n]),
+            _ => out.push_str(digits),
+        }
+        digits.clear();
+    };
+    for ch in field.chars() {
+        if ch.is_ascii_digit() {
+            digits.push(ch);
+            continue;
+        }
+        flush(&mut digits, &mut out, after_slash);
+        after_slash = ch == '/';
+        out.push(ch);
+    }
+    flush(&mut digits, &mut out, after_slash);
+    out
+}
+
+/// Check whether a cron expression fires during the minute containing `now`.
+pub fn cron_matches_minute(expr: &str, now: DateTime<Local>) -> Result<bool, String> {
+    let schedule = parse_cron_expr(expr)?;
+    let minute_start = now
+        .with_second(0)
+        .and_then(|t| t.with_nanosecond(0))
+        .ok_or_else(|| "Invalid timestamp".to_string())?;
+    let probe = minute_start - Duration::seconds(1);
+    Ok(schedule
+        .after(&probe)
+        .next()
+        .map(|next| next < minute_start + Duration::minutes(1))
+        .unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn weekly_cron_matches_only_monday_nine() {
+        // 2024-01-01 was a Monday.
+        let monday = Local.with_ymd_and_hms(2024, 1, 1, 9, 0, 30).unwrap();
+        let tuesday = Local.with_ymd_and_hms(2024, 1, 2, 9, 0, 30).unwrap();
+        let monday_late = Local.with_ymd_and_hms(2024, 1, 1, 9, 1, 0).unwrap();
+        assert!(cron_matches_minute("0 9 * * 1", monday).unwrap());
+        assert!(!cron_matches_minute("0 9 * * 1", tuesday).unwrap());
+        assert!(!cron_matches_minute("0 9 * * 1", monday_late).unwrap());
+        assert!(cron_matches_minute("0 9 * * 1-5", tuesday).unwrap());
+        assert!(!cron_matches_minute("0 9 * * 0", monday).unwrap());
+    }
+
+    #[test]
+    fn interval_cron_matches_every_six_hours() {
+        let at = |h| Local.with_ymd_and_hms(2024, 1, 1, h, 0, 0).unwrap();
+        assert!(cron_matches_minute("0 */6 * * *", at(0)).unwrap());
+        assert!(cron_matches_minute("0 */6 * * *", at(6)).unwrap());
+        assert!(cron_matches_minute("0 */6 * * *", at(18)).unwrap());
+        assert!(!cron_matches_minute("0 */6 * * *", at(7)).unwrap());
+    }
+
+    #[test]
+    fn invalid_cron_is_rejected() {
+        assert!(parse_cron_expr("not a cron").is_err());
+    }
+}
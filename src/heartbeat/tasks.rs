@@ -231,6 +231,15 @@ impl TaskSpawner {
     pub async fn spawn_task(
         task: &Task,
         settings: &Settings,
+    ) -> Result<String, Error> {
+        Self::spawn_task_cancellable(task, settings, None).await
+    }
+    
+    /// Run a task, stopping the provider when `cancel` fires.
+    pub async fn spawn_task_cancellable(
+        task: &Task,
+        settings: &Settings,
+        cancel: Option<crate::providers::CancelToken>,
     ) -> Result<String, Error> {
         let agent_id = task.agent_id.as_ref()
             .ok_or_else(|| Error::Other("Task has no assigned agent".to_string()))?;
@@ -255,12 +264,13 @@ impl TaskSpawner {
         // Run completion
         let model = agent.model.as_deref();
         let contract = crate::agent::ExecutionContract::for_agent(provider_name);
-        let result = crate::agent::execute_with_contract(
+        let result = crate::agent::execute_with_contract_cancellable(
             provider,
             &prompt,
             model,
             Some(&working_dir),
             &contract,
+            cancel,
         )
         .await
         .map_err(|e| Error::Provider(e.to_string()))?;
@@ -313,6 +323,8 @@ impl TaskSpawner {
             .current_dir(&working_dir)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            // Callers like board discussions drop the call on timeout.
+            .kill_on_drop(true)
             .output()
             .await?;
         
@@ -6,10 +6,13 @@ pub mod agent;
 pub mod board;
 pub mod context;
 pub mod core;
+pub mod daemon;
+pub mod discord;
 pub mod error;
 pub mod heartbeat;
 pub mod logging;
 pub mod memory;
+pub mod protocol;
 pub mod providers;
 pub mod task;
 pub mod sovereign;
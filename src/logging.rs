@@ -1,18 +1,28 @@
 //! Logging setup for TinyVegeta using tracing.
 
 use anyhow::Result;
-use std::path::PathBuf;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+/// Name of the live log file; rotated files get a `.<timestamp>` suffix.
+pub const LOG_FILE_NAME: &str = "tinyvegeta.log";
+
 /// Initialize logging with file appender and console output.
 pub fn init() -> Result<(WorkerGuard, PathBuf)> {
     // Get the log directory
     let log_dir = get_log_dir()?;
     std::fs::create_dir_all(&log_dir)?;
 
-    // Create file appender with rotation
-    let file_appender = tracing_appender::rolling::daily(&log_dir, "tinyvegeta.log");
+    let monitoring = crate::config::load_settings()
+        .map(|s| s.monitoring)
+        .unwrap_or_default();
+    let pruned = prune_rotated_logs(&log_dir, monitoring.log_retention_days);
+
+    // Create file appender with daily and size-based rotation
+    let file_appender = RotatingFileWriter::new(&log_dir, monitoring.log_rotate_bytes)?;
     let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
 
     // Build the subscriber
@@ -40,16 +50,158 @@ pub fn init() -> Result<(WorkerGuard, PathBuf)> {
 
     tracing::info!("TinyVegeta logging initialized");
     tracing::info!("Log directory: {}", log_dir.display());
+    if pruned > 0 {
+        tracing::info!("Removed {} log file(s) older than {} days", pruned, monitoring.log_retention_days);
+    }
 
     Ok((guard, log_dir))
 }
 
-/// Get the log directory path.
-fn get_log_dir() -> Result<PathBuf> {
+/// Get the log directory path; each profile logs to its own `logs/<profile>`.
+pub fn get_log_dir() -> Result<PathBuf> {
     let home = directories::ProjectDirs::from("com", "tinyvegeta", "tinyvegeta")
         .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
 
-    Ok(home.data_dir().join("logs"))
+    let dir = home.data_dir().join("logs");
+    Ok(match crate::config::active_profile()? {
+        Some(profile) => dir.join(profile),
+        None => dir,
+    })
+}
+
+/// Writer for `tinyvegeta.log` that rotates it when the day changes or it
+/// would grow past `max_bytes`.
+pub struct RotatingFileWriter {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    opened_on: chrono::NaiveDate,
+}
+
+impl RotatingFileWriter {
+    pub fn new(dir: &Path, max_bytes: u64) -> std::io::Result<Self> {
+        let path = dir.join(LOG_FILE_NAME);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let meta = file.metadata()?;
+        let opened_on = meta
+            .modified()
+            .map(|t| chrono::DateTime::<chrono::Local>::from(t).date_naive())
+            .unwrap_or_else(|_| chrono::Local::now().date_naive());
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file,
+            size: meta.len(),
+            max_bytes,
+            opened_on,
+        })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S%.3f").to_string();
+        let mut target = self.dir.join(format!("{}.{}", LOG_FILE_NAME, stamp));
+        let mut n = 1;
+        while target.exists() {
+            target = self.dir.join(format!("{}.{}-{}", LOG_FILE_NAME, stamp, n));
+            n += 1;
+        }
+        let current = self.dir.join(LOG_FILE_NAME);
+        std::fs::rename(&current, &target)?;
+        self.file = OpenOptions::new().create(true).append(true).open(&current)?;
+        self.size = 0;
+        self.opened_on = chrono::Local::now().date_naive();
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let over_size = self.max_bytes > 0 && self.size > 0 && self.size + buf.len() as u64 > self.max_bytes;
+        if over_size || chrono::Local::now().date_naive() != self.opened_on {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Log files in `dir`, oldest first: rotated files by modification time,
+/// then the live `tinyvegeta.log`.
+pub fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut rotated: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| is_rotated_log(&e.file_name().to_string_lossy()))
+        .map(|e| {
+            let modified = e.metadata().and_then(|m| m.modified()).unwrap_or(std::time::UNIX_EPOCH);
+            (modified, e.path())
+        })
+        .collect();
+    rotated.sort();
+    let mut files: Vec<PathBuf> = rotated.into_iter().map(|(_, p)| p).collect();
+    let current = dir.join(LOG_FILE_NAME);
+    if current.exists() {
+        files.push(current);
+    }
+    files
+}
+
+/// Read the last lines accepted by `select` across the live and rotated log
+/// files. `select` filters one file's content down to its last `n` lines;
+/// files are read newest first, only until `limit` lines are collected.
+pub fn tail_log_lines(
+    dir: &Path,
+    limit: usize,
+    select: impl Fn(&str, usize) -> Vec<String>,
+) -> std::io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for file in log_files(dir).iter().rev() {
+        let remaining = limit.saturating_sub(lines.len());
+        if remaining == 0 {
+            break;
+        }
+        // Lossy: a write cut off mid-character must not make the whole log unreadable.
+        let content = String::from_utf8_lossy(&std::fs::read(file)?).into_owned();
+        let mut older = select(&content, remaining);
+        older.append(&mut lines);
+        lines = older;
+    }
+    Ok(lines)
+}
+
+/// Delete rotated log files last modified more than `retention_days` ago.
+/// Returns how many were removed; `0` days keeps everything.
+pub fn prune_rotated_logs(dir: &Path, retention_days: u64) -> usize {
+    if retention_days == 0 {
+        return 0;
+    }
+    let max_age = std::time::Duration::from_secs(retention_days * 24 * 60 * 60);
+    let now = std::time::SystemTime::now();
+    let mut removed = 0;
+    for path in log_files(dir) {
+        if !is_rotated_log(&path.file_name().unwrap_or_default().to_string_lossy()) {
+            continue;
+        }
+        let expired = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|t| now.duration_since(t).unwrap_or_default() > max_age);
+        if expired && std::fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+fn is_rotated_log(name: &str) -> bool {
+    name.strip_prefix(LOG_FILE_NAME)
+        .is_some_and(|rest| rest.len() > 1 && rest.starts_with('.'))
 }
 
 /// Initialize logging for tests (console only, no file).
@@ -62,3 +214,40 @@ pub fn init_test() {
         .with(fmt::layer().with_writer(std::io::stderr))
         .init();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{log_files, prune_rotated_logs, tail_log_lines, RotatingFileWriter, LOG_FILE_NAME};
+    use std::io::Write;
+
+    #[test]
+    fn tiny_rotation_size_produces_multiple_files_read_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingFileWriter::new(dir.path(), 64).unwrap();
+        for i in 0..10 {
+            writer.write_all(format!("line {:02} {}\n", i, "x".repeat(20)).as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        let files = log_files(dir.path());
+        assert!(files.len() > 2, "expected rotated files, got {:?}", files);
+        assert_eq!(files.last().unwrap().file_name().unwrap(), LOG_FILE_NAME);
+        for file in &files {
+            assert!(std::fs::metadata(file).unwrap().len() <= 64);
+        }
+
+        let last = |content: &str, n: usize| {
+            let lines: Vec<String> = content.lines().map(String::from).collect();
+            lines[lines.len().saturating_sub(n)..].to_vec()
+        };
+        let all = tail_log_lines(dir.path(), 100, last).unwrap();
+        assert_eq!(all.len(), 10);
+        assert!(all[0].starts_with("line 00") && all[9].starts_with("line 09"));
+        let tail = tail_log_lines(dir.path(), 3, last).unwrap();
+        assert_eq!(tail.iter().map(|l| &l[..7]).collect::<Vec<_>>(), vec!["line 07", "line 08", "line 09"]);
+
+        // Fresh files are within retention; the live file is never pruned.
+        assert_eq!(prune_rotated_logs(dir.path(), 7), 0);
+        assert_eq!(log_files(dir.path()).len(), files.len());
+    }
+}
@@ -11,10 +11,13 @@ mod agent;
 mod board;
 mod context;
 mod core;
+mod daemon;
+mod discord;
 mod error;
 mod heartbeat;
 mod logging;
 mod memory;
+mod protocol;
 mod providers;
 mod task;
 mod sovereign;
@@ -26,14 +29,26 @@ use cli::Commands;
 
 #[tokio::main]
 async fn main() -> ExitCode {
+    // Parse command line arguments
+    let args = Commands::parse();
+
+    // Everything derived from the home dir, including child processes,
+    // follows the profile through the environment.
+    if let Some(profile) = &args.profile {
+        std::env::set_var(config::PROFILE_ENV, profile);
+    }
+
     // Initialize logging
     if let Err(e) = logging::init() {
         eprintln!("Failed to initialize logging: {}", e);
         return ExitCode::FAILURE;
     }
 
-    // Parse command line arguments
-    let args = Commands::parse();
+    // Resolve our own path before anything can replace the binary; commands
+    // that re-invoke tinyvegeta report the error if this failed.
+    if let Err(e) = core::exe::init() {
+        tracing::warn!("{}", e);
+    }
 
     // Run the command
     match args.run().await {
@@ -14,6 +14,42 @@ pub struct SessionSummary {
     pub last_outcome: Option<String>,
 }
 
+/// One event, decision, or outcome recorded for an agent.
+#[derive(Debug, Clone)]
+pub struct AgentRecord {
+    pub ts: i64,
+    pub session_id: String,
+    /// `event`, `decision`, or `outcome`.
+    pub kind: String,
+    /// Event type, decision intent, or outcome status.
+    pub label: String,
+    pub detail: String,
+}
+
+/// One row of the `decisions` log.
+#[derive(Debug, Clone)]
+pub struct DecisionRecord {
+    pub ts: i64,
+    pub session_id: String,
+    pub agent_id: String,
+    pub intent: String,
+    pub owner: String,
+    pub priority: String,
+    pub deadline: Option<String>,
+    pub reason: String,
+}
+
+/// The memory block actually injected into one message's prompt.
+#[derive(Debug, Clone)]
+pub struct MemoryTrace {
+    pub trace_id: String,
+    pub ts: i64,
+    pub session_id: String,
+    pub agent_id: String,
+    pub query: String,
+    pub block: String,
+}
+
 fn db_path() -> Result<std::path::PathBuf, Error> {
     Ok(get_home_dir()?.join("memory").join("events.db"))
 }
@@ -58,9 +94,23 @@ fn connect() -> Result<Connection, Error> {
             error_code TEXT,
             summary TEXT NOT NULL
         );
+        CREATE TABLE IF NOT EXISTS memory_traces (
+            trace_id TEXT PRIMARY KEY,
+            ts INTEGER NOT NULL,
+            session_id TEXT NOT NULL,
+            agent_id TEXT NOT NULL,
+            query TEXT NOT NULL,
+            block TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS seen_messages (
+            key TEXT PRIMARY KEY,
+            ts INTEGER NOT NULL
+        );
         CREATE INDEX IF NOT EXISTS idx_events_session ON events(session_id, ts);
         CREATE INDEX IF NOT EXISTS idx_decisions_session ON decisions(session_id, ts);
         CREATE INDEX IF NOT EXISTS idx_outcomes_session ON outcomes(session_id, ts);
+        CREATE INDEX IF NOT EXISTS idx_memory_traces_agent ON memory_traces(agent_id, ts);
+        CREATE INDEX IF NOT EXISTS idx_seen_messages_ts ON seen_messages(ts);
         "#,
     )
     .map_err(|e| Error::Memory(format!("sqlite init: {}", e)))?;
@@ -117,6 +167,54 @@ pub fn record_decision(
     Ok(())
 }
 
+/// Decisions whose intent, owner, priority, deadline or reason contains
+/// `query` (case-insensitive), newest first.
+pub fn search_decisions(query: &str, limit: usize) -> Result<Vec<DecisionRecord>, Error> {
+    query_decisions(None, Some(query), limit)
+}
+
+/// Decisions of one session or agent (`None` for all), newest first.
+pub fn list_decisions(session_or_agent: Option<&str>, limit: usize) -> Result<Vec<DecisionRecord>, Error> {
+    query_decisions(session_or_agent, None, limit)
+}
+
+fn query_decisions(
+    session_or_agent: Option<&str>,
+    query: Option<&str>,
+    limit: usize,
+) -> Result<Vec<DecisionRecord>, Error> {
+    if !db_path()?.exists() {
+        return Ok(Vec::new());
+    }
+    let conn = connect()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT ts, session_id, agent_id, intent, owner, priority, deadline, reason FROM decisions
+             WHERE (?1 IS NULL OR session_id = ?1 OR agent_id = ?1)
+               AND (?2 IS NULL OR instr(lower(intent || ' ' || owner || ' ' || priority || ' '
+                                             || COALESCE(deadline, '') || ' ' || reason), lower(?2)) > 0)
+             ORDER BY ts DESC, rowid DESC LIMIT ?3",
+        )
+        .map_err(|e| Error::Memory(format!("sqlite prepare decisions: {}", e)))?;
+    let rows = stmt
+        .query_map(params![session_or_agent, query, limit.min(i64::MAX as usize) as i64], |row| {
+            Ok(DecisionRecord {
+                ts: row.get(0)?,
+                session_id: row.get(1)?,
+                agent_id: row.get(2)?,
+                intent: row.get(3)?,
+                owner: row.get(4)?,
+                priority: row.get(5)?,
+                deadline: row.get(6)?,
+                reason: row.get(7)?,
+            })
+        })
+        .map_err(|e| Error::Memory(format!("sqlite query decisions: {}", e)))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| Error::Memory(format!("sqlite read decision: {}", e)))?;
+    Ok(rows)
+}
+
 pub fn record_outcome(
     session_id: &str,
     agent_id: &str,
@@ -141,6 +239,69 @@ pub fn record_outcome(
     Ok(())
 }
 
+/// Persist the memory block built for a message under `trace_id`.
+pub fn record_memory_trace(
+    trace_id: &str,
+    session_id: &str,
+    agent_id: &str,
+    query: &str,
+    block: &str,
+) -> Result<(), Error> {
+    let conn = connect()?;
+    conn.execute(
+        "INSERT INTO memory_traces (trace_id, ts, session_id, agent_id, query, block) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            trace_id,
+            chrono::Utc::now().timestamp_millis(),
+            session_id,
+            agent_id,
+            query,
+            block
+        ],
+    )
+    .map_err(|e| Error::Memory(format!("sqlite insert memory trace: {}", e)))?;
+    Ok(())
+}
+
+/// Load a memory trace by id, or the most recent one (optionally for one
+/// agent) when `trace_id` is `last`.
+pub fn memory_trace(trace_id: &str, agent_id: Option<&str>) -> Result<Option<MemoryTrace>, Error> {
+    let conn = connect()?;
+    let columns = "trace_id, ts, session_id, agent_id, query, block";
+    let mut stmt = if trace_id == "last" {
+        conn.prepare(&format!(
+            "SELECT {} FROM memory_traces WHERE ?1 IS NULL OR agent_id = ?1 ORDER BY ts DESC, trace_id DESC LIMIT 1",
+            columns
+        ))
+    } else {
+        conn.prepare(&format!("SELECT {} FROM memory_traces WHERE trace_id = ?1", columns))
+    }
+    .map_err(|e| Error::Memory(format!("sqlite prepare memory trace: {}", e)))?;
+    let key = if trace_id == "last" { agent_id } else { Some(trace_id) };
+    let mut rows = stmt
+        .query(params![key])
+        .map_err(|e| Error::Memory(format!("sqlite query memory trace: {}", e)))?;
+    let row = match rows
+        .next()
+        .map_err(|e| Error::Memory(format!("sqlite read memory trace: {}", e)))?
+    {
+        Some(row) => row,
+        None => return Ok(None),
+    };
+    let trace = (|| {
+        Ok::<_, rusqlite::Error>(MemoryTrace {
+            trace_id: row.get(0)?,
+            ts: row.get(1)?,
+            session_id: row.get(2)?,
+            agent_id: row.get(3)?,
+            query: row.get(4)?,
+            block: row.get(5)?,
+        })
+    })()
+    .map_err(|e| Error::Memory(format!("sqlite read memory trace: {}", e)))?;
+    Ok(Some(trace))
+}
+
 pub fn summarize_session(session_id: &str) -> Result<SessionSummary, Error> {
     let conn = connect()?;
     let event_count: i64 = conn
@@ -185,6 +346,28 @@ pub fn summarize_session(session_id: &str) -> Result<SessionSummary, Error> {
     })
 }
 
+/// Most keys [`mark_message_seen`] keeps; the oldest go first.
+const SEEN_MESSAGES_CAP: i64 = 10_000;
+
+/// Record channel message `key` (e.g. `telegram:<chat>:<message>`) as seen.
+/// Returns `false` when it was already seen within the last `window_ms`,
+/// i.e. the message is a redelivery.
+pub fn mark_message_seen(key: &str, window_ms: i64) -> Result<bool, Error> {
+    let conn = connect()?;
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute("DELETE FROM seen_messages WHERE ts < ?1", params![now - window_ms])
+        .map_err(|e| Error::Memory(format!("sqlite prune seen messages: {}", e)))?;
+    let inserted = conn
+        .execute("INSERT OR IGNORE INTO seen_messages (key, ts) VALUES (?1, ?2)", params![key, now])
+        .map_err(|e| Error::Memory(format!("sqlite insert seen message: {}", e)))?;
+    conn.execute(
+        "DELETE FROM seen_messages WHERE key NOT IN (SELECT key FROM seen_messages ORDER BY ts DESC LIMIT ?1)",
+        params![SEEN_MESSAGES_CAP],
+    )
+    .map_err(|e| Error::Memory(format!("sqlite trim seen messages: {}", e)))?;
+    Ok(inserted == 1)
+}
+
 pub fn failed_outcomes_last_hour(agent_id: &str) -> Result<u32, Error> {
     let conn = connect()?;
     let since = chrono::Utc::now().timestamp_millis() - 3_600_000;
@@ -198,9 +381,317 @@ pub fn failed_outcomes_last_hour(agent_id: &str) -> Result<u32, Error> {
     Ok(count as u32)
 }
 
+/// The `limit` most recent events, decisions, and outcomes for an agent,
+/// oldest first.
+pub fn recent_for_agent(agent_id: &str, limit: usize) -> Result<Vec<AgentRecord>, Error> {
+    if !db_path()?.exists() {
+        return Ok(Vec::new());
+    }
+    let conn = connect()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT ts, id, session_id, 'event', event_type, detail FROM events WHERE agent_id = ?1
+             UNION ALL
+             SELECT ts, id, session_id, 'decision', intent,
+                    'owner=' || owner || ' priority=' || priority || ' reason=' || reason
+             FROM decisions WHERE agent_id = ?1
+             UNION ALL
+             SELECT ts, id, session_id, 'outcome', status, COALESCE(error_code || ': ', '') || summary
+             FROM outcomes WHERE agent_id = ?1
+             ORDER BY 1 DESC, 2 DESC LIMIT ?2",
+        )
+        .map_err(|e| Error::Memory(format!("sqlite prepare agent records: {}", e)))?;
+    let mut records = stmt
+        .query_map(params![agent_id, limit as i64], |row| {
+            Ok(AgentRecord {
+                ts: row.get(0)?,
+                session_id: row.get(2)?,
+                kind: row.get(3)?,
+                label: row.get(4)?,
+                detail: row.get(5)?,
+            })
+        })
+        .map_err(|e| Error::Memory(format!("sqlite query agent records: {}", e)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::Memory(format!("sqlite read agent records: {}", e)))?;
+    records.reverse();
+    Ok(records)
+}
+
+/// Count events, decisions, outcomes, and memory traces recorded for an agent.
+pub fn count_agent_records(agent_id: &str) -> Result<usize, Error> {
+    if !db_path()?.exists() {
+        return Ok(0);
+    }
+    let conn = connect()?;
+    let mut total = 0usize;
+    for table in ["events", "decisions", "outcomes", "memory_traces"] {
+        let count: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {} WHERE agent_id = ?1", table),
+                params![agent_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| Error::Memory(format!("sqlite count {}: {}", table, e)))?;
+        total += count as usize;
+    }
+    Ok(total)
+}
+
+/// Delete all events, decisions, outcomes, and memory traces recorded for an agent.
+pub fn delete_agent_records(agent_id: &str) -> Result<usize, Error> {
+    let conn = connect()?;
+    let mut total = 0usize;
+    for table in ["events", "decisions", "outcomes", "memory_traces"] {
+        total += conn
+            .execute(&format!("DELETE FROM {} WHERE agent_id = ?1", table), params![agent_id])
+            .map_err(|e| Error::Memory(format!("sqlite delete {}: {}", table, e)))?;
+    }
+    Ok(total)
+}
+
+/// Full `VACUUM`. Also switches the database to incremental auto-vacuum, so
+/// later [`maintain`] runs can reclaim free pages without rewriting the file.
 pub fn vacuum() -> Result<(), Error> {
     let conn = connect()?;
-    conn.execute_batch("VACUUM;")
+    conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")
         .map_err(|e| Error::Memory(format!("sqlite vacuum: {}", e)))?;
     Ok(())
 }
+
+/// Result of [`maintain`].
+#[derive(Debug, Clone)]
+pub struct MaintenanceReport {
+    /// Free pages returned to the filesystem by `incremental_vacuum`.
+    pub freed_pages: i64,
+    pub duration_ms: u128,
+}
+
+/// Light maintenance: `PRAGMA incremental_vacuum` then `ANALYZE`. Unlike
+/// [`vacuum`] this never rewrites the whole file; free pages are only
+/// reclaimed once the database uses incremental auto-vacuum.
+pub fn maintain() -> Result<MaintenanceReport, Error> {
+    let started = std::time::Instant::now();
+    let conn = connect()?;
+    let free_pages = |conn: &Connection| -> Result<i64, Error> {
+        conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))
+            .map_err(|e| Error::Memory(format!("sqlite freelist_count: {}", e)))
+    };
+    let before = free_pages(&conn)?;
+    conn.execute_batch("PRAGMA incremental_vacuum; ANALYZE;")
+        .map_err(|e| Error::Memory(format!("sqlite maintenance: {}", e)))?;
+    let after = free_pages(&conn)?;
+    Ok(MaintenanceReport {
+        freed_pages: before - after,
+        duration_ms: started.elapsed().as_millis(),
+    })
+}
+
+/// Tables copied by [`recover`], in creation order.
+const TABLES: [&str; 5] = ["events", "decisions", "outcomes", "memory_traces", "seen_messages"];
+
+/// Problems reported by `PRAGMA integrity_check`; empty when the database is
+/// healthy or does not exist yet. A file that cannot be read as a database at
+/// all is reported as a problem too.
+pub fn integrity_check() -> Result<Vec<String>, Error> {
+    let path = db_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let conn = Connection::open(&path).map_err(|e| Error::Memory(format!("sqlite open: {}", e)))?;
+    let rows: rusqlite::Result<Vec<String>> = conn
+        .prepare("PRAGMA integrity_check")
+        .and_then(|mut stmt| stmt.query_map([], |row| row.get::<_, String>(0))?.collect());
+    match rows {
+        Ok(rows) => Ok(rows.into_iter().filter(|r| r != "ok").collect()),
+        Err(e) => Ok(vec![e.to_string()]),
+    }
+}
+
+/// Result of [`recover`].
+#[derive(Debug, Clone)]
+pub struct RecoveryReport {
+    /// Where the original database was moved.
+    pub backup: std::path::PathBuf,
+    /// Rows copied into the new database, per table.
+    pub recovered: Vec<(String, usize)>,
+    /// Tables that could not be read (completely), with the error.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Dump-and-reload recovery: move the database aside as
+/// `events.db.corrupt-<timestamp>`, create a fresh one and copy every row
+/// that can still be read from the backup.
+pub fn recover() -> Result<RecoveryReport, Error> {
+    let path = db_path()?;
+    let backup = path.with_extension(format!("db.corrupt-{}", chrono::Utc::now().format("%Y%m%d%H%M%S")));
+    std::fs::rename(&path, &backup)?;
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let side = std::path::PathBuf::from(format!("{}{}", path.display(), suffix));
+        if side.exists() {
+            std::fs::rename(&side, format!("{}{}", backup.display(), suffix))?;
+        }
+    }
+
+    let conn = connect()?;
+    let mut report = RecoveryReport {
+        backup: backup.clone(),
+        recovered: Vec::new(),
+        failed: Vec::new(),
+    };
+    if let Err(e) = conn.execute("ATTACH DATABASE ?1 AS old", params![backup.to_string_lossy()]) {
+        report.failed.push(("*".to_string(), e.to_string()));
+        return Ok(report);
+    }
+    for table in TABLES {
+        let (copied, error) = copy_table(&conn, table);
+        report.recovered.push((table.to_string(), copied));
+        if let Some(e) = error {
+            report.failed.push((table.to_string(), e));
+        }
+    }
+    let _ = conn.execute_batch("DETACH DATABASE old");
+    Ok(report)
+}
+
+/// Copy rows of `table` from the attached `old` database until one fails to read.
+fn copy_table(conn: &Connection, table: &str) -> (usize, Option<String>) {
+    let mut select = match conn.prepare(&format!("SELECT * FROM old.{}", table)) {
+        Ok(stmt) => stmt,
+        Err(e) => return (0, Some(e.to_string())),
+    };
+    let columns = select.column_count();
+    let insert = format!("INSERT OR IGNORE INTO main.{} VALUES ({})", table, vec!["?"; columns].join(", "));
+    let mut rows = match select.query([]) {
+        Ok(rows) => rows,
+        Err(e) => return (0, Some(e.to_string())),
+    };
+    let mut copied = 0;
+    loop {
+        let row = match rows.next() {
+            Ok(Some(row)) => row,
+            Ok(None) => return (copied, None),
+            Err(e) => return (copied, Some(e.to_string())),
+        };
+        let values: rusqlite::Result<Vec<rusqlite::types::Value>> = (0..columns).map(|i| row.get(i)).collect();
+        let result = values.and_then(|v| conn.execute(&insert, rusqlite::params_from_iter(v)));
+        match result {
+            Ok(_) => copied += 1,
+            Err(e) => return (copied, Some(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maintain_reclaims_free_pages_after_vacuum_enables_incremental_mode() {
+        let _home = crate::config::test_support::TempHome::new();
+        let detail = "x".repeat(4096);
+        for i in 0..50 {
+            record_event("s1", "coder", "execution_started", &format!("{} {}", i, detail)).unwrap();
+        }
+        vacuum().unwrap();
+        delete_agent_records("coder").unwrap();
+
+        let report = maintain().unwrap();
+        assert!(report.freed_pages > 0, "{:?}", report);
+    }
+
+    #[test]
+    fn integrity_check_flags_corruption_and_recover_reloads_rows() {
+        let _home = crate::config::test_support::TempHome::new();
+        assert!(integrity_check().unwrap().is_empty());
+
+        record_event("s1", "coder", "execution_started", "first").unwrap();
+        record_outcome("s1", "coder", "success", None, "done").unwrap();
+        assert!(integrity_check().unwrap().is_empty());
+
+        // Reloading keeps every readable row and backs up the original.
+        let report = recover().unwrap();
+        assert!(report.backup.exists());
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+        assert!(report.recovered.contains(&("events".to_string(), 1)));
+        assert_eq!(count_agent_records("coder").unwrap(), 2);
+
+        std::fs::write(db_path().unwrap(), b"definitely not a sqlite database file").unwrap();
+        assert!(!integrity_check().unwrap().is_empty());
+        let report = recover().unwrap();
+        assert!(!report.failed.is_empty());
+        assert!(integrity_check().unwrap().is_empty());
+        assert_eq!(count_agent_records("coder").unwrap(), 0);
+    }
+
+    #[test]
+    fn memory_trace_round_trips_and_resolves_last() {
+        let _home = crate::config::test_support::TempHome::new();
+        record_memory_trace("t1", "s1", "coder", "fix login", "[global] a = 1").unwrap();
+        record_memory_trace("t2", "s1", "writer", "draft post", "").unwrap();
+
+        let trace = memory_trace("t1", None).unwrap().unwrap();
+        assert_eq!(trace.agent_id, "coder");
+        assert_eq!(trace.query, "fix login");
+        assert_eq!(trace.block, "[global] a = 1");
+        assert!(memory_trace("missing", None).unwrap().is_none());
+
+        assert_eq!(memory_trace("last", None).unwrap().unwrap().trace_id, "t2");
+        assert_eq!(memory_trace("last", Some("coder")).unwrap().unwrap().trace_id, "t1");
+        assert!(memory_trace("last", Some("nobody")).unwrap().is_none());
+
+        assert_eq!(count_agent_records("coder").unwrap(), 1);
+    }
+
+    #[test]
+    fn recent_for_agent_is_chronological_and_limited() {
+        let _home = crate::config::test_support::TempHome::new();
+        assert!(recent_for_agent("coder", 10).unwrap().is_empty());
+
+        let pause = || std::thread::sleep(std::time::Duration::from_millis(3));
+        record_event("s1", "coder", "execution_started", "first").unwrap();
+        pause();
+        record_decision("s1", "coder", "code", "coder", "P2", None, "matched code rule").unwrap();
+        pause();
+        record_event("s1", "writer", "execution_started", "other agent").unwrap();
+        pause();
+        record_outcome("s1", "coder", "failed", Some("TIMEOUT"), "took too long").unwrap();
+        pause();
+        record_event("s2", "coder", "execution_started", "last").unwrap();
+
+        let all = recent_for_agent("coder", 10).unwrap();
+        let kinds: Vec<&str> = all.iter().map(|r| r.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["event", "decision", "outcome", "event"]);
+        assert!(all.windows(2).all(|w| w[0].ts <= w[1].ts));
+        assert_eq!(all[2].label, "failed");
+        assert_eq!(all[2].detail, "TIMEOUT: took too long");
+
+        let latest = recent_for_agent("coder", 2).unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].label, "failed");
+        assert_eq!(latest[1].detail, "last");
+    }
+
+    #[test]
+    fn decisions_are_searchable_by_keyword_and_scope() {
+        let _home = crate::config::test_support::TempHome::new();
+        assert!(search_decisions("deploy", 10).unwrap().is_empty());
+
+        record_decision("s1", "coder", "code_change", "coder", "high", Some("friday"), "Fix the login bug").unwrap();
+        record_decision("s1", "ops", "deploy", "ops", "normal", None, "Ship release 1.2").unwrap();
+        record_decision("s2", "coder", "deploy", "coder", "low", None, "Redeploy staging").unwrap();
+
+        let hits = search_decisions("DEPLOY", 10).unwrap();
+        let reasons: Vec<&str> = hits.iter().map(|d| d.reason.as_str()).collect();
+        assert_eq!(reasons, ["Redeploy staging", "Ship release 1.2"]);
+        assert_eq!(search_decisions("deploy", 1).unwrap().len(), 1);
+        assert_eq!(search_decisions("friday", 10).unwrap()[0].deadline.as_deref(), Some("friday"));
+        assert!(search_decisions("100%", 10).unwrap().is_empty());
+
+        let coder = list_decisions(Some("coder"), 10).unwrap();
+        assert_eq!(coder.len(), 2);
+        assert_eq!((coder[1].intent.as_str(), coder[1].priority.as_str()), ("code_change", "high"));
+        assert_eq!(list_decisions(Some("s1"), 10).unwrap().len(), 2);
+        assert_eq!(list_decisions(None, 10).unwrap().len(), 3);
+    }
+}
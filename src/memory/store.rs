@@ -3,13 +3,13 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::config::get_home_dir;
 use crate::error::Error;
 
-use super::lock::with_lock;
+use super::lock::{acquire_lock, with_lock};
 
 /// Memory scope.
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
@@ -50,6 +50,22 @@ pub struct MemoryEntry {
     pub updated_at: i64,
     pub expires_at: Option<i64>,
     pub importance: f32,
+    /// Pinned entries keep their full importance regardless of age.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Earlier values, oldest first; recorded by [`Memory::set_tracked`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<MemoryRevision>,
+}
+
+/// Earlier values kept per entry; older ones are dropped.
+pub const HISTORY_LIMIT: usize = 10;
+
+/// One value a memory entry held, and when it was written.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MemoryRevision {
+    pub value: String,
+    pub updated_at: i64,
 }
 
 impl MemoryEntry {
@@ -70,9 +86,21 @@ impl MemoryEntry {
             updated_at: now,
             expires_at: None,
             importance: 1.0,
+            pinned: false,
+            history: Vec::new(),
         }
     }
 
+    /// Importance used for ranking at `now_ms`: `importance * exp(-decay_rate * age_days)`,
+    /// with age measured from the last update. Pinned entries do not decay.
+    pub fn effective_importance(&self, decay_rate: f32, now_ms: i64) -> f32 {
+        if self.pinned || decay_rate <= 0.0 {
+            return self.importance;
+        }
+        let age_days = (now_ms - self.updated_at).max(0) as f32 / 86_400_000.0;
+        self.importance * (-decay_rate * age_days).exp()
+    }
+
     /// Check if entry has expired.
     pub fn is_expired(&self) -> bool {
         if let Some(expires_at) = self.expires_at {
@@ -201,7 +229,13 @@ pub fn ensure_memory_dirs() -> Result<(), Error> {
     Ok(())
 }
 
+/// Marker in the name of a store file moved aside because it did not parse.
+const CORRUPT_MARKER: &str = ".corrupt.";
+
 /// Load memory store from file.
+///
+/// A file that does not parse is moved aside to
+/// `<name>.corrupt.<timestamp>.json` and an empty store is returned.
 pub fn load_store(scope: &MemoryScope, scope_id: Option<&str>) -> Result<MemoryStore, Error> {
     let path = get_memory_file(scope, scope_id)?;
 
@@ -210,8 +244,36 @@ pub fn load_store(scope: &MemoryScope, scope_id: Option<&str>) -> Result<MemoryS
     }
 
     let content = std::fs::read_to_string(&path)?;
-    let store: MemoryStore = serde_json::from_str(&content)?;
-    Ok(store)
+    match serde_json::from_str(&content) {
+        Ok(store) => Ok(store),
+        Err(e) => {
+            let quarantined = quarantine_store_file(&path)?;
+            tracing::warn!(
+                "Memory store {} is corrupt ({}); moved it to {} and starting empty",
+                path.display(),
+                e,
+                quarantined.display()
+            );
+            Ok(MemoryStore::new())
+        }
+    }
+}
+
+fn quarantine_store_file(path: &Path) -> Result<PathBuf, Error> {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let stamp = chrono::Utc::now().format("%Y%m%d%H%M%S%3f");
+    let target = path.with_file_name(format!("{}{}{}.json", stem, CORRUPT_MARKER, stamp));
+    match std::fs::rename(path, &target) {
+        // Another reader moved it first.
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(target),
+        other => other.map(|_| target).map_err(Error::from),
+    }
+}
+
+/// Whether `path` is a live store file, not a quarantined or temporary one.
+fn is_store_file(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "json")
+        && !path.file_name().is_some_and(|n| n.to_string_lossy().contains(CORRUPT_MARKER))
 }
 
 /// Save memory store to file.
@@ -230,20 +292,59 @@ pub fn save_store(
     }
 
     let content = serde_json::to_string_pretty(store)?;
-    std::fs::write(&path, content)?;
-    Ok(())
+    crate::config::atomic_write(&path, content)
 }
 
 /// Memory operations.
 pub struct Memory;
 
 impl Memory {
-    /// Set a memory entry.
+    /// Set a memory entry that never expires.
     pub fn set(
         key: &str,
         value: &str,
         scope: MemoryScope,
         scope_id: Option<&str>,
+    ) -> Result<(), Error> {
+        Self::set_with_ttl(key, value, scope, scope_id, None)
+    }
+
+    /// Set a memory entry that never expires, first adding the value it
+    /// replaces to the entry's history when `keep_history` is set (callers
+    /// pass `memory.track_history`).
+    pub fn set_tracked(
+        key: &str,
+        value: &str,
+        scope: MemoryScope,
+        scope_id: Option<&str>,
+        keep_history: bool,
+    ) -> Result<(), Error> {
+        Self::write(key, value, scope, scope_id, None, keep_history)
+    }
+
+    /// Set a memory entry that expires `ttl_ms` after this write (`None` never expires).
+    ///
+    /// Every write replaces the previous expiry. Internal writers of session
+    /// artifacts that are rewritten each turn, like `interaction.last_user` and
+    /// `interaction.last_response`, should pass a TTL so they self-clean; facts,
+    /// board decisions and delegation records use [`Memory::set`].
+    pub fn set_with_ttl(
+        key: &str,
+        value: &str,
+        scope: MemoryScope,
+        scope_id: Option<&str>,
+        ttl_ms: Option<i64>,
+    ) -> Result<(), Error> {
+        Self::write(key, value, scope, scope_id, ttl_ms, false)
+    }
+
+    fn write(
+        key: &str,
+        value: &str,
+        scope: MemoryScope,
+        scope_id: Option<&str>,
+        ttl_ms: Option<i64>,
+        keep_history: bool,
     ) -> Result<(), Error> {
         ensure_memory_dirs()?;
 
@@ -253,10 +354,21 @@ impl Memory {
             let mut store = load_store(&scope, scope_id).unwrap_or_default();
 
             let mut entry = MemoryEntry::new(key, value, scope.clone(), scope_id.map(String::from));
+            entry.expires_at = ttl_ms.map(|ttl| entry.updated_at + ttl);
 
-            // Preserve category if updating
+            // Preserve category, pin and history if updating
             if let Some(existing) = store.get(key) {
                 entry.category = existing.category.clone();
+                entry.pinned = existing.pinned;
+                entry.history = existing.history.clone();
+                if keep_history && existing.value != value {
+                    entry.history.push(MemoryRevision {
+                        value: existing.value.clone(),
+                        updated_at: existing.updated_at,
+                    });
+                    let excess = entry.history.len().saturating_sub(HISTORY_LIMIT);
+                    entry.history.drain(..excess);
+                }
             }
 
             store.set(entry);
@@ -290,6 +402,47 @@ impl Memory {
         Ok(store.get(key).cloned())
     }
 
+    /// Every value `key` held, oldest first and ending with the current one;
+    /// empty when the key does not exist.
+    pub fn history(
+        key: &str,
+        scope: MemoryScope,
+        scope_id: Option<&str>,
+    ) -> Result<Vec<MemoryRevision>, Error> {
+        let Some(entry) = Self::get(key, scope, scope_id)? else {
+            return Ok(Vec::new());
+        };
+        let mut revisions = entry.history;
+        revisions.push(MemoryRevision {
+            value: entry.value,
+            updated_at: entry.updated_at,
+        });
+        Ok(revisions)
+    }
+
+    /// Pin or unpin an entry. Returns `false` if the key does not exist.
+    pub fn set_pinned(
+        key: &str,
+        scope: MemoryScope,
+        scope_id: Option<&str>,
+        pinned: bool,
+    ) -> Result<bool, Error> {
+        let path = get_memory_file(&scope, scope_id)?;
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        with_lock(&path, || {
+            let mut store = load_store(&scope, scope_id)?;
+            let Some(entry) = store.entries.get_mut(key).filter(|e| !e.is_expired()) else {
+                return Ok(false);
+            };
+            entry.pinned = pinned;
+            save_store(&scope, scope_id, &store)?;
+            Ok(true)
+        })
+    }
+
     /// Delete a memory entry.
     pub fn delete(key: &str, scope: MemoryScope, scope_id: Option<&str>) -> Result<(), Error> {
         let path = get_memory_file(&scope, scope_id)?;
@@ -312,6 +465,33 @@ impl Memory {
         })
     }
 
+    /// Delete all entries whose key starts with `prefix`. Returns the number removed.
+    pub fn delete_prefix(prefix: &str, scope: MemoryScope, scope_id: Option<&str>) -> Result<usize, Error> {
+        let path = get_memory_file(&scope, scope_id)?;
+
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        with_lock(&path, || {
+            let mut store = load_store(&scope, scope_id).unwrap_or_default();
+            let before = store.entries.len();
+            store.entries.retain(|key, _| !key.starts_with(prefix));
+            let removed = before - store.entries.len();
+            if removed > 0 {
+                save_store(&scope, scope_id, &store)?;
+            }
+            tracing::debug!(
+                "Deleted {} memory entries with prefix {} (scope: {:?}, id: {:?})",
+                removed,
+                prefix,
+                scope,
+                scope_id
+            );
+            Ok(removed)
+        })
+    }
+
     /// List memory entries.
     pub fn list(
         scope: MemoryScope,
@@ -388,11 +568,15 @@ impl Memory {
     }
 
     /// Retrieve relevant memory entries for prompt context.
+    ///
+    /// Importance decays with age at `decay_rate` per day (`memory.decay_rate`)
+    /// for ranking only; stored values are untouched.
     pub fn relevant(
         query: &str,
         scope: MemoryScope,
         scope_id: Option<&str>,
         limit: usize,
+        decay_rate: f32,
     ) -> Result<Vec<MemoryEntry>, Error> {
         let path = get_memory_file(&scope, scope_id)?;
         if !path.exists() {
@@ -400,13 +584,14 @@ impl Memory {
         }
         let store = load_store(&scope, scope_id)?;
         let q = query.to_lowercase();
+        let now = chrono::Utc::now().timestamp_millis();
         let mut entries: Vec<MemoryEntry> = store
             .entries
             .values()
             .filter(|e| !e.is_expired())
             .map(|e| {
                 let mut c = e.clone();
-                let mut score = c.importance;
+                let mut score = c.effective_importance(decay_rate, now);
                 let kl = c.key.to_lowercase();
                 let vl = c.value.to_lowercase();
                 if !q.is_empty() {
@@ -515,6 +700,72 @@ impl Memory {
         Ok(())
     }
 
+    /// Physically remove expired entries from a scope's store file.
+    ///
+    /// Returns how many entries were removed; the file is only rewritten when
+    /// something expired.
+    pub fn gc(scope: MemoryScope, scope_id: Option<&str>) -> Result<usize, Error> {
+        let path = get_memory_file(&scope, scope_id)?;
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        with_lock(&path, || {
+            let mut store = load_store(&scope, scope_id)?;
+            let removed = store.cleanup();
+            if removed > 0 {
+                save_store(&scope, scope_id, &store)?;
+                tracing::debug!("GC removed {} expired entries from {}", removed, path.display());
+            }
+            Ok(removed)
+        })
+    }
+
+    /// Run [`Memory::gc`] over every store file, or only those of `scope`.
+    pub fn gc_all(scope: Option<MemoryScope>) -> Result<Vec<GcReport>, Error> {
+        let mut reports = Vec::new();
+        let wanted = |s: MemoryScope| scope.is_none_or(|only| only == s);
+
+        if wanted(MemoryScope::Global) {
+            reports.push(GcReport {
+                scope: MemoryScope::Global,
+                scope_id: None,
+                removed: Self::gc(MemoryScope::Global, None)?,
+            });
+        }
+
+        let mem_dir = get_memory_dir()?;
+        for (dir, dir_scope) in [
+            ("agents", MemoryScope::Agent),
+            ("teams", MemoryScope::Team),
+            ("tasks", MemoryScope::Task),
+        ] {
+            if !wanted(dir_scope) {
+                continue;
+            }
+            let dir = mem_dir.join(dir);
+            if !dir.exists() {
+                continue;
+            }
+            let mut ids: Vec<String> = std::fs::read_dir(&dir)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| is_store_file(p))
+                .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+                .collect();
+            ids.sort();
+            for id in ids {
+                let removed = Self::gc(dir_scope, Some(&id))?;
+                reports.push(GcReport {
+                    scope: dir_scope,
+                    scope_id: Some(id),
+                    removed,
+                });
+            }
+        }
+
+        Ok(reports)
+    }
+
     /// Compact memory: dedupe, merge similar, cleanup expired, promote high-signal.
     pub fn compact(scope: MemoryScope, scope_id: Option<&str>) -> Result<CompactReport, Error> {
         let path = get_memory_file(&scope, scope_id)?;
@@ -562,6 +813,178 @@ impl Memory {
             Ok(report)
         })
     }
+
+    /// Copy every store file into `snapshots/<name>/`, keeping the layout of
+    /// the memory directory.
+    pub fn snapshot_create(name: &str) -> Result<SnapshotInfo, Error> {
+        validate_snapshot_name(name)?;
+        let mem_dir = get_memory_dir()?;
+        let target = snapshots_dir()?.join(name);
+        if target.exists() {
+            return Err(Error::AlreadyExists(format!("Snapshot '{}'", name)));
+        }
+        std::fs::create_dir_all(&target)?;
+
+        let mut files = 0;
+        for rel in store_files(&mem_dir)? {
+            let dest = target.join(&rel);
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(mem_dir.join(&rel), dest)?;
+            files += 1;
+        }
+
+        let info = SnapshotInfo {
+            name: name.to_string(),
+            created_at: chrono::Utc::now().timestamp_millis(),
+            files,
+        };
+        std::fs::write(target.join(SNAPSHOT_MANIFEST), serde_json::to_string_pretty(&info)?)?;
+        tracing::info!("Created memory snapshot '{}' ({} files)", name, files);
+        Ok(info)
+    }
+
+    /// Snapshots under `snapshots/`, oldest first.
+    pub fn snapshot_list() -> Result<Vec<SnapshotInfo>, Error> {
+        let dir = snapshots_dir()?;
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut snapshots: Vec<SnapshotInfo> = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path().join(SNAPSHOT_MANIFEST)))
+            .filter_map(|manifest| std::fs::read_to_string(manifest).ok())
+            .filter_map(|content| serde_json::from_str(&content).ok())
+            .collect();
+        snapshots.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.name.cmp(&b.name)));
+        Ok(snapshots)
+    }
+
+    /// Replace the current store files with those saved in snapshot `name`.
+    ///
+    /// The snapshot is copied to a staging directory first and only renamed
+    /// into place once every affected store file is locked, so a failed copy
+    /// leaves the store untouched.
+    pub fn snapshot_restore(name: &str) -> Result<SnapshotInfo, Error> {
+        validate_snapshot_name(name)?;
+        let source = snapshots_dir()?.join(name);
+        let manifest = std::fs::read_to_string(source.join(SNAPSHOT_MANIFEST))
+            .map_err(|_| Error::NotFound(format!("Snapshot '{}'", name)))?;
+        let info: SnapshotInfo = serde_json::from_str(&manifest)?;
+
+        let staging = snapshots_dir()?.join(format!(".restoring-{}", ulid::Ulid::new()));
+        let restored = restore_staged(&source, &staging);
+        let _ = std::fs::remove_dir_all(&staging);
+        restored?;
+        tracing::info!("Restored memory snapshot '{}'", name);
+        Ok(info)
+    }
+
+    /// Delete snapshots whose name starts with `prefix` and that were created
+    /// more than `retention_days` ago; returns their names. `0` days keeps
+    /// everything.
+    pub fn snapshot_prune(prefix: &str, retention_days: u64) -> Result<Vec<String>, Error> {
+        if retention_days == 0 {
+            return Ok(Vec::new());
+        }
+        let cutoff = chrono::Utc::now().timestamp_millis() - retention_days as i64 * 86_400_000;
+        let dir = snapshots_dir()?;
+        let mut removed = Vec::new();
+        for snapshot in Self::snapshot_list()? {
+            if snapshot.name.starts_with(prefix) && snapshot.created_at < cutoff {
+                std::fs::remove_dir_all(dir.join(&snapshot.name))?;
+                removed.push(snapshot.name);
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// File written into each snapshot directory describing it.
+const SNAPSHOT_MANIFEST: &str = "snapshot.json";
+
+/// A saved copy of the memory store files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub name: String,
+    /// Creation time in milliseconds since the epoch.
+    pub created_at: i64,
+    /// Number of store files copied.
+    pub files: usize,
+}
+
+fn snapshots_dir() -> Result<PathBuf, Error> {
+    Ok(get_memory_dir()?.join("snapshots"))
+}
+
+fn validate_snapshot_name(name: &str) -> Result<(), Error> {
+    let valid = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Memory(format!(
+            "Invalid snapshot name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        )))
+    }
+}
+
+/// Stage the store files of snapshot `source` in `staging`, then swap them in
+/// for the current ones while holding the lock of each file involved.
+fn restore_staged(source: &Path, staging: &Path) -> Result<(), Error> {
+    let restored = store_files(source)?;
+    for rel in &restored {
+        let dest = staging.join(rel);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(source.join(rel), dest)?;
+    }
+
+    let mem_dir = get_memory_dir()?;
+    let mut affected = store_files(&mem_dir)?;
+    affected.extend(restored.iter().cloned());
+    affected.sort();
+    affected.dedup();
+    let mut locks = Vec::with_capacity(affected.len());
+    for rel in &affected {
+        let path = mem_dir.join(rel);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        locks.push(acquire_lock(&path)?);
+    }
+    for rel in &affected {
+        if restored.contains(rel) {
+            std::fs::rename(staging.join(rel), mem_dir.join(rel))?;
+        } else {
+            std::fs::remove_file(mem_dir.join(rel))?;
+        }
+    }
+    Ok(())
+}
+
+/// Store files under `root` (`global.json` and the per-scope directories),
+/// relative to it.
+fn store_files(root: &std::path::Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+    if root.join("global.json").is_file() {
+        files.push(PathBuf::from("global.json"));
+    }
+    for dir in ["agents", "teams", "tasks"] {
+        let Ok(entries) = std::fs::read_dir(root.join(dir)) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() && is_store_file(&path) {
+                files.push(PathBuf::from(dir).join(entry.file_name()));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
 }
 
 #[derive(Debug, Clone, Default)]
@@ -572,6 +995,14 @@ pub struct CompactReport {
     pub pruned: usize,
 }
 
+/// Expired entries removed from one store file by [`Memory::gc_all`].
+#[derive(Debug, Clone)]
+pub struct GcReport {
+    pub scope: MemoryScope,
+    pub scope_id: Option<String>,
+    pub removed: usize,
+}
+
 fn scope_limit(scope: MemoryScope, _scope_id: Option<&str>) -> usize {
     match scope {
         MemoryScope::Global => GLOBAL_LIMIT,
@@ -682,4 +1113,169 @@ mod tests {
         store.delete("key1");
         assert!(store.get("key1").is_none());
     }
+
+    #[test]
+    fn old_unpinned_entries_decay_below_fresh_ones() {
+        let _home = crate::config::test_support::TempHome::new();
+        let day_ms = 86_400_000;
+        let mut store = MemoryStore::new();
+        for (key, age_days, pinned) in [("stale", 120, false), ("pinned", 120, true), ("fresh", 0, false)] {
+            let mut e = MemoryEntry::new(key, "note", MemoryScope::Global, None);
+            e.updated_at -= age_days * day_ms;
+            e.pinned = pinned;
+            store.set(e);
+        }
+        save_store(&MemoryScope::Global, None, &store).unwrap();
+
+        let ranked = Memory::relevant("", MemoryScope::Global, None, 10, 0.02).unwrap();
+        let keys: Vec<&str> = ranked.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, ["fresh", "pinned", "stale"]);
+        assert!(ranked[1].importance - ranked[2].importance > 0.8);
+
+        // Ranking never rewrites the stored importance.
+        let stored = Memory::get("stale", MemoryScope::Global, None).unwrap().unwrap();
+        assert_eq!(stored.importance, 1.0);
+
+        assert!(Memory::set_pinned("stale", MemoryScope::Global, None, true).unwrap());
+        assert!(!Memory::set_pinned("missing", MemoryScope::Global, None, true).unwrap());
+        let ranked = Memory::relevant("", MemoryScope::Global, None, 10, 0.02).unwrap();
+        let score = |key: &str| ranked.iter().find(|e| e.key == key).unwrap().importance;
+        assert!((score("stale") - score("pinned")).abs() < 0.01);
+    }
+
+    #[test]
+    fn gc_removes_expired_entries_from_disk() {
+        let _home = crate::config::test_support::TempHome::new();
+        Memory::set("fresh", "keep me", MemoryScope::Agent, Some("coder")).unwrap();
+
+        let mut store = load_store(&MemoryScope::Agent, Some("coder")).unwrap();
+        let mut stale = MemoryEntry::new("stale", "old news", MemoryScope::Agent, Some("coder".to_string()));
+        stale.expires_at = Some(stale.created_at - 1000);
+        store.set(stale);
+        save_store(&MemoryScope::Agent, Some("coder"), &store).unwrap();
+
+        let path = get_memory_file(&MemoryScope::Agent, Some("coder")).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("old news"));
+        assert!(Memory::get("stale", MemoryScope::Agent, Some("coder")).unwrap().is_none());
+
+        let reports = Memory::gc_all(None).unwrap();
+        let coder = reports
+            .iter()
+            .find(|r| r.scope == MemoryScope::Agent && r.scope_id.as_deref() == Some("coder"))
+            .unwrap();
+        assert_eq!(coder.removed, 1);
+
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("old news"));
+        assert!(on_disk.contains("keep me"));
+        assert_eq!(Memory::gc(MemoryScope::Agent, Some("coder")).unwrap(), 0);
+    }
+
+    #[test]
+    fn snapshots_restore_store_files_and_prune_by_prefix() {
+        let _home = crate::config::test_support::TempHome::new();
+        Memory::set("owner", "alice", MemoryScope::Global, None).unwrap();
+        Memory::set("lang", "rust", MemoryScope::Agent, Some("coder")).unwrap();
+
+        let info = Memory::snapshot_create("before").unwrap();
+        assert_eq!(info.files, 2);
+        assert!(matches!(Memory::snapshot_create("before"), Err(Error::AlreadyExists(_))));
+        assert!(Memory::snapshot_create("../escape").is_err());
+
+        Memory::set("owner", "bob", MemoryScope::Global, None).unwrap();
+        Memory::set("lang", "go", MemoryScope::Team, Some("dev")).unwrap();
+        Memory::snapshot_restore("before").unwrap();
+        assert_eq!(Memory::get("owner", MemoryScope::Global, None).unwrap().unwrap().value, "alice");
+        assert!(Memory::get("lang", MemoryScope::Agent, Some("coder")).unwrap().is_some());
+        assert!(Memory::get("lang", MemoryScope::Team, Some("dev")).unwrap().is_none());
+        assert!(matches!(Memory::snapshot_restore("missing"), Err(Error::NotFound(_))));
+
+        // A locked store file makes the restore fail without touching anything.
+        Memory::set("owner", "carol", MemoryScope::Global, None).unwrap();
+        let global = get_memory_file(&MemoryScope::Global, None).unwrap();
+        let lock = acquire_lock(&global).unwrap();
+        assert!(Memory::snapshot_restore("before").is_err());
+        drop(lock);
+        assert_eq!(Memory::get("owner", MemoryScope::Global, None).unwrap().unwrap().value, "carol");
+        let mut leftovers = std::fs::read_dir(snapshots_dir().unwrap()).unwrap().flatten();
+        assert!(!leftovers.any(|e| e.file_name().to_string_lossy().starts_with(".restoring-")));
+
+        Memory::snapshot_create("auto-old").unwrap();
+        let manifest = get_memory_dir().unwrap().join("snapshots/auto-old/snapshot.json");
+        let mut old: SnapshotInfo = serde_json::from_str(&std::fs::read_to_string(&manifest).unwrap()).unwrap();
+        old.created_at -= 10 * 86_400_000;
+        std::fs::write(&manifest, serde_json::to_string(&old).unwrap()).unwrap();
+
+        assert!(Memory::snapshot_prune("auto-", 0).unwrap().is_empty());
+        assert_eq!(Memory::snapshot_prune("auto-", 7).unwrap(), vec!["auto-old".to_string()]);
+        let names: Vec<String> = Memory::snapshot_list().unwrap().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["before".to_string()]);
+    }
+
+    #[test]
+    fn history_keeps_the_last_values_in_order() {
+        let _home = crate::config::test_support::TempHome::new();
+        Memory::set("k", "v0", MemoryScope::Global, None).unwrap();
+        Memory::set_tracked("k", "v1", MemoryScope::Global, None, false).unwrap();
+        // Off unless asked for: only the current value.
+        let values = |key| -> Vec<String> {
+            Memory::history(key, MemoryScope::Global, None).unwrap().into_iter().map(|r| r.value).collect()
+        };
+        assert_eq!(values("k"), ["v1"]);
+
+        for i in 1..=12 {
+            Memory::set_tracked("k", &format!("v{}", i), MemoryScope::Global, None, true).unwrap();
+        }
+        // Rewriting the same value records nothing, and untracked writes keep the history.
+        Memory::set_tracked("k", "v12", MemoryScope::Global, None, true).unwrap();
+        Memory::set("k", "v12", MemoryScope::Global, None).unwrap();
+
+        let expected: Vec<String> = (2..=12).map(|i| format!("v{}", i)).collect();
+        assert_eq!(values("k"), expected);
+        let revisions = Memory::history("k", MemoryScope::Global, None).unwrap();
+        assert!(revisions.windows(2).all(|w| w[0].updated_at <= w[1].updated_at));
+        assert_eq!(revisions.len(), HISTORY_LIMIT + 1);
+        assert!(Memory::history("missing", MemoryScope::Global, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn corrupt_store_files_are_quarantined() {
+        let home = crate::config::test_support::TempHome::new();
+        Memory::set("kept", "yes", MemoryScope::Agent, Some("coder")).unwrap();
+        let path = get_memory_file(&MemoryScope::Agent, Some("coder")).unwrap();
+        // Truncated mid-write.
+        std::fs::write(&path, "{\"entries\": {\"kept\": {\"key\": \"ke").unwrap();
+
+        assert!(load_store(&MemoryScope::Agent, Some("coder")).unwrap().entries.is_empty());
+        assert!(!path.exists());
+        let moved: Vec<String> = std::fs::read_dir(home.tinyvegeta_dir().join("memory").join("agents"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(moved.len(), 1);
+        assert!(moved[0].starts_with("coder.corrupt.") && moved[0].ends_with(".json"), "{:?}", moved);
+
+        // Writes start over, and the quarantined file is not treated as a scope.
+        Memory::set("fresh", "1", MemoryScope::Agent, Some("coder")).unwrap();
+        assert!(Memory::get("fresh", MemoryScope::Agent, Some("coder")).unwrap().is_some());
+        let ids: Vec<String> = Memory::gc_all(None).unwrap().into_iter().filter_map(|r| r.scope_id).collect();
+        assert_eq!(ids, vec!["coder".to_string()]);
+    }
+
+    #[test]
+    fn saves_replace_the_store_atomically() {
+        let _home = crate::config::test_support::TempHome::new();
+        Memory::set("a", "1", MemoryScope::Global, None).unwrap();
+        let path = get_memory_file(&MemoryScope::Global, None).unwrap();
+        let tmp = path.with_extension("json.tmp");
+
+        // A save interrupted before its rename leaves only a partial temp file.
+        std::fs::write(&tmp, "{\"entries\": {").unwrap();
+        assert_eq!(Memory::get("a", MemoryScope::Global, None).unwrap().unwrap().value, "1");
+
+        Memory::set("b", "2", MemoryScope::Global, None).unwrap();
+        assert!(!tmp.exists());
+        let store = load_store(&MemoryScope::Global, None).unwrap();
+        assert_eq!(store.entries.len(), 2);
+    }
 }
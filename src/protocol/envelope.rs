@@ -36,6 +36,7 @@ pub struct Envelope {
     pub max_attempts: u32,
 }
 
+#[allow(dead_code)]
 impl Envelope {
     /// Create a new envelope from an agent to another agent.
     pub fn new(from_agent: impl Into<String>, to_agent: impl Into<String>, message: AgentMessage) -> Self {
@@ -176,6 +177,7 @@ impl Envelope {
 }
 
 /// Builder for creating envelopes with fluent API.
+#[allow(dead_code)]
 pub struct EnvelopeBuilder {
     from_agent: String,
     to_agent: Option<String>,
@@ -189,6 +191,7 @@ pub struct EnvelopeBuilder {
     priority: Priority,
 }
 
+#[allow(dead_code)]
 impl EnvelopeBuilder {
     /// Start building an envelope from an agent.
     pub fn from(agent_id: impl Into<String>) -> Self {
@@ -213,12 +216,14 @@ impl EnvelopeBuilder {
     }
 
     /// Address to multiple agents.
+    #[allow(clippy::wrong_self_convention)]
     pub fn to_many(mut self, agent_ids: Vec<String>) -> Self {
         self.to_agents = agent_ids;
         self
     }
 
     /// Address to a team.
+    #[allow(clippy::wrong_self_convention)]
     pub fn to_team(mut self, team_id: impl Into<String>) -> Self {
         self.team_id = Some(team_id.into());
         self
@@ -347,6 +352,8 @@ mod tests {
             .build()
             .unwrap();
 
+        // Expiry is strict, so let the clock move past `expires_at`.
+        std::thread::sleep(std::time::Duration::from_millis(2));
         assert!(envelope.is_expired());
     }
 
@@ -9,7 +9,7 @@ use std::sync::{Arc, Mutex};
 use serde::{Deserialize, Serialize};
 
 use super::envelope::Envelope;
-use super::types::{MessageStatus, Priority};
+use super::types::{AgentMessage, MessageStatus, MessageType, Priority};
 
 /// Maximum messages per mailbox before pruning.
 const MAX_MAILBOX_SIZE: usize = 1000;
@@ -36,6 +36,7 @@ pub struct AgentMailbox {
     pub last_activity: i64,
 }
 
+#[allow(dead_code)]
 impl AgentMailbox {
     /// Create a new mailbox for an agent.
     pub fn new(agent_id: impl Into<String>) -> Self {
@@ -88,6 +89,7 @@ impl AgentMailbox {
     }
 
     /// Get messages from a specific sender.
+    #[allow(clippy::wrong_self_convention)]
     pub fn from_agent(&self, agent_id: &str) -> Vec<&Envelope> {
         self.inbox
             .iter()
@@ -151,6 +153,27 @@ impl AgentMailbox {
         before - self.inbox.len()
     }
 
+    /// Read every pending envelope, highest priority first (oldest first
+    /// within a priority). Drained envelopes are marked read but stay in the
+    /// inbox until completed or failed.
+    pub fn drain(&mut self) -> Vec<Envelope> {
+        let mut drained = Vec::new();
+        for envelope in self.inbox.iter_mut() {
+            if matches!(envelope.message.status, MessageStatus::Pending | MessageStatus::Delivered) {
+                envelope.message.mark_read();
+                drained.push(envelope.clone());
+            }
+        }
+        if !drained.is_empty() {
+            self.last_activity = current_timestamp();
+        }
+        drained.sort_by(|a, b| {
+            b.message.priority.cmp(&a.message.priority)
+                .then_with(|| a.created_at.cmp(&b.created_at))
+        });
+        drained
+    }
+
     /// Get the next pending message (highest priority first).
     pub fn next_pending(&self) -> Option<&Envelope> {
         self.inbox
@@ -188,6 +211,7 @@ impl AgentMailbox {
 
 /// Mailbox statistics.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct MailboxStats {
     pub agent_id: String,
     pub inbox_count: usize,
@@ -208,6 +232,7 @@ pub struct MailboxStore {
     cache: Arc<Mutex<HashMap<String, AgentMailbox>>>,
 }
 
+#[allow(dead_code)]
 impl MailboxStore {
     /// Create a new mailbox store.
     pub fn new(base_path: impl AsRef<Path>) -> Self {
@@ -246,7 +271,6 @@ impl MailboxStore {
 
     /// Deliver an envelope to an agent's mailbox.
     pub fn deliver(&self, agent_id: &str, envelope: Envelope) {
-        let mailbox_path = self.mailbox_path(agent_id);
         let mut mailbox = self.get_mailbox(agent_id);
         mailbox.deliver(envelope);
         self.save_mailbox(&mailbox);
@@ -255,6 +279,66 @@ impl MailboxStore {
         cache.insert(agent_id.to_string(), mailbox);
     }
 
+    /// Broadcast an envelope to every member of a team except its sender.
+    ///
+    /// Each member receives a copy with the same id, `team_id` and
+    /// correlation ID (the envelope id when none is set), so replies built
+    /// with [`Envelope::create_response`] link back to the broadcast. The
+    /// sender's outbox keeps the original as the audit record.
+    ///
+    /// Returns the agents the envelope was delivered to.
+    pub fn broadcast(&self, team_id: &str, members: &[String], mut envelope: Envelope) -> Vec<String> {
+        let recipients: Vec<String> = members
+            .iter()
+            .filter(|m| **m != envelope.from_agent)
+            .cloned()
+            .collect();
+
+        envelope.message.message_type = MessageType::Broadcast;
+        envelope.team_id = Some(team_id.to_string());
+        envelope.to_agent = None;
+        envelope.to_agents = recipients.clone();
+        if envelope.correlation_id.is_none() {
+            envelope.correlation_id = Some(envelope.id.clone());
+        }
+
+        for agent_id in &recipients {
+            self.deliver(agent_id, envelope.clone());
+        }
+        let sender = envelope.from_agent.clone();
+        self.queue_outgoing(&sender, envelope);
+        recipients
+    }
+
+    /// [`AgentMailbox::drain`] for `agent_id`, saved to disk.
+    pub fn drain(&self, agent_id: &str) -> Vec<Envelope> {
+        let mut mailbox = self.get_mailbox(agent_id);
+        let drained = mailbox.drain();
+        if !drained.is_empty() {
+            self.update(&mailbox);
+        }
+        drained
+    }
+
+    /// Answer the envelope in `agent_id`'s inbox carrying `correlation_id`:
+    /// complete it and deliver the response to its sender.
+    ///
+    /// Returns the response, or `None` when no such envelope is waiting.
+    pub fn respond(&self, agent_id: &str, correlation_id: &str, message: AgentMessage) -> Option<Envelope> {
+        let mut mailbox = self.get_mailbox(agent_id);
+        let request = mailbox
+            .inbox
+            .iter()
+            .find(|e| e.correlation_id.as_deref() == Some(correlation_id))?
+            .clone();
+        mailbox.complete(&request.id);
+        self.update(&mailbox);
+
+        let response = request.create_response(agent_id, message);
+        self.deliver(&request.from_agent, response.clone());
+        Some(response)
+    }
+
     /// Queue an outgoing message.
     pub fn queue_outgoing(&self, agent_id: &str, envelope: Envelope) {
         let mut mailbox = self.get_mailbox(agent_id);
@@ -368,7 +452,6 @@ fn current_timestamp() -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::protocol::types::AgentMessage;
 
     #[test]
     fn test_mailbox_creation() {
@@ -435,4 +518,54 @@ mod tests {
         let mailbox = store.get_mailbox("coder");
         assert_eq!(mailbox.inbox.len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_broadcast_reaches_every_member() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = MailboxStore::new(temp_dir.path());
+        let members = vec!["ceo".to_string(), "coder".to_string(), "writer".to_string()];
+
+        let envelope = Envelope::to_team("ceo", "dev", AgentMessage::broadcast("Ship it"));
+        let delivered = store.broadcast("dev", &members, envelope);
+        assert_eq!(delivered, vec!["coder".to_string(), "writer".to_string()]);
+
+        for member in ["coder", "writer"] {
+            let pending = store.drain(member);
+            assert_eq!(pending.len(), 1);
+            assert_eq!(pending[0].message.message_type, MessageType::Broadcast);
+            assert_eq!(pending[0].team_id.as_deref(), Some("dev"));
+            assert!(store.drain(member).is_empty());
+        }
+        assert!(store.get_mailbox("ceo").inbox.is_empty());
+        assert_eq!(store.get_mailbox("ceo").outbox.len(), 1);
+
+        // Drained state survives a reload from disk.
+        let reloaded = MailboxStore::new(temp_dir.path());
+        assert_eq!(reloaded.get_mailbox("coder").unread().len(), 0);
+    }
+
+    #[test]
+    fn test_broadcast_replies_share_correlation_id() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = MailboxStore::new(temp_dir.path());
+        let members = vec!["ceo".to_string(), "coder".to_string(), "writer".to_string()];
+
+        let envelope = Envelope::to_team("ceo", "dev", AgentMessage::broadcast("Status?"))
+            .with_correlation_id("corr-1");
+        let broadcast_id = envelope.id.clone();
+        store.broadcast("dev", &members, envelope);
+
+        let reply = store
+            .respond("coder", "corr-1", AgentMessage::response("On track"))
+            .unwrap();
+        assert_eq!(reply.correlation_id.as_deref(), Some("corr-1"));
+        assert_eq!(reply.reply_to.as_deref(), Some(broadcast_id.as_str()));
+        assert!(store.get_mailbox("coder").inbox.is_empty());
+        assert!(store.respond("coder", "corr-1", AgentMessage::response("again")).is_none());
+
+        let replies = store.drain("ceo");
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].from_agent, "coder");
+        assert_eq!(replies[0].correlation_id.as_deref(), Some("corr-1"));
+    }
+}
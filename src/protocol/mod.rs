@@ -10,6 +10,6 @@ pub mod envelope;
 pub mod mailbox;
 pub mod types;
 
-pub use envelope::{Envelope, EnvelopeBuilder};
-pub use mailbox::{AgentMailbox, MailboxStore};
-pub use types::{MessageType, Priority, MessageStatus, AgentMessage};
\ No newline at end of file
+pub use envelope::Envelope;
+pub use mailbox::MailboxStore;
+pub use types::AgentMessage;
\ No newline at end of file
@@ -21,21 +21,16 @@ pub enum MessageType {
 }
 
 /// Message priority levels.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "snake_case")]
 pub enum Priority {
     Low = 0,
+    #[default]
     Normal = 1,
     High = 2,
     Urgent = 3,
 }
 
-impl Default for Priority {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 /// Message delivery status.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -77,6 +72,7 @@ pub struct AgentMessage {
     pub error: Option<String>,
 }
 
+#[allow(dead_code)]
 impl AgentMessage {
     /// Create a new request message.
     pub fn request(body: impl Into<String>) -> Self {
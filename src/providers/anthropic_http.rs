@@ -0,0 +1,317 @@
+//! Anthropic HTTP provider (Messages API, no CLI required).
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::provider::{
+    CompletionParams, Provider, ProviderError, ResponseFormat, Result, RetryPolicy, Usage,
+};
+
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+const API_VERSION: &str = "2023-06-01";
+const DEFAULT_MAX_TOKENS: u32 = 8192;
+
+pub struct AnthropicHttpProvider {
+    client: Client,
+    api_key: Option<String>,
+    base_url: String,
+    default_model: String,
+    last_usage: Mutex<Option<Usage>>,
+    retry: RetryPolicy,
+}
+
+#[derive(Serialize)]
+struct MessagesRequest {
+    model: String,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<Message>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+    #[serde(default)]
+    usage: Option<ApiUsage>,
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+#[derive(Deserialize)]
+struct ModelsResponse {
+    #[serde(default)]
+    data: Vec<ModelInfo>,
+}
+
+#[derive(Deserialize)]
+struct ModelInfo {
+    id: String,
+}
+
+/// Map the short aliases used by the claude CLI to API model IDs.
+pub fn resolve_model_alias(model: &str) -> &str {
+    match model {
+        "sonnet" => "claude-sonnet-4-5",
+        "opus" => "claude-opus-4-1",
+        "haiku" => "claude-haiku-4-5",
+        "sonnet-3.5" => "claude-3-5-sonnet-latest",
+        other => other,
+    }
+}
+
+impl AnthropicHttpProvider {
+    /// Build from settings values, falling back to `ANTHROPIC_API_KEY`.
+    pub fn new(api_key: Option<String>, base_url: Option<String>) -> Self {
+        let api_key = api_key
+            .filter(|k| !k.trim().is_empty())
+            .or_else(|| env::var("ANTHROPIC_API_KEY").ok());
+        let base_url = base_url
+            .filter(|u| !u.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+
+        Self {
+            client: Client::new(),
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            default_model: "claude-sonnet-4-5".to_string(),
+            last_usage: Mutex::new(None),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Retry schedule for transient HTTP errors.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    fn get_api_key(&self) -> Result<&str> {
+        self.api_key
+            .as_deref()
+            .ok_or_else(|| ProviderError::NotAvailable("ANTHROPIC_API_KEY not set".to_string()))
+    }
+
+    async fn send(&self, api_key: &str, request: &MessagesRequest) -> Result<MessagesResponse> {
+        let response = self.client
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("x-api-key", api_key)
+            .header("anthropic-version", API_VERSION)
+            .header("Content-Type", "application/json")
+            .json(request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(ProviderError::HttpStatus { status, body: text });
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+impl Default for AnthropicHttpProvider {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicHttpProvider {
+    fn name(&self) -> &str {
+        "anthropic-http"
+    }
+
+    async fn is_available(&self) -> bool {
+        if self.api_key.is_none() {
+            return false;
+        }
+
+        // Any HTTP response means the endpoint is reachable.
+        self.client
+            .get(&self.base_url)
+            .send()
+            .await
+            .is_ok()
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let fallback = || super::known_model_ids("anthropic-http");
+        let Ok(api_key) = self.get_api_key() else {
+            return Ok(fallback());
+        };
+
+        let response = self.client
+            .get(format!("{}/v1/models", self.base_url))
+            .header("x-api-key", api_key)
+            .header("anthropic-version", API_VERSION)
+            .send()
+            .await;
+
+        match response {
+            Ok(resp) if resp.status().is_success() => {
+                let models: ModelsResponse = resp.json().await?;
+                if models.data.is_empty() {
+                    Ok(fallback())
+                } else {
+                    Ok(models.data.into_iter().map(|m| m.id).collect())
+                }
+            }
+            _ => Ok(fallback()),
+        }
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        working_dir: Option<&Path>,
+    ) -> Result<String> {
+        self.complete_with_params(prompt, model, working_dir, &CompletionParams::default())
+            .await
+    }
+
+    async fn complete_with_params(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        _working_dir: Option<&Path>,
+        params: &CompletionParams,
+    ) -> Result<String> {
+        let api_key = self.get_api_key()?;
+        let model = match model {
+            None | Some("default") => self.default_model.as_str(),
+            Some(m) => resolve_model_alias(m),
+        };
+
+        // The Messages API has no JSON mode; ask for it in the system prompt.
+        let system = match params.response_format {
+            ResponseFormat::JsonObject => Some(
+                "Respond with a single valid JSON object and nothing else.".to_string(),
+            ),
+            ResponseFormat::Text => None,
+        };
+
+        let request = MessagesRequest {
+            model: model.to_string(),
+            max_tokens: DEFAULT_MAX_TOKENS,
+            system,
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: prompt.to_string(),
+            }],
+        };
+
+        let messages_response = self.retry.run(|| self.send(api_key, &request)).await?;
+
+        if let Ok(mut last) = self.last_usage.lock() {
+            *last = messages_response.usage.as_ref().map(|u| Usage {
+                prompt_tokens: u.input_tokens,
+                completion_tokens: u.output_tokens,
+            });
+        }
+
+        let text: Vec<String> = messages_response
+            .content
+            .into_iter()
+            .filter(|block| block.kind == "text")
+            .filter_map(|block| block.text)
+            .collect();
+        if text.is_empty() {
+            return Err(ProviderError::ApiError("No text content in response".to_string()));
+        }
+        Ok(text.join(""))
+    }
+
+    fn default_model(&self) -> Option<&str> {
+        Some(&self.default_model)
+    }
+
+    fn last_usage(&self) -> Option<Usage> {
+        self.last_usage.lock().ok().and_then(|u| *u)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::HeaderMap, routing::post, Json, Router};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn complete_posts_messages_request_and_parses_reply() {
+        let seen: Arc<Mutex<Option<(HeaderMap, serde_json::Value)>>> = Arc::new(Mutex::new(None));
+        let captured = seen.clone();
+        let app = Router::new().route(
+            "/v1/messages",
+            post(move |headers: HeaderMap, Json(body): Json<serde_json::Value>| {
+                let captured = captured.clone();
+                async move {
+                    *captured.lock().unwrap() = Some((headers, body));
+                    Json(serde_json::json!({
+                        "id": "msg_1",
+                        "type": "message",
+                        "role": "assistant",
+                        "content": [
+                            { "type": "text", "text": "Hello" },
+                            { "type": "text", "text": " there" }
+                        ],
+                        "usage": { "input_tokens": 12, "output_tokens": 3 }
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let provider = AnthropicHttpProvider::new(
+            Some("test-key".to_string()),
+            Some(format!("http://{}/", addr)),
+        );
+        let reply = provider.complete("Hi", Some("haiku"), None).await.unwrap();
+        assert_eq!(reply, "Hello there");
+
+        let usage = provider.last_usage().unwrap();
+        assert_eq!(usage.prompt_tokens, 12);
+        assert_eq!(usage.completion_tokens, 3);
+
+        let (headers, body) = seen.lock().unwrap().take().unwrap();
+        assert_eq!(headers["x-api-key"], "test-key");
+        assert_eq!(headers["anthropic-version"], API_VERSION);
+        assert_eq!(body["model"], "claude-haiku-4-5");
+        assert_eq!(body["messages"][0]["role"], "user");
+        assert_eq!(body["messages"][0]["content"], "Hi");
+        assert!(body.get("system").is_none());
+    }
+}
@@ -0,0 +1,164 @@
+//! Run one prompt through several providers/models and compare them.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Semaphore;
+
+use crate::agent::{execute_with_contract, ExecutionContract};
+use crate::config::Settings;
+
+/// A provider and the model to benchmark it with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkTarget {
+    pub provider: String,
+    /// Explicit model; falls back to the provider's configured model.
+    pub model: Option<String>,
+}
+
+/// Outcome of one benchmark run.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResult {
+    pub provider: String,
+    pub model: Option<String>,
+    pub latency_ms: u64,
+    /// Response length in characters (0 on failure).
+    pub chars: usize,
+    pub response: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Build targets from `providers` and `provider=model` overrides.
+pub fn parse_targets(providers: &[String], models: &[String]) -> Result<Vec<BenchmarkTarget>, String> {
+    let mut overrides: HashMap<&str, &str> = HashMap::new();
+    for spec in models {
+        let (provider, model) = spec
+            .split_once('=')
+            .filter(|(p, m)| !p.trim().is_empty() && !m.trim().is_empty())
+            .ok_or_else(|| format!("invalid model override '{}' (expected provider=model)", spec))?;
+        overrides.insert(provider.trim(), model.trim());
+    }
+    if let Some(unused) = overrides.keys().find(|p| !providers.iter().any(|name| name == *p)) {
+        return Err(format!("model override for '{}', which is not being benchmarked", unused));
+    }
+    Ok(providers
+        .iter()
+        .map(|name| BenchmarkTarget {
+            provider: name.clone(),
+            model: overrides.get(name.as_str()).map(|m| m.to_string()),
+        })
+        .collect())
+}
+
+/// Run `prompt` through every target concurrently, at most `per_provider`
+/// at a time for any one provider. Each run gets the provider's execution
+/// timeout and a single attempt, though HTTP providers still retry transient
+/// errors per `models.max_retries`. Results keep the order of `targets`.
+pub async fn run(
+    prompt: &str,
+    targets: &[BenchmarkTarget],
+    settings: &Settings,
+    per_provider: usize,
+) -> Vec<BenchmarkResult> {
+    let mut limits: HashMap<String, Arc<Semaphore>> = HashMap::new();
+    let mut handles = Vec::new();
+
+    for target in targets {
+        let limit = limits
+            .entry(target.provider.clone())
+            .or_insert_with(|| Arc::new(Semaphore::new(per_provider.max(1))))
+            .clone();
+        let provider = super::create_provider(&target.provider, settings);
+        let model = target
+            .model
+            .clone()
+            .or_else(|| super::configured_model(&target.provider, settings).map(String::from));
+        let contract = ExecutionContract {
+            retries: 0,
+            ..ExecutionContract::for_agent(&target.provider)
+        };
+        let name = target.provider.clone();
+        let prompt = prompt.to_string();
+
+        handles.push(tokio::spawn(async move {
+            let _permit = limit.acquire_owned().await;
+            let started = Instant::now();
+            let outcome = execute_with_contract(provider, &prompt, model.as_deref(), None, &contract).await;
+            let latency_ms = started.elapsed().as_millis() as u64;
+            let (response, error) = match outcome {
+                Ok(text) => (Some(text), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            BenchmarkResult {
+                provider: name,
+                model,
+                latency_ms,
+                chars: response.as_deref().map_or(0, |r| r.chars().count()),
+                response,
+                error,
+            }
+        }));
+    }
+
+    let mut results = Vec::with_capacity(handles.len());
+    for (handle, target) in handles.into_iter().zip(targets) {
+        results.push(handle.await.unwrap_or_else(|e| BenchmarkResult {
+            provider: target.provider.clone(),
+            model: target.model.clone(),
+            latency_ms: 0,
+            chars: 0,
+            response: None,
+            error: Some(format!("benchmark task failed: {}", e)),
+        }));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+
+    #[test]
+    fn parses_targets_with_model_overrides() {
+        let providers = vec!["ollama".to_string(), "grok".to_string()];
+        let targets = parse_targets(&providers, &["ollama=qwen3".to_string()]).unwrap();
+        assert_eq!(targets[0].model.as_deref(), Some("qwen3"));
+        assert_eq!(targets[1].model, None);
+
+        assert!(parse_targets(&providers, &["ollama".to_string()]).is_err());
+        assert!(parse_targets(&providers, &["claude=opus".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn measures_each_target_in_order() {
+        let app = Router::new().route(
+            "/api/chat",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                let model = body["model"].as_str().unwrap_or_default().to_string();
+                Json(serde_json::json!({ "message": { "content": format!("hello from {}", model) } }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let mut settings = Settings::default();
+        settings.models.ollama.base_url = Some(format!("http://{}", addr));
+        settings.models.ollama.model = Some("llama3".to_string());
+        let targets = vec![
+            BenchmarkTarget { provider: "ollama".to_string(), model: Some("qwen3".to_string()) },
+            BenchmarkTarget { provider: "ollama".to_string(), model: None },
+        ];
+
+        let results = run("hi", &targets, &settings, 2).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].response.as_deref(), Some("hello from qwen3"));
+        assert_eq!(results[1].model.as_deref(), Some("llama3"));
+        assert_eq!(results[1].chars, "hello from llama3".len());
+        assert!(results.iter().all(|r| r.error.is_none()));
+    }
+}
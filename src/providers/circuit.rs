@@ -0,0 +1,163 @@
+//! Per-provider circuit breaker, stored in `provider-circuits.json` under
+//! the TinyVegeta home.
+//!
+//! After `failure_threshold` consecutive failures a provider's circuit opens
+//! for `cooldown_secs`; queued work for it is rejected until the cooldown
+//! passes or a health check sees the provider recover.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{get_home_dir, CircuitBreakerOptions};
+use crate::error::Error;
+
+/// Circuit state of one provider.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CircuitStatus {
+    /// Consecutive failures since the last success.
+    pub failures: u32,
+    /// When the current (or last) open window ends, in epoch ms.
+    pub open_until_ms: Option<i64>,
+}
+
+impl CircuitStatus {
+    /// Whether the circuit rejects work at `now_ms`.
+    pub fn is_open_at(&self, now_ms: i64) -> bool {
+        self.open_until_ms.is_some_and(|until| now_ms < until)
+    }
+
+    /// Whether the circuit rejects work right now.
+    pub fn is_open(&self) -> bool {
+        self.is_open_at(now_ms())
+    }
+
+    /// Seconds left in the open window (0 when closed).
+    pub fn remaining_secs(&self) -> i64 {
+        self.open_until_ms
+            .map(|until| ((until - now_ms()).max(0) + 999) / 1000)
+            .unwrap_or(0)
+    }
+}
+
+/// Serialises read-modify-write cycles on the circuit file.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn circuits_file_path() -> Result<std::path::PathBuf, Error> {
+    Ok(get_home_dir()?.join("provider-circuits.json"))
+}
+
+/// Every provider's circuit state; a missing or unreadable file yields none.
+fn load() -> BTreeMap<String, CircuitStatus> {
+    circuits_file_path()
+        .and_then(|path| Ok(std::fs::read_to_string(path)?))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(circuits: &BTreeMap<String, CircuitStatus>) -> Result<(), Error> {
+    let path = circuits_file_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    crate::config::atomic_write(&path, serde_json::to_string_pretty(circuits)?)
+}
+
+/// Current circuit state of `provider`.
+pub fn status(provider: &str) -> CircuitStatus {
+    load().remove(provider).unwrap_or_default()
+}
+
+/// Record a failed execution. Opens the circuit once the threshold is hit;
+/// a failure after the cooldown expires reopens it immediately.
+///
+/// Returns whether this failure tripped the circuit.
+pub fn record_failure(provider: &str, options: &CircuitBreakerOptions) -> Result<bool, Error> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut circuits = load();
+    let circuit = circuits.entry(provider.to_string()).or_default();
+    circuit.failures = circuit.failures.saturating_add(1);
+    let tripped = options.failure_threshold > 0 && circuit.failures >= options.failure_threshold;
+    if tripped {
+        circuit.open_until_ms = Some(now_ms() + (options.cooldown_secs as i64).saturating_mul(1000));
+        tracing::warn!(
+            "Provider {} circuit opened after {} consecutive failures ({}s cooldown)",
+            provider,
+            circuit.failures,
+            options.cooldown_secs
+        );
+    }
+    save(&circuits)?;
+    Ok(tripped)
+}
+
+/// Close the circuit and reset the failure count.
+///
+/// Returns whether the circuit had been tripped.
+pub fn close(provider: &str) -> Result<bool, Error> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut circuits = load();
+    let Some(current) = circuits.remove(provider) else {
+        return Ok(false);
+    };
+    save(&circuits)?;
+    Ok(current.open_until_ms.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_and_auto_closes_after_cooldown() {
+        let _home = crate::config::test_support::TempHome::new();
+        let options = CircuitBreakerOptions {
+            failure_threshold: 2,
+            cooldown_secs: 60,
+        };
+
+        assert!(!record_failure("grok", &options).unwrap());
+        assert!(!status("grok").is_open());
+        assert!(record_failure("grok", &options).unwrap());
+        let open = status("grok");
+        assert_eq!(open.failures, 2);
+        assert!(open.is_open());
+        assert!(open.remaining_secs() > 0);
+        assert!(circuits_file_path().unwrap().exists());
+        assert!(!status("ollama").is_open());
+
+        // Once the cooldown window has passed the provider is tried again.
+        let after_cooldown = open.open_until_ms.unwrap();
+        assert!(!open.is_open_at(after_cooldown));
+
+        assert!(close("grok").unwrap());
+        assert_eq!(status("grok"), CircuitStatus::default());
+        assert!(!close("grok").unwrap());
+    }
+
+    #[test]
+    fn zero_cooldown_closes_immediately_and_zero_threshold_disables() {
+        let _home = crate::config::test_support::TempHome::new();
+        let instant = CircuitBreakerOptions {
+            failure_threshold: 1,
+            cooldown_secs: 0,
+        };
+        assert!(record_failure("claude", &instant).unwrap());
+        assert!(!status("claude").is_open());
+
+        let disabled = CircuitBreakerOptions {
+            failure_threshold: 0,
+            cooldown_secs: 60,
+        };
+        for _ in 0..5 {
+            assert!(!record_failure("codex", &disabled).unwrap());
+        }
+        assert!(!status("codex").is_open());
+    }
+}
@@ -6,7 +6,8 @@ use std::path::Path;
 use std::process::Stdio;
 use tokio::process::Command;
 
-use super::provider::{Provider, ProviderError, Result};
+use super::process::run_cli;
+use super::provider::{CompletionParams, Provider, ProviderError, Result};
 
 pub struct ClaudeProvider {
     cli_path: String,
@@ -59,11 +60,7 @@ impl Provider for ClaudeProvider {
     }
     
     async fn list_models(&self) -> Result<Vec<String>> {
-        Ok(vec![
-            "sonnet".to_string(),
-            "opus".to_string(),
-            "haiku".to_string(),
-        ])
+        Ok(super::known_model_ids("claude"))
     }
     
     async fn complete(
@@ -71,6 +68,17 @@ impl Provider for ClaudeProvider {
         prompt: &str,
         model: Option<&str>,
         working_dir: Option<&Path>,
+    ) -> Result<String> {
+        self.complete_with_params(prompt, model, working_dir, &CompletionParams::default())
+            .await
+    }
+    
+    async fn complete_with_params(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        working_dir: Option<&Path>,
+        params: &CompletionParams,
     ) -> Result<String> {
         let mut cmd = Command::new(&self.cli_path);
         cmd.arg("-c")
@@ -85,10 +93,7 @@ impl Provider for ClaudeProvider {
             cmd.current_dir(dir);
         }
         
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-        
-        let output = cmd.output().await?;
+        let output = run_cli(&mut cmd, params.cancel.as_ref()).await?;
         
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
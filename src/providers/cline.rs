@@ -7,7 +7,8 @@ use std::path::Path;
 use std::process::Stdio;
 use tokio::process::Command;
 
-use super::provider::{Provider, ProviderError, Result};
+use super::process::run_cli;
+use super::provider::{CompletionParams, Provider, ProviderError, Result};
 
 pub struct ClineProvider {
     cli_path: String,
@@ -138,11 +139,7 @@ impl Provider for ClineProvider {
     }
     
     async fn list_models(&self) -> Result<Vec<String>> {
-        Ok(vec![
-            "default".to_string(),
-            "claude-sonnet-4-20250514".to_string(),
-            "gpt-4o".to_string(),
-        ])
+        Ok(super::known_model_ids("cline"))
     }
     
     async fn complete(
@@ -150,6 +147,17 @@ impl Provider for ClineProvider {
         prompt: &str,
         model: Option<&str>,
         working_dir: Option<&Path>,
+    ) -> Result<String> {
+        self.complete_with_params(prompt, model, working_dir, &CompletionParams::default())
+            .await
+    }
+    
+    async fn complete_with_params(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        working_dir: Option<&Path>,
+        params: &CompletionParams,
     ) -> Result<String> {
         let mut cmd = Command::new(&self.cli_path);
         cmd.arg("task")
@@ -164,10 +172,7 @@ impl Provider for ClineProvider {
             cmd.current_dir(dir);
         }
         
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-        
-        let output = cmd.output().await?;
+        let output = run_cli(&mut cmd, params.cancel.as_ref()).await?;
 
         if output.status.success() {
             let raw = String::from_utf8_lossy(&output.stdout).to_string();
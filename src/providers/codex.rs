@@ -6,7 +6,8 @@ use std::path::Path;
 use std::process::Stdio;
 use tokio::process::Command;
 
-use super::provider::{Provider, ProviderError, Result};
+use super::process::run_cli;
+use super::provider::{CompletionParams, Provider, ProviderError, Result};
 
 pub struct CodexProvider {
     cli_path: String,
@@ -59,10 +60,7 @@ impl Provider for CodexProvider {
     }
     
     async fn list_models(&self) -> Result<Vec<String>> {
-        Ok(vec![
-            "gpt-5.3-codex".to_string(),
-            "gpt-4o-codex".to_string(),
-        ])
+        Ok(super::known_model_ids("codex"))
     }
     
     async fn complete(
@@ -70,6 +68,17 @@ impl Provider for CodexProvider {
         prompt: &str,
         model: Option<&str>,
         working_dir: Option<&Path>,
+    ) -> Result<String> {
+        self.complete_with_params(prompt, model, working_dir, &CompletionParams::default())
+            .await
+    }
+    
+    async fn complete_with_params(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        working_dir: Option<&Path>,
+        params: &CompletionParams,
     ) -> Result<String> {
         let mut cmd = Command::new(&self.cli_path);
         // Use non-interactive mode and place flags before prompt.
@@ -88,10 +97,7 @@ impl Provider for CodexProvider {
             cmd.current_dir(dir);
         }
         
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-        
-        let output = cmd.output().await?;
+        let output = run_cli(&mut cmd, params.cancel.as_ref()).await?;
         
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
@@ -6,20 +6,27 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::path::Path;
+use std::sync::Mutex;
 
-use super::provider::{Provider, ProviderError, Result};
+use super::provider::{
+    CompletionParams, Provider, ProviderError, ResponseFormat, Result, RetryPolicy, Usage,
+};
 
 pub struct GrokProvider {
     client: Client,
     api_key: Option<String>,
     base_url: String,
     default_model: String,
+    last_usage: Mutex<Option<Usage>>,
+    retry: RetryPolicy,
 }
 
 #[derive(Serialize)]
 struct ChatRequest {
     messages: Vec<Message>,
     model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 #[derive(Serialize)]
@@ -31,6 +38,16 @@ struct Message {
 #[derive(Deserialize)]
 struct ChatResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<ApiUsage>,
+}
+
+#[derive(Deserialize)]
+struct ApiUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -54,6 +71,8 @@ impl GrokProvider {
             api_key,
             base_url: "https://api.x.ai/v1".to_string(),
             default_model: "grok-4".to_string(),
+            last_usage: Mutex::new(None),
+            retry: RetryPolicy::default(),
         }
     }
     
@@ -63,14 +82,40 @@ impl GrokProvider {
             api_key: Some(api_key.into()),
             base_url: "https://api.x.ai/v1".to_string(),
             default_model: "grok-4".to_string(),
+            last_usage: Mutex::new(None),
+            retry: RetryPolicy::default(),
         }
     }
     
+    /// Retry schedule for transient HTTP errors.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+    
     fn get_api_key(&self) -> Result<&str> {
         self.api_key
             .as_deref()
             .ok_or_else(|| ProviderError::NotAvailable("XAI_API_KEY not set".to_string()))
     }
+    
+    async fn send(&self, api_key: &str, request: &ChatRequest) -> Result<ChatResponse> {
+        let response = self.client
+            .post(format!("{}/chat/completions", self.base_url))
+            .header("Authorization", format!("Bearer {}", api_key))
+            .header("Content-Type", "application/json")
+            .json(request)
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(ProviderError::HttpStatus { status, body: text });
+        }
+        
+        Ok(response.json().await?)
+    }
 }
 
 impl Default for GrokProvider {
@@ -99,19 +144,25 @@ impl Provider for GrokProvider {
     }
     
     async fn list_models(&self) -> Result<Vec<String>> {
-        Ok(vec![
-            "grok-4".to_string(),
-            "grok-4-latest".to_string(),
-            "grok-2".to_string(),
-            "grok-2-vision-1212".to_string(),
-        ])
+        Ok(super::known_model_ids("grok"))
     }
     
     async fn complete(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        working_dir: Option<&Path>,
+    ) -> Result<String> {
+        self.complete_with_params(prompt, model, working_dir, &CompletionParams::default())
+            .await
+    }
+    
+    async fn complete_with_params(
         &self,
         prompt: &str,
         model: Option<&str>,
         _working_dir: Option<&Path>,
+        params: &CompletionParams,
     ) -> Result<String> {
         let api_key = self.get_api_key()?;
         let model = model.unwrap_or(&self.default_model);
@@ -122,24 +173,21 @@ impl Provider for GrokProvider {
                 content: prompt.to_string(),
             }],
             model: model.to_string(),
+            response_format: match params.response_format {
+                ResponseFormat::JsonObject => Some(serde_json::json!({ "type": "json_object" })),
+                ResponseFormat::Text => None,
+            },
         };
         
-        let response = self.client
-            .post(format!("{}/chat/completions", self.base_url))
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+        let chat_response = self.retry.run(|| self.send(api_key, &request)).await?;
         
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(ProviderError::ApiError(format!("HTTP {}: {}", status, text)));
+        if let Ok(mut last) = self.last_usage.lock() {
+            *last = chat_response.usage.as_ref().map(|u| Usage {
+                prompt_tokens: u.prompt_tokens,
+                completion_tokens: u.completion_tokens,
+            });
         }
         
-        let chat_response: ChatResponse = response.json().await?;
-        
         chat_response
             .choices
             .first()
@@ -150,4 +198,8 @@ impl Provider for GrokProvider {
     fn default_model(&self) -> Option<&str> {
         Some(&self.default_model)
     }
+    
+    fn last_usage(&self) -> Option<Usage> {
+        self.last_usage.lock().ok().and_then(|u| *u)
+    }
 }
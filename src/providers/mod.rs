@@ -4,32 +4,158 @@
 use std::sync::Arc;
 
 pub mod provider;
+pub mod process;
 pub mod claude;
 pub mod codex;
 pub mod cline;
 pub mod opencode;
 pub mod ollama;
 pub mod grok;
+pub mod anthropic_http;
+pub mod benchmark;
+pub mod circuit;
 
-pub use provider::{Provider, Result};
+pub use provider::{
+    cancel_pair, CancelToken, CompletionParams, Provider, ProviderError, Result, RetryPolicy, Usage,
+};
 
 use crate::config::Settings;
 
+/// Model configured in `models.*` for a provider, if any.
+pub fn configured_model<'a>(name: &str, settings: &'a Settings) -> Option<&'a str> {
+    match name {
+        "claude" | "anthropic-http" => settings.models.anthropic.model.as_deref(),
+        "codex" => settings.models.openai.model.as_deref(),
+        "grok" => settings.models.grok.model.as_deref(),
+        "ollama" => settings.models.ollama.model.as_deref(),
+        _ => None,
+    }
+}
+
+/// Whether `name` is a CLI provider, which picks its own model unless told.
+pub fn is_cli_provider(name: &str) -> bool {
+    matches!(name, "claude" | "codex" | "cline" | "opencode")
+}
+
+/// Make `name` the active provider in `settings`.
+///
+/// The primary agent (`assistant`, else the first agent) follows the switch;
+/// `model`, if given, becomes both its model and the provider's default.
+/// Callers validate `name` against [`PROVIDER_NAMES`] and save the settings.
+pub fn switch_provider(settings: &mut Settings, name: &str, model: Option<&str>) {
+    settings.models.provider = name.to_string();
+
+    let target_agent_id = if settings.agents.contains_key("assistant") {
+        Some("assistant".to_string())
+    } else {
+        settings.agents.keys().next().cloned()
+    };
+    if let Some(agent) = target_agent_id.and_then(|id| settings.agents.get_mut(&id)) {
+        agent.provider = Some(name.to_string());
+        match model {
+            Some(m) => agent.model = Some(m.to_string()),
+            // For CLI providers, "default" means use whatever the CLI selected.
+            None if is_cli_provider(name) => agent.model = Some("default".to_string()),
+            None => {}
+        }
+    }
+
+    if let Some(m) = model {
+        let m = Some(m.to_string());
+        match name {
+            "claude" | "anthropic-http" => settings.models.anthropic.model = m,
+            "codex" => settings.models.openai.model = m,
+            "grok" => settings.models.grok.model = m,
+            "ollama" => settings.models.ollama.model = m,
+            _ => {}
+        }
+    }
+}
+
+/// Curated `(model, description)` choices for a provider, best first. Used by
+/// the setup wizard, and by `list_models` for providers that cannot enumerate
+/// their models.
+pub fn known_models(provider: &str) -> &'static [(&'static str, &'static str)] {
+    match provider {
+        "claude" => &[
+            ("sonnet", "Claude Sonnet 4 (balanced, fast)"),
+            ("opus", "Claude Opus 4 (most capable)"),
+            ("sonnet-3.5", "Claude Sonnet 3.5 (legacy)"),
+            ("haiku", "Claude Haiku 3.5 (fastest)"),
+        ],
+        "codex" => &[
+            ("gpt-5.3-codex", "GPT-5.3 Codex (recommended)"),
+            ("o3", "O3 (advanced reasoning)"),
+            ("o4-mini", "O4 Mini (fast, cheap)"),
+            ("gpt-4.1", "GPT-4.1 (legacy)"),
+        ],
+        "cline" | "opencode" => &[
+            ("default", "Default model"),
+            ("claude-sonnet", "Claude Sonnet"),
+            ("gpt-4o", "GPT-4o"),
+        ],
+        "ollama" => &[
+            ("llama3.3", "Llama 3.3 (latest)"),
+            ("llama3.1", "Llama 3.1 (stable)"),
+            ("codellama", "Code Llama"),
+            ("mistral", "Mistral"),
+            ("deepseek-coder", "DeepSeek Coder"),
+        ],
+        "anthropic-http" => &[
+            ("sonnet", "Claude Sonnet 4.5 (balanced, fast)"),
+            ("opus", "Claude Opus 4.1 (most capable)"),
+            ("haiku", "Claude Haiku 4.5 (fastest)"),
+        ],
+        "grok" => &[
+            ("grok-4", "Grok 4 (latest)"),
+            ("grok-2", "Grok 2"),
+            ("grok-2-mini", "Grok 2 Mini (fast)"),
+            ("grok-2-vision-1212", "Grok 2 Vision"),
+        ],
+        _ => &[("default", "Default")],
+    }
+}
+
+/// Model names from [`known_models`].
+pub fn known_model_ids(provider: &str) -> Vec<String> {
+    known_models(provider).iter().map(|(id, _)| id.to_string()).collect()
+}
+
+/// Provider names understood by [`create_provider`].
+pub const PROVIDER_NAMES: &[&str] = &["claude", "codex", "cline", "opencode", "ollama", "grok", "anthropic-http"];
+
 /// Provider factory.
+///
+/// HTTP providers retry transient errors up to `models.max_retries` times;
+/// CLI providers never retry.
 pub fn create_provider(name: &str, settings: &Settings) -> Arc<dyn Provider> {
+    let retry = RetryPolicy::with_max_retries(settings.models.max_retries);
     match name {
         "claude" => Arc::new(claude::ClaudeProvider::new()),
         "codex" => Arc::new(codex::CodexProvider::new()),
         "cline" => Arc::new(cline::ClineProvider::new()),
         "opencode" => Arc::new(opencode::OpenCodeProvider::new()),
         "ollama" => {
-            if let Some(url) = &settings.models.ollama.base_url {
-                Arc::new(ollama::OllamaProvider::with_base_url(url.clone()))
+            let provider = if let Some(url) = &settings.models.ollama.base_url {
+                ollama::OllamaProvider::with_base_url(url.clone())
             } else {
-                Arc::new(ollama::OllamaProvider::new())
-            }
+                ollama::OllamaProvider::new()
+            };
+            Arc::new(
+                provider
+                    .with_auto_pull(settings.providers.ollama.auto_pull)
+                    .with_retry(retry),
+            )
         }
-        "grok" => Arc::new(grok::GrokProvider::new()),
+        "grok" => Arc::new(grok::GrokProvider::new().with_retry(retry)),
+        "anthropic-http" => Arc::new(
+            anthropic_http::AnthropicHttpProvider::new(
+                settings.models.anthropic.api_key.clone(),
+                settings.models.anthropic.base_url.clone(),
+            )
+            .with_retry(retry),
+        ),
+        // Unknown names fall back to cline; `config validate` reports them.
         _ => Arc::new(cline::ClineProvider::new()),
     }
 }
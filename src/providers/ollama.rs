@@ -5,13 +5,19 @@ use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::sync::Mutex;
 
-use super::provider::{Provider, Result};
+use super::provider::{
+    CompletionParams, Provider, ProviderError, ResponseFormat, Result, RetryPolicy, Usage,
+};
 
 pub struct OllamaProvider {
     client: Client,
     base_url: String,
     default_model: String,
+    auto_pull: bool,
+    last_usage: Mutex<Option<Usage>>,
+    retry: RetryPolicy,
 }
 
 #[derive(Serialize)]
@@ -19,6 +25,8 @@ struct ChatRequest {
     model: String,
     messages: Vec<Message>,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -30,6 +38,10 @@ struct Message {
 #[derive(Deserialize)]
 struct ChatResponse {
     message: ResponseMessage,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
 }
 
 #[derive(Deserialize)]
@@ -37,6 +49,24 @@ struct ResponseMessage {
     content: String,
 }
 
+#[derive(Serialize)]
+struct PullRequest {
+    model: String,
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct PullProgress {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    completed: Option<u64>,
+    #[serde(default)]
+    total: Option<u64>,
+}
+
 #[derive(Deserialize)]
 struct ModelsResponse {
     models: Vec<ModelInfo>,
@@ -53,6 +83,9 @@ impl OllamaProvider {
             client: Client::new(),
             base_url: "http://localhost:11434".to_string(),
             default_model: "llama3.2".to_string(),
+            auto_pull: false,
+            last_usage: Mutex::new(None),
+            retry: RetryPolicy::default(),
         }
     }
     
@@ -61,6 +94,9 @@ impl OllamaProvider {
             client: Client::new(),
             base_url: base_url.into(),
             default_model: "llama3.2".to_string(),
+            auto_pull: false,
+            last_usage: Mutex::new(None),
+            retry: RetryPolicy::default(),
         }
     }
     
@@ -69,10 +105,138 @@ impl OllamaProvider {
             client: Client::new(),
             base_url: "http://localhost:11434".to_string(),
             default_model: model.into(),
+            auto_pull: false,
+            last_usage: Mutex::new(None),
+            retry: RetryPolicy::default(),
         }
     }
 }
 
+impl OllamaProvider {
+    /// Pull missing models on demand (`providers.ollama.auto_pull`).
+    pub fn with_auto_pull(mut self, auto_pull: bool) -> Self {
+        self.auto_pull = auto_pull;
+        self
+    }
+
+    /// Retry schedule for transient HTTP errors.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+    
+    async fn chat(&self, prompt: &str, model: &str, params: &CompletionParams) -> Result<String> {
+        let request = ChatRequest {
+            model: model.to_string(),
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: prompt.to_string(),
+            }],
+            stream: false,
+            format: match params.response_format {
+                ResponseFormat::JsonObject => Some("json".to_string()),
+                ResponseFormat::Text => None,
+            },
+        };
+        
+        let response = self.client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&request)
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            if status == reqwest::StatusCode::NOT_FOUND || is_missing_model_error(&text) {
+                return Err(ProviderError::ModelNotFound(model.to_string()));
+            }
+            return Err(ProviderError::HttpStatus { status, body: text });
+        }
+        
+        let chat_response: ChatResponse = response.json().await?;
+        
+        if let Ok(mut last) = self.last_usage.lock() {
+            *last = match (chat_response.prompt_eval_count, chat_response.eval_count) {
+                (None, None) => None,
+                (prompt, completion) => Some(Usage {
+                    prompt_tokens: prompt.unwrap_or(0),
+                    completion_tokens: completion.unwrap_or(0),
+                }),
+            };
+        }
+        
+        Ok(chat_response.message.content)
+    }
+    
+    /// Pull a model via `/api/pull`, logging streamed progress.
+    async fn pull_model(&self, model: &str) -> Result<()> {
+        tracing::info!("Ollama model {} not found locally, pulling...", model);
+        
+        let mut response = self.client
+            .post(format!("{}/api/pull", self.base_url))
+            .json(&PullRequest {
+                model: model.to_string(),
+                stream: true,
+            })
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(ProviderError::ApiError(format!("pull {} failed: HTTP {}: {}", model, status, text)));
+        }
+        
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut last_status = String::new();
+        let mut last_percent = None;
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+            while let Some(idx) = buffer.iter().position(|b| *b == b'\n') {
+                let raw: Vec<u8> = buffer.drain(..=idx).collect();
+                let line = String::from_utf8_lossy(&raw).trim().to_string();
+                if line.is_empty() {
+                    continue;
+                }
+                let Ok(progress) = serde_json::from_str::<PullProgress>(&line) else {
+                    continue;
+                };
+                if let Some(err) = progress.error {
+                    return Err(ProviderError::ApiError(format!("pull {} failed: {}", model, err)));
+                }
+                let percent = match (progress.completed, progress.total) {
+                    (Some(done), Some(total)) if total > 0 => Some(done * 100 / total / 10 * 10),
+                    _ => None,
+                };
+                if progress.status != last_status || percent != last_percent {
+                    match percent {
+                        Some(p) => tracing::info!("Pulling {}: {} ({}%)", model, progress.status, p),
+                        None => tracing::info!("Pulling {}: {}", model, progress.status),
+                    }
+                    last_status = progress.status;
+                    last_percent = percent;
+                }
+            }
+        }
+        
+        if last_status != "success" {
+            return Err(ProviderError::ApiError(format!(
+                "pull {} ended without success (last status: {})",
+                model, last_status
+            )));
+        }
+        
+        tracing::info!("Pulled ollama model {}", model);
+        Ok(())
+    }
+}
+
+fn is_missing_model_error(body: &str) -> bool {
+    let lower = body.to_lowercase();
+    lower.contains("not found") && (lower.contains("model") || lower.contains("pull"))
+}
+
 impl Default for OllamaProvider {
     fn default() -> Self {
         Self::new()
@@ -99,40 +263,135 @@ impl Provider for OllamaProvider {
             .send()
             .await?;
         
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(ProviderError::HttpStatus { status, body });
+        }
+        
         let models: ModelsResponse = response.json().await?;
         
         Ok(models.models.into_iter().map(|m| m.name).collect())
     }
     
     async fn complete(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        working_dir: Option<&Path>,
+    ) -> Result<String> {
+        self.complete_with_params(prompt, model, working_dir, &CompletionParams::default())
+            .await
+    }
+    
+    async fn complete_with_params(
         &self,
         prompt: &str,
         model: Option<&str>,
         _working_dir: Option<&Path>,
+        params: &CompletionParams,
     ) -> Result<String> {
         let model = model.unwrap_or(&self.default_model);
         
-        let request = ChatRequest {
-            model: model.to_string(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
-            stream: false,
-        };
-        
-        let response = self.client
-            .post(format!("{}/api/chat", self.base_url))
-            .json(&request)
-            .send()
-            .await?;
-        
-        let chat_response: ChatResponse = response.json().await?;
-        
-        Ok(chat_response.message.content)
+        let chat = || self.chat(prompt, model, params);
+        match self.retry.run(chat).await {
+            Err(ProviderError::ModelNotFound(_)) if self.auto_pull => {
+                self.pull_model(model).await?;
+                self.retry.run(chat).await
+            }
+            result => result,
+        }
     }
     
     fn default_model(&self) -> Option<&str> {
         Some(&self.default_model)
     }
+    
+    fn last_usage(&self) -> Option<Usage> {
+        self.last_usage.lock().ok().and_then(|u| *u)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::{get, post}, Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    fn fast_retry(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_but_not_client_errors() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let flaky = Router::new().route(
+            "/api/chat",
+            post(move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                        Err((StatusCode::SERVICE_UNAVAILABLE, "busy"))
+                    } else {
+                        Ok(Json(serde_json::json!({ "message": { "content": "ok" } })))
+                    }
+                }
+            }),
+        );
+        let provider = OllamaProvider::with_base_url(serve(flaky).await).with_retry(fast_retry(2));
+        assert_eq!(provider.complete("hi", Some("llama3"), None).await.unwrap(), "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let unauthorized = Router::new().route(
+            "/api/chat",
+            post(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { (StatusCode::UNAUTHORIZED, "bad key") }
+            }),
+        );
+        let provider = OllamaProvider::with_base_url(serve(unauthorized).await).with_retry(fast_retry(2));
+        let err = provider.complete("hi", Some("llama3"), None).await.unwrap_err();
+        assert!(!err.is_retryable());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn list_models_reads_installed_models_from_tags() {
+        let tags = Router::new().route(
+            "/api/tags",
+            get(|| async {
+                Json(serde_json::json!({
+                    "models": [
+                        { "name": "qwen3:8b", "size": 5_200_000_000u64 },
+                        { "name": "llama3.2:latest" }
+                    ]
+                }))
+            }),
+        );
+        let provider = OllamaProvider::with_base_url(serve(tags).await);
+        assert_eq!(provider.list_models().await.unwrap(), vec!["qwen3:8b", "llama3.2:latest"]);
+
+        let down = Router::new().route("/api/tags", get(|| async { (StatusCode::BAD_GATEWAY, "no daemon") }));
+        let provider = OllamaProvider::with_base_url(serve(down).await);
+        let err = provider.list_models().await.unwrap_err();
+        assert!(err.to_string().contains("502"), "{}", err);
+    }
 }
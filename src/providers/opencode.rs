@@ -6,7 +6,8 @@ use std::path::Path;
 use std::process::Stdio;
 use tokio::process::Command;
 
-use super::provider::{Provider, ProviderError, Result};
+use super::process::run_cli;
+use super::provider::{CompletionParams, Provider, ProviderError, Result};
 
 pub struct OpenCodeProvider {
     cli_path: String,
@@ -52,9 +53,7 @@ impl Provider for OpenCodeProvider {
     }
     
     async fn list_models(&self) -> Result<Vec<String>> {
-        Ok(vec![
-            "default".to_string(),
-        ])
+        Ok(super::known_model_ids("opencode"))
     }
     
     async fn complete(
@@ -62,6 +61,17 @@ impl Provider for OpenCodeProvider {
         prompt: &str,
         model: Option<&str>,
         working_dir: Option<&Path>,
+    ) -> Result<String> {
+        self.complete_with_params(prompt, model, working_dir, &CompletionParams::default())
+            .await
+    }
+    
+    async fn complete_with_params(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        working_dir: Option<&Path>,
+        params: &CompletionParams,
     ) -> Result<String> {
         let _model = model.unwrap_or(&self.default_model);
         
@@ -73,10 +83,7 @@ impl Provider for OpenCodeProvider {
             cmd.current_dir(dir);
         }
         
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-        
-        let output = cmd.output().await?;
+        let output = run_cli(&mut cmd, params.cancel.as_ref()).await?;
         
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
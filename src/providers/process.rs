@@ -0,0 +1,146 @@
+//! Cancellation-aware child process runner for CLI providers.
+
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
+use tokio::task::JoinHandle;
+
+use super::provider::{CancelToken, ProviderError, Result};
+
+/// How long to wait for pipes to drain after the child has been killed.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Output of a CLI run that completed (successfully or not).
+#[derive(Debug)]
+pub struct CliOutput {
+    pub status: ExitStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// A spawned CLI child with its output pipes being drained in the background.
+///
+/// The child is spawned with `kill_on_drop`, so dropping the wrapper (e.g. when
+/// an outer timeout fires) kills it and lets tokio reap it.
+pub struct CliChild {
+    child: Child,
+    stdout: JoinHandle<Vec<u8>>,
+    stderr: JoinHandle<Vec<u8>>,
+}
+
+impl CliChild {
+    /// Spawn `cmd` with piped stdout/stderr.
+    pub fn spawn(cmd: &mut Command) -> Result<Self> {
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+        cmd.kill_on_drop(true);
+        let mut child = cmd.spawn()?;
+        let stdout = tokio::spawn(read_all(child.stdout.take()));
+        let stderr = tokio::spawn(read_all(child.stderr.take()));
+        Ok(Self { child, stdout, stderr })
+    }
+
+    /// Wait for the child to exit, killing it if `cancel` fires first.
+    ///
+    /// On cancellation the child is killed and reaped before returning
+    /// `ProviderError::Cancelled` with whatever stdout was produced so far.
+    pub async fn wait(mut self, cancel: Option<&CancelToken>) -> Result<CliOutput> {
+        let status = match cancel {
+            Some(token) => tokio::select! {
+                status = self.child.wait() => Some(status?),
+                _ = token.cancelled() => None,
+            },
+            None => Some(self.child.wait().await?),
+        };
+
+        let Some(status) = status else {
+            // `kill` sends SIGKILL and waits, so no zombie is left behind.
+            let _ = self.child.kill().await;
+            let partial = drain(self.stdout).await;
+            self.stderr.abort();
+            return Err(ProviderError::Cancelled(
+                String::from_utf8_lossy(&partial).to_string(),
+            ));
+        };
+
+        Ok(CliOutput {
+            status,
+            stdout: self.stdout.await.unwrap_or_default(),
+            stderr: self.stderr.await.unwrap_or_default(),
+        })
+    }
+}
+
+/// Spawn `cmd` and wait for it, honoring an optional cancellation token.
+pub async fn run_cli(cmd: &mut Command, cancel: Option<&CancelToken>) -> Result<CliOutput> {
+    CliChild::spawn(cmd)?.wait(cancel).await
+}
+
+async fn read_all<R: AsyncRead + Unpin>(pipe: Option<R>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if let Some(mut pipe) = pipe {
+        // Keep whatever was read before an error (e.g. pipe closed on kill).
+        let mut chunk = [0u8; 8192];
+        loop {
+            match pipe.read(&mut chunk).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            }
+        }
+    }
+    buf
+}
+
+/// Collect drained output, giving up if a grandchild keeps the pipe open.
+async fn drain(handle: JoinHandle<Vec<u8>>) -> Vec<u8> {
+    let abort = handle.abort_handle();
+    match tokio::time::timeout(DRAIN_TIMEOUT, handle).await {
+        Ok(Ok(buf)) => buf,
+        _ => {
+            abort.abort();
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::provider::cancel_pair;
+
+    #[tokio::test]
+    async fn cancel_kills_child_and_returns_partial_output() {
+        let (handle, token) = cancel_pair();
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("echo partial; exec sleep 30");
+        let child = CliChild::spawn(&mut cmd).unwrap();
+        let pid = child.child.id().unwrap();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            handle.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        match child.wait(Some(&token)).await {
+            Err(ProviderError::Cancelled(partial)) => assert_eq!(partial.trim(), "partial"),
+            other => panic!("expected cancellation, got {:?}", other.map(|o| o.status)),
+        }
+        assert!(started.elapsed() < Duration::from_secs(10));
+        if cfg!(target_os = "linux") {
+            // Reaped: the pid no longer exists (not even as a zombie).
+            assert!(!std::path::Path::new(&format!("/proc/{}", pid)).exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn run_cli_collects_output_without_cancel() {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("echo out; echo err >&2");
+        let out = run_cli(&mut cmd, None).await.unwrap();
+        assert!(out.status.success());
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "out");
+        assert_eq!(String::from_utf8_lossy(&out.stderr).trim(), "err");
+    }
+}
@@ -2,8 +2,11 @@
 #![allow(dead_code)]
 
 use async_trait::async_trait;
+use std::future::Future;
 use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
+use tokio::sync::watch;
 
 #[derive(Error, Debug)]
 pub enum ProviderError {
@@ -16,6 +19,10 @@ pub enum ProviderError {
     #[error("API error: {0}")]
     ApiError(String),
     
+    /// Non-success HTTP status from an API provider.
+    #[error("API error: HTTP {status}: {body}")]
+    HttpStatus { status: reqwest::StatusCode, body: String },
+    
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     
@@ -28,12 +35,94 @@ pub enum ProviderError {
     #[error("Timeout")]
     Timeout,
     
+    /// Cancelled before completion; carries any partial output.
+    #[error("Cancelled")]
+    Cancelled(String),
+    
     #[error("{0}")]
     Other(String),
 }
 
 pub type Result<T> = std::result::Result<T, ProviderError>;
 
+/// Token usage reported by a provider for its last completion.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+/// Output format requested from the model.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ResponseFormat {
+    /// Free-form text (default).
+    #[default]
+    Text,
+    /// Constrain output to a single valid JSON object.
+    JsonObject,
+}
+
+/// Sending side of a cancellation signal.
+#[derive(Debug)]
+pub struct CancelHandle(watch::Sender<bool>);
+
+impl CancelHandle {
+    /// Signal cancellation to every token cloned from this handle.
+    pub fn cancel(&self) {
+        let _ = self.0.send(true);
+    }
+}
+
+/// Receiving side of a cancellation signal.
+#[derive(Debug, Clone)]
+pub struct CancelToken(watch::Receiver<bool>);
+
+impl CancelToken {
+    /// Whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Resolve once cancellation is requested. Never resolves if the handle
+    /// is dropped without cancelling.
+    pub async fn cancelled(&self) {
+        let mut rx = self.0.clone();
+        if rx.wait_for(|c| *c).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Create a linked cancellation handle and token.
+pub fn cancel_pair() -> (CancelHandle, CancelToken) {
+    let (tx, rx) = watch::channel(false);
+    (CancelHandle(tx), CancelToken(rx))
+}
+
+/// Optional per-request completion parameters.
+#[derive(Debug, Clone, Default)]
+pub struct CompletionParams {
+    pub response_format: ResponseFormat,
+    /// Cancellation signal; CLI providers kill their child process on cancel.
+    pub cancel: Option<CancelToken>,
+}
+
+impl CompletionParams {
+    /// Parameters requesting a JSON object response.
+    pub fn json() -> Self {
+        Self {
+            response_format: ResponseFormat::JsonObject,
+            ..Self::default()
+        }
+    }
+
+    /// Attach a cancellation token.
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+}
+
 /// AI Provider trait.
 #[async_trait]
 pub trait Provider: Send + Sync {
@@ -54,8 +143,26 @@ pub trait Provider: Send + Sync {
         working_dir: Option<&Path>,
     ) -> Result<String>;
     
+    /// Complete a prompt with extra parameters.
+    ///
+    /// Providers that cannot honor a parameter (e.g. CLI providers) ignore it.
+    async fn complete_with_params(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        working_dir: Option<&Path>,
+        _params: &CompletionParams,
+    ) -> Result<String> {
+        self.complete(prompt, model, working_dir).await
+    }
+    
     /// Get the default model.
     fn default_model(&self) -> Option<&str>;
+
+    /// Token usage of the last completion, if the provider reports it.
+    fn last_usage(&self) -> Option<Usage> {
+        None
+    }
 }
 
 
@@ -63,4 +170,92 @@ impl ProviderError {
     pub fn other(s: impl Into<String>) -> Self {
         ProviderError::Other(s.into())
     }
+
+    /// Whether retrying the same request may succeed: network failures,
+    /// timeouts, 429 and 5xx. Auth and other 4xx errors are fatal.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ProviderError::HttpStatus { status, .. } => is_retryable_status(*status),
+            ProviderError::HttpError(e) => {
+                e.is_connect()
+                    || e.is_timeout()
+                    || e.is_request()
+                    || e.status().is_some_and(is_retryable_status)
+            }
+            ProviderError::Timeout => true,
+            _ => false,
+        }
+    }
+}
+
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Retry schedule for transient HTTP provider failures (`models.max_retries`).
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn with_max_retries(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+
+    /// Exponential backoff with jitter: a random point in the upper half of
+    /// `base_delay * 2^attempt`, capped at `max_delay`.
+    fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(1u32 << attempt.min(16))
+            .min(self.max_delay);
+        let half = ceiling / 2;
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64)
+            .unwrap_or(0);
+        half + Duration::from_nanos(seed % (half.as_nanos() as u64 + 1))
+    }
+
+    /// Run `op`, retrying retryable errors up to `max_retries` times.
+    pub async fn run<T, F, Fut>(&self, mut op: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Err(e) if attempt < self.max_retries && e.is_retryable() => {
+                    let delay = self.delay(attempt);
+                    attempt += 1;
+                    tracing::warn!(
+                        "Transient provider error ({}); retry {}/{} in {:?}",
+                        e,
+                        attempt,
+                        self.max_retries,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
 }
@@ -2,17 +2,38 @@
 
 use anyhow::{anyhow, Result};
 use chrono::Utc;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use tokio::process::Command;
 
-use crate::config::{get_home_dir, get_settings_path, load_settings, BoardSchedule, Settings};
+use crate::config::{get_home_dir, load_settings, BoardSchedule, Settings};
 use crate::memory::Memory;
-use crate::providers::create_provider;
+use crate::providers::{create_provider, CompletionParams};
 
 const DEFAULT_CONSTITUTION: &str = include_str!("../../constitution/LAWS.md");
 
+/// Always-blocked shell patterns; `sovereign.shell_denylist` adds to these.
+const BUILTIN_SHELL_DENYLIST: &[&str] = &[
+    r"\brm\s+(-\S+\s+)*-[a-zA-Z]*[rRf][a-zA-Z]*\b.*(\s|=)[/~]",
+    r":\(\)\s*\{\s*:\s*\|\s*:\s*&\s*\}\s*;\s*:",
+    r"\bmkfs(\.\w+)?\b",
+    r"\bdd\s+.*\bif=",
+    r">\s*/dev/(sd|nvme|disk)",
+    r"\b(shutdown|reboot|halt|poweroff)\b",
+    r"\bsudo\b",
+    r"\bchmod\s+(-\S+\s+)*777\s+/",
+];
+
+const TOOL_INSTALL_PATTERNS: &[&str] = &[
+    r"\bbrew\s+install\b",
+    r"\bapt(-get)?\s+install\b",
+    r"\bcargo\s+install\b",
+    r"\bnpm\s+(i|install)\s+(.*\s)?-g\b",
+    r"\bpip3?\s+install\b",
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct SovereignPlan {
     thought: String,
@@ -46,6 +67,7 @@ enum SovereignAction {
         agent_id: Option<String>,
     },
     SkillCreate { name: String, content: String },
+    RevertFile { path: String },
     ReplicateAgent {
         new_agent_id: String,
         provider: Option<String>,
@@ -63,6 +85,31 @@ struct AuditEntry {
     detail: String,
 }
 
+/// A planned action held in `~/.tinyvegeta/sovereign/pending/` until an operator
+/// approves or denies it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingAction {
+    pub id: String,
+    pub created_at: String,
+    pub agent_id: String,
+    pub cycle: u64,
+    #[serde(default)]
+    pub approved: bool,
+    action: SovereignAction,
+}
+
+impl PendingAction {
+    /// Action type, e.g. `shell` or `write_file`.
+    pub fn action_name(&self) -> &'static str {
+        action_name(&self.action)
+    }
+
+    /// The planned action as JSON.
+    pub fn action_json(&self) -> String {
+        serde_json::to_string(&self.action).unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Default)]
 struct SelfModifyWindow {
     seen: VecDeque<i64>,
@@ -86,13 +133,93 @@ impl SelfModifyWindow {
     }
 }
 
+/// Shell allow/deny rules, compiled once per loop.
+#[derive(Debug)]
+struct ShellPolicy {
+    deny: Vec<Regex>,
+    allow: Vec<String>,
+    tool_install: Vec<Regex>,
+}
+
+impl ShellPolicy {
+    fn from_settings(settings: &Settings) -> Result<Self> {
+        let compile = |pattern: &str| {
+            Regex::new(pattern).map_err(|e| anyhow!("invalid shell pattern '{}': {}", pattern, e))
+        };
+        let deny = BUILTIN_SHELL_DENYLIST
+            .iter()
+            .copied()
+            .chain(settings.sovereign.shell_denylist.iter().map(String::as_str))
+            .map(compile)
+            .collect::<Result<Vec<_>>>()?;
+        let tool_install = TOOL_INSTALL_PATTERNS
+            .iter()
+            .map(|p| compile(p))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            deny,
+            allow: settings.sovereign.shell_allowlist.clone(),
+            tool_install,
+        })
+    }
+
+    /// Reject denylisted commands and, with an allowlist, any command segment
+    /// whose program is not on it.
+    fn check(&self, cmd: &str) -> Result<()> {
+        if let Some(re) = self.deny.iter().find(|re| re.is_match(cmd)) {
+            return Err(anyhow!("blocked shell command by sovereign guard (matched '{}')", re.as_str()));
+        }
+        if self.allow.is_empty() {
+            return Ok(());
+        }
+        if cmd.contains("$(") || cmd.contains('`') {
+            return Err(anyhow!("command substitution is not allowed with sovereign.shell_allowlist"));
+        }
+        for segment in cmd.split([';', '&', '|', '\n']) {
+            let Some(program) = segment.split_whitespace().next() else {
+                continue;
+            };
+            let name = Path::new(program).file_name().and_then(|n| n.to_str()).unwrap_or(program);
+            if !self.allow.iter().any(|a| a == program || a == name) {
+                return Err(anyhow!("'{}' is not in sovereign.shell_allowlist", program));
+            }
+        }
+        Ok(())
+    }
+
+    fn is_tool_install(&self, cmd: &str) -> bool {
+        self.tool_install.iter().any(|re| re.is_match(cmd))
+    }
+}
+
+/// Per-loop guard state shared by every action.
+#[derive(Debug)]
+struct Guards {
+    mod_window: SelfModifyWindow,
+    shell: ShellPolicy,
+    /// Identifies this run; its file backups live under `backups/<run_id>`.
+    run_id: String,
+}
+
+impl Guards {
+    fn new(settings: &Settings) -> Result<Self> {
+        Ok(Self {
+            mod_window: SelfModifyWindow::default(),
+            shell: ShellPolicy::from_settings(settings)?,
+            run_id: ulid::Ulid::new().to_string(),
+        })
+    }
+}
+
 pub async fn run(
     agent_id: Option<String>,
     goal: Option<String>,
     max_cycles: Option<u32>,
     dry_run: bool,
+    require_approval: bool,
 ) -> Result<()> {
     let mut settings = load_settings().map_err(|e| anyhow!(e.to_string()))?;
+    let require_approval = require_approval || settings.sovereign.require_approval;
     let resolved_agent = resolve_agent(&settings, agent_id)?;
     let agent_cfg = settings
         .agents
@@ -113,9 +240,29 @@ pub async fn run(
     let loop_sleep_default = settings.sovereign.loop_sleep_seconds.max(5);
     let max_actions = settings.sovereign.max_actions_per_cycle.max(1) as usize;
     let mut cycle: u64 = 0;
-    let mut mod_window = SelfModifyWindow::default();
+    let mut guards = Guards::new(&settings)?;
+    let mut quiet = false;
+    println!("Sovereign run {} (backups: sovereign/backups/{})", guards.run_id, guards.run_id);
 
     loop {
+        if settings.monitoring.in_quiet_hours(Utc::now()) {
+            if !quiet {
+                quiet = true;
+                tracing::info!("Sovereign cycles suppressed: quiet hours");
+                append_audit(AuditEntry {
+                    ts: Utc::now().to_rfc3339(),
+                    agent_id: resolved_agent.clone(),
+                    cycle,
+                    action: "quiet_hours".to_string(),
+                    status: "suppressed".to_string(),
+                    detail: "cycles deferred until quiet hours end".to_string(),
+                })?;
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(loop_sleep_default)).await;
+            continue;
+        }
+        quiet = false;
+
         cycle += 1;
         if let Some(max) = max_cycles {
             if cycle > max as u64 {
@@ -123,6 +270,16 @@ pub async fn run(
             }
         }
 
+        if require_approval {
+            for pending in list_pending()?
+                .into_iter()
+                .filter(|p| p.approved && p.agent_id == resolved_agent)
+            {
+                run_approved(&mut settings, &working_dir, cycle, pending, dry_run, &mut guards)
+                    .await?;
+            }
+        }
+
         let prompt = build_prompt(
             &constitution,
             &resolved_agent,
@@ -133,7 +290,12 @@ pub async fn run(
         );
         let provider = create_provider(&provider_name, &settings);
         let reply = provider
-            .complete(&prompt, model.as_deref(), Some(&working_dir))
+            .complete_with_params(
+                &prompt,
+                model.as_deref(),
+                Some(&working_dir),
+                &CompletionParams::json(),
+            )
             .await
             .map_err(|e| anyhow!("Provider error: {}", e))?;
         let plan = parse_plan(&reply).unwrap_or(SovereignPlan {
@@ -152,28 +314,21 @@ pub async fn run(
         })?;
 
         for action in plan.actions.into_iter().take(max_actions) {
-            let action_name = action_name(&action).to_string();
-            let execution = execute_action(
+            if require_approval {
+                let id = queue_for_approval(&resolved_agent, cycle, action)?;
+                println!("Action {} awaiting approval (tinyvegeta sovereign approve {})", id, id);
+                continue;
+            }
+            let (status, detail) = run_action(
                 &mut settings,
                 &resolved_agent,
                 &working_dir,
+                cycle,
                 action,
                 dry_run,
-                &mut mod_window,
+                &mut guards,
             )
-            .await;
-            let (status, detail) = match execution {
-                Ok(d) => ("ok".to_string(), d),
-                Err(e) => ("blocked".to_string(), e.to_string()),
-            };
-            append_audit(AuditEntry {
-                ts: Utc::now().to_rfc3339(),
-                agent_id: resolved_agent.clone(),
-                cycle,
-                action: action_name,
-                status: status.clone(),
-                detail: detail.clone(),
-            })?;
+            .await?;
             let key = format!("sovereign.cycle.{}.{}", cycle, Utc::now().timestamp_millis());
             let val = serde_json::json!({ "status": status, "detail": detail }).to_string();
             let _ = Memory::set(&key, &val, crate::memory::MemoryScope::Global, None);
@@ -186,6 +341,148 @@ pub async fn run(
     Ok(())
 }
 
+/// Execute one action and record the outcome in the audit log.
+async fn run_action(
+    settings: &mut Settings,
+    agent_id: &str,
+    working_dir: &Path,
+    cycle: u64,
+    action: SovereignAction,
+    dry_run: bool,
+    guards: &mut Guards,
+) -> Result<(String, String)> {
+    let action_name = action_name(&action).to_string();
+    let execution =
+        execute_action(settings, agent_id, working_dir, cycle, action, dry_run, guards).await;
+    let (status, detail) = match execution {
+        Ok(d) => ("ok".to_string(), d),
+        Err(e) => ("blocked".to_string(), e.to_string()),
+    };
+    append_audit(AuditEntry {
+        ts: Utc::now().to_rfc3339(),
+        agent_id: agent_id.to_string(),
+        cycle,
+        action: action_name,
+        status: status.clone(),
+        detail: detail.clone(),
+    })?;
+    Ok((status, detail))
+}
+
+fn pending_dir() -> Result<PathBuf> {
+    Ok(get_home_dir()?.join("sovereign").join("pending"))
+}
+
+fn save_pending(pending: &PendingAction) -> Result<()> {
+    let dir = pending_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+        dir.join(format!("{}.json", pending.id)),
+        serde_json::to_string_pretty(pending)?,
+    )?;
+    Ok(())
+}
+
+fn load_pending(action_id: &str) -> Result<PendingAction> {
+    let path = pending_dir()?.join(format!("{}.json", action_id));
+    if !path.exists() {
+        return Err(anyhow!("pending action '{}' not found", action_id));
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+fn remove_pending(action_id: &str) -> Result<()> {
+    std::fs::remove_file(pending_dir()?.join(format!("{}.json", action_id)))?;
+    Ok(())
+}
+
+fn audit_pending(pending: &PendingAction, status: &str, detail: String) -> Result<()> {
+    append_audit(AuditEntry {
+        ts: Utc::now().to_rfc3339(),
+        agent_id: pending.agent_id.clone(),
+        cycle: pending.cycle,
+        action: pending.action_name().to_string(),
+        status: status.to_string(),
+        detail,
+    })
+}
+
+/// List pending actions, oldest first. Unreadable files are skipped.
+pub fn list_pending() -> Result<Vec<PendingAction>> {
+    let dir = pending_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut pending: Vec<PendingAction> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|x| x.to_str()) == Some("json"))
+        .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+        .filter_map(|c| serde_json::from_str(&c).ok())
+        .collect();
+    pending.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(pending)
+}
+
+fn queue_for_approval(agent_id: &str, cycle: u64, action: SovereignAction) -> Result<String> {
+    let pending = PendingAction {
+        id: ulid::Ulid::new().to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        agent_id: agent_id.to_string(),
+        cycle,
+        approved: false,
+        action,
+    };
+    save_pending(&pending)?;
+    audit_pending(&pending, "pending", format!("awaiting approval: {}", pending.id))?;
+    Ok(pending.id)
+}
+
+/// Approve a pending action; the running loop executes it on its next cycle.
+pub fn approve(action_id: &str) -> Result<PendingAction> {
+    let mut pending = load_pending(action_id)?;
+    if !pending.approved {
+        pending.approved = true;
+        save_pending(&pending)?;
+        audit_pending(&pending, "approved", format!("approved: {}", pending.id))?;
+    }
+    Ok(pending)
+}
+
+/// Discard a pending action without executing it.
+pub fn deny(action_id: &str) -> Result<PendingAction> {
+    let pending = load_pending(action_id)?;
+    remove_pending(action_id)?;
+    audit_pending(&pending, "denied", format!("denied: {}", pending.id))?;
+    Ok(pending)
+}
+
+/// Execute an approved action. The pending file is removed first so a crash
+/// mid-execution never replays it.
+async fn run_approved(
+    settings: &mut Settings,
+    working_dir: &Path,
+    cycle: u64,
+    pending: PendingAction,
+    dry_run: bool,
+    guards: &mut Guards,
+) -> Result<()> {
+    remove_pending(&pending.id)?;
+    let execution = execute_action(
+        settings,
+        &pending.agent_id,
+        working_dir,
+        cycle,
+        pending.action.clone(),
+        dry_run,
+        guards,
+    )
+    .await;
+    match execution {
+        Ok(detail) => audit_pending(&pending, "executed", format!("{}: {}", pending.id, detail)),
+        Err(e) => audit_pending(&pending, "blocked", format!("{}: {}", pending.id, e)),
+    }
+}
+
 fn resolve_agent(settings: &Settings, agent_id: Option<String>) -> Result<String> {
     if let Some(agent_id) = agent_id {
         return Ok(agent_id);
@@ -228,7 +525,7 @@ fn build_prompt(
          Goal:\n{}\n\n\
          Return JSON only with this schema:\n\
          {{\"thought\":\"...\",\"actions\":[...],\"sleep_seconds\":20}}\n\
-         Allowed action types: shell, write_file, memory_set, schedule_set, skill_create, replicate_agent.\n\
+         Allowed action types: shell, write_file, memory_set, schedule_set, skill_create, replicate_agent, revert_file.\n\
          Hard limits: max {} actions. Do not request harmful, deceptive, or unauthorized actions.",
         constitution,
         agent_id,
@@ -262,14 +559,18 @@ async fn execute_action(
     settings: &mut Settings,
     agent_id: &str,
     working_dir: &Path,
+    cycle: u64,
     action: SovereignAction,
     dry_run: bool,
-    mod_window: &mut SelfModifyWindow,
+    guards: &mut Guards,
 ) -> Result<String> {
     match action {
         SovereignAction::Shell { cmd, reason: _ } => {
-            guard_shell(&cmd)?;
-            if !settings.sovereign.allow_tool_install && looks_like_tool_install(&cmd) {
+            guards.shell.check(&cmd)?;
+            if settings.sovereign.confine_to_workspace {
+                confine_shell_cds(working_dir, &cmd)?;
+            }
+            if !settings.sovereign.allow_tool_install && guards.shell.is_tool_install(&cmd) {
                 return Err(anyhow!("tool install blocked by policy"));
             }
             if dry_run {
@@ -293,15 +594,19 @@ async fn execute_action(
         } => {
             let target = normalize_path(working_dir, &path)?;
             guard_file_write(settings, &target)?;
+            if settings.sovereign.confine_to_workspace {
+                confine_to(working_dir, &target)?;
+            }
             if !settings.sovereign.allow_self_modify {
                 return Err(anyhow!("self-modifying file writes are disabled by policy"));
             }
-            if !mod_window.allow(settings.sovereign.max_self_modifications_per_hour as usize) {
+            if !guards.mod_window.allow(settings.sovereign.max_self_modifications_per_hour as usize) {
                 return Err(anyhow!("self-modification rate limit reached"));
             }
             if dry_run {
                 return Ok(format!("dry-run write: {}", target.display()));
             }
+            backup_before_write(&guards.run_id, cycle, &target)?;
             if let Some(parent) = target.parent() {
                 std::fs::create_dir_all(parent)?;
             }
@@ -348,6 +653,7 @@ async fn execute_action(
                 agent_id: target_agent.or_else(|| Some(agent_id.to_string())),
                 sender_id: None,
                 enabled: true,
+                cron: None,
             };
             schedules.push(schedule);
             settings.board.schedules = Some(schedules);
@@ -363,11 +669,35 @@ async fn execute_action(
             std::fs::write(skill_dir.join("SKILL.md"), content)?;
             Ok(format!("skill created: {}", name))
         }
+        SovereignAction::RevertFile { path } => {
+            let target = normalize_path(working_dir, &path)?;
+            guard_file_write(settings, &target)?;
+            if settings.sovereign.confine_to_workspace {
+                confine_to(working_dir, &target)?;
+            }
+            let backup_cycle = latest_backup_cycle(&guards.run_id, &target)?
+                .ok_or_else(|| anyhow!("no backup recorded for {} in this run", target.display()))?;
+            if dry_run {
+                return Ok(format!("dry-run revert: {} (cycle {})", target.display(), backup_cycle));
+            }
+            restore_backup(settings, &guards.run_id, backup_cycle, &target)
+        }
         SovereignAction::ReplicateAgent {
             new_agent_id,
             provider,
             model,
         } => {
+            if !settings.sovereign.allow_replication {
+                return Err(anyhow!("replicate_agent disabled by policy (sovereign.allow_replication=false)"));
+            }
+            let sovereign_agents = settings.agents.values().filter(|a| a.is_sovereign).count();
+            if sovereign_agents >= settings.sovereign.max_agents as usize {
+                return Err(anyhow!(
+                    "replicate_agent blocked: {} sovereign agents already exist (sovereign.max_agents={})",
+                    sovereign_agents,
+                    settings.sovereign.max_agents
+                ));
+            }
             if dry_run {
                 return Ok(format!("dry-run replicate agent: {}", new_agent_id));
             }
@@ -391,6 +721,8 @@ async fn execute_action(
                     model,
                     working_directory: Some(agent_dir),
                     is_sovereign: true,
+                    sender_context: false,
+                    enabled: true,
                 },
             );
             save_settings(settings)?;
@@ -402,7 +734,200 @@ async fn execute_action(
 fn normalize_path(base: &Path, requested: &str) -> Result<PathBuf> {
     let p = PathBuf::from(requested);
     let full = if p.is_absolute() { p } else { base.join(p) };
-    Ok(full.canonicalize().unwrap_or(full))
+    if let Ok(canonical) = full.canonicalize() {
+        return Ok(canonical);
+    }
+    // Not created yet: canonicalize the parent so the path matches once it exists.
+    match (full.parent().and_then(|p| p.canonicalize().ok()), full.file_name()) {
+        (Some(parent), Some(name)) => Ok(parent.join(name)),
+        _ => Ok(full),
+    }
+}
+
+/// Real location of `path`: the deepest existing ancestor is canonicalized
+/// (resolving symlinks) and the rest appended. `..` in the part that does not
+/// exist yet cannot be resolved and is rejected.
+fn real_path(path: &Path) -> Result<PathBuf> {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            let mut real = canonical;
+            for part in rest.iter().rev() {
+                real.push(part);
+            }
+            return Ok(real);
+        }
+        match (existing.parent(), existing.components().next_back()) {
+            (Some(parent), Some(Component::Normal(name))) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return Err(anyhow!("cannot resolve path '{}'", path.display())),
+        }
+    }
+}
+
+/// Reject `path` unless its real location is inside `root`
+/// (`sovereign.confine_to_workspace`).
+fn confine_to(root: &Path, path: &Path) -> Result<()> {
+    let root = real_path(root)?;
+    if real_path(path)?.starts_with(&root) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "'{}' is outside the workspace {} (sovereign.confine_to_workspace)",
+            path.display(),
+            root.display()
+        ))
+    }
+}
+
+/// Follow the `cd`/`pushd` segments of `cmd` from `root` and reject the
+/// command if any of them leaves it. Targets that only the shell can expand
+/// (`~`, `$VAR`, `-`, a bare `cd`) are rejected too, as are subshells,
+/// command substitution, `eval` and `sh -c`, whose `cd`s cannot be followed.
+fn confine_shell_cds(root: &Path, cmd: &str) -> Result<()> {
+    let unfollowable = |what: &str| {
+        Err(anyhow!("cannot confine {} to the workspace (sovereign.confine_to_workspace)", what))
+    };
+    if cmd.contains(['(', '`']) {
+        return unfollowable("subshells or command substitution");
+    }
+    let mut cwd = root.to_path_buf();
+    for segment in cmd.split([';', '&', '|', '\n']) {
+        let mut words = segment
+            .split_whitespace()
+            .skip_while(|w| matches!(*w, "then" | "do" | "else" | "{" | "!" | "time" | "exec" | "command" | "builtin"));
+        let first = words.next().unwrap_or_default();
+        let program = first.rsplit('/').next().unwrap_or(first);
+        if program == "eval" {
+            return unfollowable("eval");
+        }
+        if matches!(program, "sh" | "bash" | "zsh" | "dash" | "ksh" | "fish")
+            && segment.split_whitespace().any(|w| w.starts_with('-') && !w.starts_with("--") && w.contains('c'))
+        {
+            return unfollowable(&format!("'{} -c'", program));
+        }
+        if !matches!(first, "cd" | "pushd") {
+            continue;
+        }
+        let target = words.next().unwrap_or("~").trim_matches(['"', '\'']);
+        if target.starts_with('~') || target.contains('$') || target == "-" {
+            return Err(anyhow!("cannot confine 'cd {}' to the workspace (sovereign.confine_to_workspace)", target));
+        }
+        cwd = cwd.join(target);
+        confine_to(root, &cwd)?;
+    }
+    Ok(())
+}
+
+fn backups_dir() -> Result<PathBuf> {
+    Ok(get_home_dir()?.join("sovereign").join("backups"))
+}
+
+/// Backup location of `target`, mirroring its absolute path.
+fn backup_rel(target: &Path) -> PathBuf {
+    target
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Backups written by `cycle` of run `run_id`. Cycle numbers restart with
+/// every run, so they are only unique within one.
+fn cycle_backup_dir(run_id: &str, cycle: u64) -> Result<PathBuf> {
+    Ok(backups_dir()?.join(run_id).join(cycle.to_string()))
+}
+
+/// Record the bytes of `target` (or that it did not exist) before `cycle`
+/// writes it. Only the first write of a file in a cycle is recorded.
+fn backup_before_write(run_id: &str, cycle: u64, target: &Path) -> Result<()> {
+    let dir = cycle_backup_dir(run_id, cycle)?;
+    let rel = backup_rel(target);
+    let saved = dir.join("files").join(&rel);
+    let absent = dir.join("absent").join(&rel);
+    if saved.exists() || absent.exists() {
+        return Ok(());
+    }
+    let (dest, bytes) = if target.exists() {
+        (saved, std::fs::read(target)?)
+    } else {
+        (absent, Vec::new())
+    };
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(dest, bytes)?;
+    Ok(())
+}
+
+/// Most recent cycle of run `run_id` holding a backup of `target`.
+fn latest_backup_cycle(run_id: &str, target: &Path) -> Result<Option<u64>> {
+    let dir = backups_dir()?.join(run_id);
+    if !dir.exists() {
+        return Ok(None);
+    }
+    let rel = backup_rel(target);
+    Ok(std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| Some((e.file_name().to_str()?.parse::<u64>().ok()?, e.path())))
+        .filter(|(_, p)| p.join("files").join(&rel).exists() || p.join("absent").join(&rel).exists())
+        .map(|(cycle, _)| cycle)
+        .max())
+}
+
+/// Restore `target` to its exact state before `cycle` of run `run_id`
+/// first wrote it.
+fn restore_backup(settings: &Settings, run_id: &str, cycle: u64, target: &Path) -> Result<String> {
+    guard_file_write(settings, target)?;
+    let dir = cycle_backup_dir(run_id, cycle)?;
+    let rel = backup_rel(target);
+    let saved = dir.join("files").join(&rel);
+    if saved.exists() {
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(target, std::fs::read(&saved)?)?;
+        return Ok(format!("restored {} to its state before cycle {}", target.display(), cycle));
+    }
+    if dir.join("absent").join(&rel).exists() {
+        if target.exists() {
+            std::fs::remove_file(target)?;
+        }
+        return Ok(format!("removed {} (did not exist before cycle {})", target.display(), cycle));
+    }
+    Err(anyhow!("no backup of {} for cycle {}", target.display(), cycle))
+}
+
+/// Revert a file written by the sovereign loop in `cycle` of run `run_id`,
+/// or of the most recent run. Relative paths resolve against the current
+/// directory.
+pub fn revert(run_id: Option<&str>, cycle: u64, path: &str) -> Result<String> {
+    let settings = load_settings().map_err(|e| anyhow!(e.to_string()))?;
+    let target = normalize_path(&std::env::current_dir()?, path)?;
+    let run_id = match run_id {
+        Some(id) => id.to_string(),
+        None => latest_run()?.ok_or_else(|| anyhow!("no sovereign backups recorded"))?,
+    };
+    restore_backup(&settings, &run_id, cycle, &target)
+}
+
+/// Id of the most recent run with backups; run ids are ULIDs, so they sort
+/// by start time.
+fn latest_run() -> Result<Option<String>> {
+    let dir = backups_dir()?;
+    if !dir.exists() {
+        return Ok(None);
+    }
+    Ok(std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .max())
 }
 
 fn parse_scope(scope: &str) -> Result<crate::memory::MemoryScope> {
@@ -415,20 +940,6 @@ fn parse_scope(scope: &str) -> Result<crate::memory::MemoryScope> {
     }
 }
 
-fn guard_shell(cmd: &str) -> Result<()> {
-    let blocked = ["rm -rf /", ":(){:|:&};:", "mkfs", "dd if=", "shutdown", "reboot"];
-    if blocked.iter().any(|x| cmd.contains(x)) {
-        return Err(anyhow!("blocked shell command by sovereign guard"));
-    }
-    Ok(())
-}
-
-fn looks_like_tool_install(cmd: &str) -> bool {
-    ["brew install", "apt install", "cargo install", "npm i -g", "pip install"]
-        .iter()
-        .any(|x| cmd.contains(x))
-}
-
 fn guard_file_write(settings: &Settings, path: &Path) -> Result<()> {
     let protected: Vec<PathBuf> = settings
         .sovereign
@@ -446,8 +957,7 @@ fn guard_file_write(settings: &Settings, path: &Path) -> Result<()> {
 }
 
 fn save_settings(settings: &Settings) -> Result<()> {
-    let path = get_settings_path()?;
-    std::fs::write(path, serde_json::to_string_pretty(settings)?)?;
+    crate::config::save_settings(settings)?;
     Ok(())
 }
 
@@ -458,6 +968,7 @@ fn action_name(action: &SovereignAction) -> &'static str {
         SovereignAction::MemorySet { .. } => "memory_set",
         SovereignAction::ScheduleSet { .. } => "schedule_set",
         SovereignAction::SkillCreate { .. } => "skill_create",
+        SovereignAction::RevertFile { .. } => "revert_file",
         SovereignAction::ReplicateAgent { .. } => "replicate_agent",
     }
 }
@@ -474,3 +985,281 @@ fn append_audit(entry: AuditEntry) -> Result<()> {
     writeln!(file, "{}", line)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_support::TempHome;
+
+    fn replicate(id: &str) -> SovereignAction {
+        SovereignAction::ReplicateAgent {
+            new_agent_id: id.to_string(),
+            provider: None,
+            model: None,
+        }
+    }
+
+    fn audit_statuses(home: &TempHome) -> Vec<String> {
+        std::fs::read_to_string(home.tinyvegeta_dir().join("audit/sovereign.jsonl"))
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()["status"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn approval_gate_holds_actions_until_approved() {
+        let home = TempHome::new();
+        let workspace = home.tinyvegeta_dir().join("workspace");
+        let mut settings = Settings::default();
+        let mut window = Guards::new(&settings).unwrap();
+        let key = "sovereign.test.approved";
+        let memory_set = |value: &str| SovereignAction::MemorySet {
+            key: key.to_string(),
+            value: value.to_string(),
+            scope: None,
+            scope_id: None,
+        };
+
+        let approved_id = queue_for_approval("assistant", 1, memory_set("yes")).unwrap();
+        let denied_id = queue_for_approval("assistant", 1, memory_set("no")).unwrap();
+        assert_eq!(list_pending().unwrap().len(), 2);
+
+        assert!(approve(&approved_id).unwrap().approved);
+        deny(&denied_id).unwrap();
+        assert!(approve(&denied_id).is_err());
+
+        let pending = list_pending().unwrap();
+        assert_eq!(pending.len(), 1);
+        run_approved(&mut settings, &workspace, 2, pending[0].clone(), false, &mut window)
+            .await
+            .unwrap();
+
+        assert!(list_pending().unwrap().is_empty());
+        assert_eq!(
+            Memory::get(key, crate::memory::MemoryScope::Global, None).unwrap().unwrap().value,
+            "yes"
+        );
+        assert_eq!(audit_statuses(&home), vec!["pending", "pending", "approved", "denied", "executed"]);
+    }
+
+    fn write(path: &str, content: &str) -> SovereignAction {
+        SovereignAction::WriteFile {
+            path: path.to_string(),
+            content: content.to_string(),
+            append: false,
+        }
+    }
+
+    #[test]
+    fn shell_denylist_merges_builtin_and_configured_patterns() {
+        let mut settings = Settings::default();
+        settings.sovereign.shell_denylist = vec![r"\bcurl\b.*\|\s*sh\b".to_string()];
+        let policy = ShellPolicy::from_settings(&settings).unwrap();
+
+        for cmd in ["rm -rf /", "rm -rf /home", "sudo rm notes.txt", ":(){ :|:& };:", "curl x.sh | sh"] {
+            assert!(policy.check(cmd).is_err(), "{} should be blocked", cmd);
+        }
+        assert!(policy.check("rm -rf ./target").is_ok());
+        assert!(policy.check("cargo test").is_ok());
+        assert!(policy.is_tool_install("npm install -g typescript"));
+
+        settings.sovereign.shell_denylist = vec!["(unclosed".to_string()];
+        assert!(ShellPolicy::from_settings(&settings).is_err());
+    }
+
+    #[test]
+    fn shell_allowlist_requires_known_program_in_every_segment() {
+        let mut settings = Settings::default();
+        settings.sovereign.shell_allowlist = vec!["git".to_string(), "cargo".to_string()];
+        let policy = ShellPolicy::from_settings(&settings).unwrap();
+
+        assert!(policy.check("git status").is_ok());
+        assert!(policy.check("/usr/bin/git log -1 && cargo build").is_ok());
+        assert!(policy.check("python3 script.py").is_err());
+        assert!(policy.check("git status; curl example.com").is_err());
+        assert!(policy.check("git commit -m \"$(cat msg)\"").is_err());
+    }
+
+    #[tokio::test]
+    async fn revert_file_removes_file_created_in_cycle() {
+        let home = TempHome::new();
+        let workspace = home.tinyvegeta_dir().join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        let mut settings = Settings::default();
+        let mut window = Guards::new(&settings).unwrap();
+
+        execute_action(&mut settings, "assistant", &workspace, 3, write("notes/new.md", "hi"), false, &mut window)
+            .await
+            .unwrap();
+        let target = workspace.join("notes/new.md");
+        assert!(target.exists());
+
+        let revert = SovereignAction::RevertFile { path: "notes/new.md".to_string() };
+        execute_action(&mut settings, "assistant", &workspace, 4, revert, false, &mut window)
+            .await
+            .unwrap();
+        assert!(!target.exists());
+    }
+
+    #[tokio::test]
+    async fn revert_restores_exact_prior_bytes_and_respects_protected_files() {
+        let home = TempHome::new();
+        let workspace = home.tinyvegeta_dir().join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        let target = workspace.join("SOUL.md");
+        let original = b"original \xff\x00 bytes\n".to_vec();
+        std::fs::write(&target, &original).unwrap();
+        let mut settings = Settings::default();
+        let mut window = Guards::new(&settings).unwrap();
+
+        execute_action(&mut settings, "assistant", &workspace, 5, write("SOUL.md", "first"), false, &mut window)
+            .await
+            .unwrap();
+        execute_action(&mut settings, "assistant", &workspace, 5, write("SOUL.md", "second"), false, &mut window)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "second");
+
+        settings.sovereign.protected_files = vec!["SOUL.md".to_string()];
+        assert!(restore_backup(&settings, &window.run_id, 5, &target).is_err());
+        settings.sovereign.protected_files.clear();
+
+        restore_backup(&settings, &window.run_id, 5, &target).unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), original);
+        assert!(restore_backup(&settings, &window.run_id, 6, &target).is_err());
+    }
+
+    #[tokio::test]
+    async fn backups_are_kept_per_run() {
+        let home = TempHome::new();
+        let workspace = home.tinyvegeta_dir().join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        let target = workspace.join("notes.md");
+        std::fs::write(&target, "v0").unwrap();
+        let mut settings = Settings::default();
+
+        let mut first = Guards::new(&settings).unwrap();
+        execute_action(&mut settings, "assistant", &workspace, 1, write("notes.md", "v1"), false, &mut first)
+            .await
+            .unwrap();
+
+        // A later run reuses cycle 1 without touching the first run's backups,
+        // and only reverts what it wrote itself.
+        let mut second = Guards::new(&settings).unwrap();
+        execute_action(&mut settings, "assistant", &workspace, 1, write("other.md", "x"), false, &mut second)
+            .await
+            .unwrap();
+        let revert = SovereignAction::RevertFile { path: "notes.md".to_string() };
+        let err = execute_action(&mut settings, "assistant", &workspace, 2, revert, false, &mut second)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no backup recorded"), "{}", err);
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "v1");
+
+        restore_backup(&settings, &first.run_id, 1, &target).unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "v0");
+        assert_eq!(latest_run().unwrap(), Some(second.run_id.clone()));
+    }
+
+    #[tokio::test]
+    async fn replicate_agent_is_capped_and_audited() {
+        let home = TempHome::new();
+        let workspace = home.tinyvegeta_dir().join("workspace");
+        let mut settings = Settings::default();
+        settings.workspace.path = Some(workspace.clone());
+        settings.sovereign.max_agents = 1;
+        let mut window = Guards::new(&settings).unwrap();
+
+        // Opt-in is required.
+        let err = execute_action(&mut settings, "assistant", &workspace, 1, replicate("clone-1"), false, &mut window)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("allow_replication"));
+
+        settings.sovereign.allow_replication = true;
+        execute_action(&mut settings, "assistant", &workspace, 1, replicate("clone-1"), false, &mut window)
+            .await
+            .unwrap();
+        assert!(settings.agents["clone-1"].is_sovereign);
+
+        let err = execute_action(&mut settings, "assistant", &workspace, 1, replicate("clone-2"), false, &mut window)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("sovereign.max_agents=1"));
+
+        let (status, _) = run_action(&mut settings, "assistant", &workspace, 7, replicate("clone-2"), false, &mut window)
+            .await
+            .unwrap();
+        assert_eq!(status, "blocked");
+        assert!(!settings.agents.contains_key("clone-2"));
+
+        let audit = std::fs::read_to_string(home.tinyvegeta_dir().join("audit/sovereign.jsonl")).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(audit.lines().last().unwrap()).unwrap();
+        assert_eq!(entry["action"], "replicate_agent");
+        assert_eq!(entry["status"], "blocked");
+        assert_eq!(entry["cycle"], 7);
+        assert!(entry["detail"].as_str().unwrap().contains("max_agents"));
+    }
+
+    #[tokio::test]
+    async fn writes_and_cds_outside_the_workspace_are_blocked() {
+        let home = TempHome::new();
+        let workspace = home.tinyvegeta_dir().join("workspace");
+        let outside = home.tinyvegeta_dir().join("outside");
+        std::fs::create_dir_all(workspace.join("notes")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        let mut settings = Settings::default();
+        let mut window = Guards::new(&settings).unwrap();
+
+        let escape = outside.join("abs.md").display().to_string();
+        for path in ["../escape.md", escape.as_str()] {
+            let (status, detail) = run_action(&mut settings, "assistant", &workspace, 1, write(path, "x"), false, &mut window)
+                .await
+                .unwrap();
+            assert_eq!(status, "blocked", "{}", path);
+            assert!(detail.contains("confine_to_workspace"), "{}", detail);
+        }
+        assert!(!home.tinyvegeta_dir().join("escape.md").exists());
+        assert!(!outside.join("abs.md").exists());
+
+        // A symlink inside the workspace does not make its target writable.
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&outside, workspace.join("link")).unwrap();
+            assert!(execute_action(&mut settings, "assistant", &workspace, 1, write("link/x.md", "x"), false, &mut window)
+                .await
+                .is_err());
+            assert!(confine_shell_cds(&workspace, "cd link && ls").is_err());
+        }
+
+        execute_action(&mut settings, "assistant", &workspace, 1, write("notes/ok.md", "fine"), false, &mut window)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(workspace.join("notes/ok.md")).unwrap(), "fine");
+
+        assert!(confine_shell_cds(&workspace, "cd notes && cd .. && ls").is_ok());
+        assert!(confine_shell_cds(&workspace, "cd notes; cd ../..").is_err());
+        assert!(confine_shell_cds(&workspace, "cd /tmp").is_err());
+        assert!(confine_shell_cds(&workspace, "cd ~").is_err());
+        assert!(confine_shell_cds(&workspace, "cd new/../..").is_err());
+        for cmd in [
+            "(cd /tmp && rm x)",
+            "echo $(cd /tmp; pwd)",
+            "echo `cd /tmp`",
+            "sh -c 'cd /tmp'",
+            "/bin/bash -lc 'cd /tmp'",
+            "eval 'cd /tmp'",
+            "if true; then cd /tmp; fi",
+        ] {
+            assert!(confine_shell_cds(&workspace, cmd).is_err(), "{}", cmd);
+        }
+        assert!(confine_shell_cds(&workspace, "bash scripts/build.sh --check").is_ok());
+
+        settings.sovereign.confine_to_workspace = false;
+        execute_action(&mut settings, "assistant", &workspace, 1, write("../escape.md", "x"), false, &mut window)
+            .await
+            .unwrap();
+        assert!(home.tinyvegeta_dir().join("escape.md").exists());
+    }
+}
@@ -1,10 +1,11 @@
 //! Deterministic task routing with typed schema.
 
 use regex::Regex;
+use serde::Serialize;
 
-use crate::config::Settings;
+use crate::config::{RoutingRule, Settings};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RoutedTask {
     pub intent: String,
     pub owner: String,
@@ -28,7 +29,13 @@ impl TaskRouter {
         }
 
         let intent = infer_intent(message);
-        let owner = select_owner(intent, settings);
+        let (owner, reason) = match matching_rule(message, settings) {
+            Some(rule) => (rule.agent.clone(), format!("routing rule /{}/", rule.pattern)),
+            None => (
+                select_owner(intent, settings),
+                format!("hard-rule routing by intent '{}'", intent),
+            ),
+        };
         let priority = infer_priority(message);
         let deadline = extract_deadline(message);
         RoutedTask {
@@ -36,7 +43,7 @@ impl TaskRouter {
             owner,
             priority: priority.to_string(),
             deadline,
-            reason: format!("hard-rule routing by intent '{}'", intent),
+            reason,
         }
     }
 }
@@ -138,6 +145,14 @@ fn extract_deadline(message: &str) -> Option<String> {
         .and_then(|cap| cap.get(1).map(|m| m.as_str().to_lowercase()))
 }
 
+/// Highest-priority `routing.rules` entry matching `message` whose agent exists
+/// and is enabled. Invalid patterns are skipped; `config validate` reports them.
+pub fn matching_rule<'a>(message: &str, settings: &'a Settings) -> Option<&'a RoutingRule> {
+    settings.routing.rules_by_priority().into_iter().find(|rule| {
+        settings.is_agent_enabled(&rule.agent) && rule.regex().is_some_and(|re| re.is_match(message))
+    })
+}
+
 fn select_owner(intent: &str, settings: &Settings) -> String {
     let candidates: &[&str] = match intent {
         "security" => &["security", "assistant"],
@@ -150,7 +165,7 @@ fn select_owner(intent: &str, settings: &Settings) -> String {
     };
 
     for candidate in candidates {
-        if settings.agents.contains_key(*candidate) {
+        if settings.is_agent_enabled(candidate) {
             return (*candidate).to_string();
         }
     }
@@ -163,3 +178,31 @@ fn select_owner(intent: &str, settings: &Settings) -> String {
 fn has_any(message: &str, terms: &[&str]) -> bool {
     terms.iter().any(|term| message.contains(term))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TaskRouter;
+    use crate::config::{AgentConfig, RoutingRule, Settings};
+
+    #[test]
+    fn user_rules_take_precedence_over_hard_rules_by_priority() {
+        let mut settings = Settings::default();
+        for id in ["assistant", "operations", "coder"] {
+            settings.agents.insert(id.to_string(), AgentConfig::default());
+        }
+        let message = "deploy the new build to staging";
+        assert_eq!(TaskRouter::route(message, &settings, None).owner, "operations");
+
+        let rule = |pattern: &str, agent: &str, priority: i32| RoutingRule {
+            pattern: pattern.to_string(),
+            agent: agent.to_string(),
+            priority,
+        };
+        settings.routing.rules = vec![rule("staging", "assistant", 0), rule("deploy|infra", "coder", 5)];
+        let routed = TaskRouter::route(message, &settings, None);
+        assert_eq!((routed.owner.as_str(), routed.reason.as_str()), ("coder", "routing rule /deploy|infra/"));
+
+        // Explicit targets still win.
+        assert_eq!(TaskRouter::route(message, &settings, Some("operations")).owner, "operations");
+    }
+}
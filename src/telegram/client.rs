@@ -4,14 +4,16 @@ use std::collections::HashMap;
 use std::sync::OnceLock;
 
 use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, UpdateKind};
 use teloxide::RequestError;
 use tokio::process::Command as TokioCommand;
 use tokio::sync::Mutex;
 
-use crate::config::load_settings;
+use crate::config::{load_settings, Settings, TelegramMode};
+use crate::core::text::split_for_telegram;
 use crate::error::Error;
 
-use super::pairing::PairingManager;
+use super::pairing::{PairingManager, PairingOutcome};
 
 #[derive(Clone)]
 struct SoulTarget {
@@ -108,7 +110,7 @@ pub async fn run_telegram_daemon() -> Result<(), Error> {
     
     let settings = load_settings()?;
     
-    let token = settings.channels.telegram.bot_token
+    let token = settings.channels.telegram.bot_token.clone()
         .ok_or_else(|| Error::Telegram("No bot token configured".to_string()))?;
     
     let bot = Bot::new(token);
@@ -124,7 +126,7 @@ pub async fn run_telegram_daemon() -> Result<(), Error> {
         teloxide::types::BotCommand::new("upgrade", "Reinstall and restart TinyVegeta"),
         teloxide::types::BotCommand::new("doctor", "Run remote health checks"),
         teloxide::types::BotCommand::new("provider", "Show or set provider"),
-        teloxide::types::BotCommand::new("models", "Alias for provider switch"),
+        teloxide::types::BotCommand::new("models", "List models or switch provider"),
         teloxide::types::BotCommand::new("memory", "Quick memory ops"),
         teloxide::types::BotCommand::new("brain", "BRAIN.md quick ops"),
         teloxide::types::BotCommand::new("logs", "Tail filtered logs"),
@@ -134,20 +136,44 @@ pub async fn run_telegram_daemon() -> Result<(), Error> {
         teloxide::types::BotCommand::new("soul", "Edit/show SOUL.md"),
         teloxide::types::BotCommand::new("reset", "Reset conversation"),
         teloxide::types::BotCommand::new("triage", "Toggle auto-triage"),
+        teloxide::types::BotCommand::new("ctx", "Send a message with one-off context"),
+        teloxide::types::BotCommand::new("task", "Create, list, show or start tasks"),
     ]).await {
         tracing::warn!("Failed to set commands: {}", e);
     }
     
     tracing::info!("Telegram bot commands set");
+
+    if settings.channels.telegram.mode == TelegramMode::Webhook {
+        return super::webhook::run_webhook(bot, &settings.channels.telegram).await;
+    }
     
-    // Use dispatch with a simple handler
-    teloxide::repl(bot, |bot, msg| async move {
-        handle_message(bot, msg).await
-    }).await;
+    // Messages and inline keyboard callbacks share one dispatcher.
+    let handler = dptree::entry()
+        .branch(Update::filter_message().endpoint(handle_message))
+        .branch(Update::filter_callback_query().endpoint(handle_callback_query));
+    Dispatcher::builder(bot, handler)
+        .enable_ctrlc_handler()
+        .build()
+        .dispatch()
+        .await;
     
     Ok(())
 }
 
+/// Handle one update received by the webhook, as the polling dispatcher
+/// would.
+pub(super) async fn dispatch_update(bot: Bot, update: Update) {
+    let result = match update.kind {
+        UpdateKind::Message(msg) => handle_message(bot, msg).await,
+        UpdateKind::CallbackQuery(q) => handle_callback_query(bot, q).await,
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        tracing::error!("Failed to handle Telegram update: {}", e);
+    }
+}
+
 /// Handle incoming messages.
 async fn handle_message(bot: Bot, msg: Message) -> Result<(), RequestError> {
     // Check if it's a command
@@ -226,8 +252,10 @@ async fn handle_message(bot: Bot, msg: Message) -> Result<(), RequestError> {
                     if !ensure_approved_sender(&bot, &msg).await? {
                         return Ok(());
                     }
-                    let provider = parts.next();
-                    cmd_provider(bot, chat_id, provider).await?;
+                    match parts.next() {
+                        Some("list") => cmd_models_list(bot, chat_id, parts.next()).await?,
+                        provider => cmd_provider(bot, chat_id, provider).await?,
+                    }
                 }
                 "/memory" => {
                     if !ensure_approved_sender(&bot, &msg).await? {
@@ -299,6 +327,31 @@ async fn handle_message(bot: Bot, msg: Message) -> Result<(), RequestError> {
                         cmd_reset_agents(bot, chat_id, &agents).await?;
                     }
                 }
+                "/task" => {
+                    if !ensure_approved_sender(&bot, &msg).await? {
+                        return Ok(());
+                    }
+                    let args = parts.collect::<Vec<_>>();
+                    cmd_task(bot, chat_id, &args).await?;
+                }
+                "/ctx" => {
+                    if !ensure_approved_sender(&bot, &msg).await? {
+                        return Ok(());
+                    }
+                    match parse_ctx_command(text.trim_start().strip_prefix(cmd).unwrap_or("")) {
+                        Some((context, message)) => {
+                            let sender = msg.from.as_ref().map(|u| u.full_name()).unwrap_or_else(|| "Unknown".to_string());
+                            let sender_id = msg.from.as_ref().map(|u| u.id.0.to_string()).unwrap_or_else(|| "0".to_string());
+                            if rate_limited(&bot, &msg, &sender_id).await? {
+                                return Ok(());
+                            }
+                            enqueue_chat_message(&bot, &msg, &sender, &sender_id, message, Vec::new(), Some(context)).await?;
+                        }
+                        None => {
+                            bot.send_message(chat_id, "Usage: /ctx <context> | <message>").await?;
+                        }
+                    }
+                }
                 "/triage" => {
                     if !ensure_approved_sender(&bot, &msg).await? {
                         return Ok(());
@@ -318,6 +371,69 @@ async fn handle_message(bot: Bot, msg: Message) -> Result<(), RequestError> {
     handle_regular_message(bot, msg).await
 }
 
+/// Handle inline keyboard callbacks; only approved senders may use them.
+async fn handle_callback_query(bot: Bot, q: CallbackQuery) -> Result<(), RequestError> {
+    let sender_id = q.from.id.0.to_string();
+    let data = q.data.clone().unwrap_or_default();
+    let result = if PairingManager::is_approved(&sender_id) {
+        apply_provider_callback(&data)
+    } else {
+        Err("Pair first to use these buttons.".to_string())
+    };
+
+    match result {
+        Ok(text) => {
+            bot.answer_callback_query(q.id.clone()).await?;
+            if let Some(message) = q.regular_message() {
+                bot.edit_message_text(message.chat.id, message.id, text).await?;
+            }
+        }
+        Err(e) => {
+            bot.answer_callback_query(q.id.clone()).text(e).show_alert(true).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Our own binary, for commands that re-invoke it; when it could not be
+/// resolved, tells the chat why and returns `None`.
+async fn self_exe_or_report(bot: &Bot, chat_id: ChatId) -> Result<Option<&'static std::path::Path>, RequestError> {
+    match crate::core::exe::self_exe() {
+        Ok(exe) => Ok(Some(exe)),
+        Err(e) => {
+            bot.send_message(chat_id, e.to_string()).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Send `text` as one message, or as numbered parts when it is too long.
+async fn send_chunked(bot: &Bot, chat_id: ChatId, text: &str) -> Result<(), RequestError> {
+    for part in split_for_telegram(text) {
+        bot.send_message(chat_id, part).await?;
+    }
+    Ok(())
+}
+
+/// Reply with the wait time and return `true` when `sender_id` is over the
+/// per-minute message limit.
+async fn rate_limited(bot: &Bot, msg: &Message, sender_id: &str) -> Result<bool, RequestError> {
+    let settings = load_settings().unwrap_or_default();
+    let telegram = &settings.channels.telegram;
+    if telegram.rate_limit_exempt_ids.iter().any(|id| id == sender_id) {
+        return Ok(false);
+    }
+    match super::rate_limit::check_sender(sender_id, telegram.rate_limit_per_minute) {
+        Ok(()) => Ok(false),
+        Err(wait) => {
+            tracing::warn!("Rate limit reached for sender {}", sender_id);
+            bot.send_message(msg.chat.id, format!("Rate limit reached, try again in {}s", wait.as_secs().max(1)))
+                .await?;
+            Ok(true)
+        }
+    }
+}
+
 async fn ensure_approved_sender(bot: &Bot, msg: &Message) -> Result<bool, RequestError> {
     let sender = msg.from
         .as_ref()
@@ -336,7 +452,8 @@ async fn ensure_approved_sender(bot: &Bot, msg: &Message) -> Result<bool, Reques
         bot.send_message(msg.chat.id, "Your request is pending approval.").await?;
     } else {
         match PairingManager::add_pending(&sender_id, &sender) {
-            Ok(code) => {
+            Ok(PairingOutcome::Approved(_)) => return Ok(true),
+            Ok(PairingOutcome::Pending(code)) => {
                 bot.send_message(
                     msg.chat.id,
                     format!("Pair first. Your code is: {}\nApprove with:\ntinyvegeta pairing approve {}", code, code),
@@ -363,26 +480,33 @@ async fn handle_regular_message(bot: Bot, msg: Message) -> Result<(), RequestErr
         .map(|u| u.id.0.to_string())
         .unwrap_or_else(|| "0".to_string());
     
-    // Check pairing approval
+    // Check pairing approval; allowlisted senders are approved on the spot.
     if !PairingManager::is_approved(&sender_id) {
         if PairingManager::is_pending(&sender_id) {
             bot.send_message(
                 msg.chat.id,
                 "Your request is pending approval."
             ).await?;
-        } else {
-            match PairingManager::add_pending(&sender_id, &sender) {
-                Ok(code) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("Welcome! Your pairing code is: {}\n\nApprove with:\ntinyvegeta pairing approve {}", code, code)
-                    ).await?;
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to add pending sender: {}", e);
-                }
+            return Ok(());
+        }
+        match PairingManager::add_pending(&sender_id, &sender) {
+            Ok(PairingOutcome::Approved(_)) => {}
+            Ok(PairingOutcome::Pending(code)) => {
+                bot.send_message(
+                    msg.chat.id,
+                    format!("Welcome! Your pairing code is: {}\n\nApprove with:\ntinyvegeta pairing approve {}", code, code)
+                ).await?;
+                return Ok(());
+            }
+            Err(e) => {
+                tracing::warn!("Failed to add pending sender: {}", e);
+                return Ok(());
             }
         }
+    }
+
+    // Over-limit senders are turned away before any downloads or triage.
+    if rate_limited(&bot, &msg, &sender_id).await? {
         return Ok(());
     }
     
@@ -489,15 +613,33 @@ async fn handle_regular_message(bot: Bot, msg: Message) -> Result<(), RequestErr
         }
     }
     
+    enqueue_chat_message(&bot, &msg, &sender, &sender_id, text, downloaded_files, None).await
+}
+
+/// Route and enqueue a chat message from an approved sender, with optional
+/// one-off `context` for this message only.
+async fn enqueue_chat_message(
+    bot: &Bot,
+    msg: &Message,
+    sender: &str,
+    sender_id: &str,
+    text: String,
+    downloaded_files: Vec<String>,
+    context: Option<String>,
+) -> Result<(), RequestError> {
+    // Restarts and webhook redeliveries can hand us the same message again.
+    if !first_delivery(msg.chat.id.0, msg.id.0) {
+        tracing::info!("Skipping duplicate Telegram message {} in chat {}", msg.id.0, msg.chat.id.0);
+        return Ok(());
+    }
+
     // Parse routing
     let mut routed_text = text.to_string();
     if !text.trim_start().starts_with('@') && triage_enabled() {
-        if let Some(agent) = triage_agent_candidate(&text) {
-            if let Ok(settings) = load_settings() {
-                if settings.agents.contains_key(&agent) {
-                    routed_text = format!("@{} {}", agent, text);
-                    let _ = bot.send_message(msg.chat.id, format!("Auto-routed to @{}.", agent)).await;
-                }
+        if let Ok(settings) = load_settings() {
+            if let Some(agent) = triage_agent_candidate(&text, &settings) {
+                routed_text = format!("@{} {}", agent, text);
+                let _ = bot.send_message(msg.chat.id, format!("Auto-routed to @{}.", agent)).await;
             }
         }
     }
@@ -514,13 +656,29 @@ async fn handle_regular_message(bot: Bot, msg: Message) -> Result<(), RequestErr
         };
     }
     let (target_agent, message) = parse_message_routing(&routed_text);
+
+    let settings = load_settings().unwrap_or_default();
+    // File references count toward the limit too.
+    if crate::core::Queue::check_message_size(&message, settings.queue.max_message_bytes).is_err() {
+        tracing::warn!("Rejected {} byte message from {}", message.len(), sender_id);
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "Message too large ({} KB, limit {} KB). Send long content as a file attachment instead.",
+                message.len().div_ceil(1024),
+                settings.queue.max_message_bytes / 1024
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
     
     // Create message data
     use crate::core::MessageData;
     let mut message_data = MessageData::new(
         "telegram",
-        &sender,
-        &sender_id,
+        sender,
+        sender_id,
         &message,
     );
     
@@ -528,17 +686,21 @@ async fn handle_regular_message(bot: Bot, msg: Message) -> Result<(), RequestErr
     message_data.response_channel = Some("telegram".to_string());
     message_data.response_chat_id = Some(msg.chat.id.0);
     if !downloaded_files.is_empty() {
-        message_data.files = Some(downloaded_files.clone());
+        message_data.files = Some(downloaded_files);
     }
+    message_data.context = context;
     
     if let Some(ref agent) = target_agent {
         message_data.agent = Some(agent.clone());
     }
     
     // Enqueue message
-    match crate::core::Queue::enqueue(message_data) {
-        Ok(id) => {
+    match crate::core::Queue::enqueue_validated(message_data, &settings) {
+        Ok((id, warning)) => {
             tracing::info!("Enqueued message {} from {} to agent {:?}", id, sender, target_agent);
+            if let Some(warning) = warning {
+                let _ = bot.send_message(msg.chat.id, format!("⚠️ {}.", warning)).await;
+            }
             let short_id = id.chars().take(8).collect::<String>();
             let route = target_agent.unwrap_or_else(|| "default".to_string());
             let _ = bot
@@ -549,6 +711,10 @@ async fn handle_regular_message(bot: Bot, msg: Message) -> Result<(), RequestErr
                 .await;
             let _ = bot.send_chat_action(msg.chat.id, teloxide::types::ChatAction::Typing).await;
         }
+        Err(Error::NotFound(reason)) => {
+            bot.send_message(msg.chat.id, format!("❌ {}. Use /agent or /team to list targets.", reason))
+                .await?;
+        }
         Err(e) => {
             tracing::error!("Failed to enqueue message: {}", e);
             bot.send_message(msg.chat.id, "Failed to process message.").await?;
@@ -558,6 +724,29 @@ async fn handle_regular_message(bot: Bot, msg: Message) -> Result<(), RequestErr
     Ok(())
 }
 
+/// How long a chat message id is remembered to detect redeliveries.
+const DEDUP_WINDOW_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Whether message `message_id` of `chat_id` was not seen in the last
+/// [`DEDUP_WINDOW_MS`]. Fails open when the store cannot be used.
+fn first_delivery(chat_id: i64, message_id: i32) -> bool {
+    let key = format!("telegram:{}:{}", chat_id, message_id);
+    crate::memory::sqlite::mark_message_seen(&key, DEDUP_WINDOW_MS).unwrap_or_else(|e| {
+        tracing::warn!("Failed to check for duplicate message {}: {}", key, e);
+        true
+    })
+}
+
+/// Split `/ctx` arguments into `(context, message)` at the first `|`.
+fn parse_ctx_command(args: &str) -> Option<(String, String)> {
+    let (context, message) = args.split_once('|')?;
+    let (context, message) = (context.trim(), message.trim());
+    if context.is_empty() || message.is_empty() {
+        return None;
+    }
+    Some((context.to_string(), message.to_string()))
+}
+
 /// Parse message for routing (e.g., @agent_id message).
 fn parse_message_routing(text: &str) -> (Option<String>, String) {
     if text.starts_with('@') {
@@ -587,7 +776,8 @@ async fn cmd_agents(bot: Bot, chat_id: ChatId) -> Result<(), RequestError> {
     for (id, agent) in &settings.agents {
         let name = agent.name.as_deref().unwrap_or(id);
         let provider = agent.provider.as_deref().unwrap_or("unknown");
-        response.push_str(&format!("• @{} - {} ({})\n", id, name, provider));
+        let disabled = if agent.enabled { "" } else { " [disabled]" };
+        response.push_str(&format!("• @{} - {} ({}){}\n", id, name, provider, disabled));
     }
     
     bot.send_message(chat_id, response).await?;
@@ -650,7 +840,8 @@ async fn cmd_board(bot: Bot, chat_id: ChatId) -> Result<(), RequestError> {
 
 /// Handle /status command.
 async fn cmd_status(bot: Bot, chat_id: ChatId) -> Result<(), RequestError> {
-    match crate::tmux::get_status() {
+    let backend = load_settings().map(|s| s.daemon.backend).unwrap_or_default();
+    match crate::daemon::get_status(backend) {
         Ok(status) => {
             bot.send_message(chat_id, status).await?;
         }
@@ -662,9 +853,9 @@ async fn cmd_status(bot: Bot, chat_id: ChatId) -> Result<(), RequestError> {
 }
 
 async fn cmd_doctor(bot: Bot, chat_id: ChatId) -> Result<(), RequestError> {
-    let exe = std::env::current_exe()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|_| "tinyvegeta".to_string());
+    let Some(exe) = self_exe_or_report(&bot, chat_id).await? else {
+        return Ok(());
+    };
 
     let out = TokioCommand::new(exe)
         .arg("doctor")
@@ -696,12 +887,7 @@ async fn cmd_doctor(bot: Bot, chat_id: ChatId) -> Result<(), RequestError> {
                 lines = text.lines().rev().take(25).map(|s| s.to_string()).collect();
                 lines.reverse();
             }
-            let mut response = format!("Doctor summary:\n{}", lines.join("\n"));
-            if response.len() > 3900 {
-                response.truncate(3900);
-                response.push_str("\n...[truncated]");
-            }
-            bot.send_message(chat_id, response).await?;
+            send_chunked(&bot, chat_id, &format!("Doctor summary:\n{}", lines.join("\n"))).await?;
         }
         Err(e) => {
             bot.send_message(chat_id, format!("Doctor failed: {}", e)).await?;
@@ -712,9 +898,9 @@ async fn cmd_doctor(bot: Bot, chat_id: ChatId) -> Result<(), RequestError> {
 
 async fn cmd_provider(bot: Bot, chat_id: ChatId, provider: Option<&str>) -> Result<(), RequestError> {
     if let Some(p) = provider {
-        let exe = std::env::current_exe()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| "tinyvegeta".to_string());
+        let Some(exe) = self_exe_or_report(&bot, chat_id).await? else {
+            return Ok(());
+        };
         match TokioCommand::new(exe).args(["provider", p]).output().await {
             Ok(output) => {
                 let text = String::from_utf8_lossy(&output.stdout).to_string();
@@ -743,6 +929,7 @@ async fn cmd_provider(bot: Bot, chat_id: ChatId, provider: Option<&str>) -> Resu
                     chat_id,
                     format!("Current provider: {}\nDefault agent: @{}\nAgent model: {}", provider_name, default_agent, model),
                 )
+                .reply_markup(provider_keyboard(provider_name))
                 .await?;
             }
             Err(e) => {
@@ -753,6 +940,171 @@ async fn cmd_provider(bot: Bot, chat_id: ChatId, provider: Option<&str>) -> Resu
     Ok(())
 }
 
+async fn cmd_models_list(bot: Bot, chat_id: ChatId, provider: Option<&str>) -> Result<(), RequestError> {
+    let settings = match load_settings() {
+        Ok(s) => s,
+        Err(e) => {
+            bot.send_message(chat_id, format!("Could not load settings: {}", e)).await?;
+            return Ok(());
+        }
+    };
+    let provider = provider.unwrap_or(&settings.models.provider);
+    if !crate::providers::PROVIDER_NAMES.contains(&provider) {
+        bot.send_message(chat_id, format!("Unknown provider: {}", provider)).await?;
+        return Ok(());
+    }
+    let reply = match crate::providers::list_models(provider, &settings).await {
+        Ok(models) if models.is_empty() => format!("No models found for {}", provider),
+        Ok(models) => format!("Models for {}:\n{}", provider, models.join("\n")),
+        Err(e) => format!("Could not list models for {}: {}", provider, e),
+    };
+    send_chunked(&bot, chat_id, &reply).await
+}
+
+/// Callback data prefix for the `/provider` inline keyboard.
+const PROVIDER_CALLBACK_PREFIX: &str = "provider:";
+
+/// One button per provider, two per row, with the current one ticked.
+fn provider_keyboard(current: &str) -> InlineKeyboardMarkup {
+    let buttons: Vec<InlineKeyboardButton> = crate::providers::PROVIDER_NAMES
+        .iter()
+        .map(|name| {
+            let label = if *name == current { format!("✓ {}", name) } else { name.to_string() };
+            InlineKeyboardButton::callback(label, format!("{}{}", PROVIDER_CALLBACK_PREFIX, name))
+        })
+        .collect();
+    InlineKeyboardMarkup::new(buttons.chunks(2).map(|row| row.to_vec()))
+}
+
+/// Switch to the provider named by a `provider:<name>` callback and save the
+/// settings. Returns the confirmation text for the edited message.
+fn apply_provider_callback(data: &str) -> Result<String, String> {
+    let name = data
+        .strip_prefix(PROVIDER_CALLBACK_PREFIX)
+        .ok_or_else(|| format!("Unknown action: {}", data))?;
+    if !crate::providers::PROVIDER_NAMES.contains(&name) {
+        return Err(format!("Unknown provider: {}", name));
+    }
+    let mut settings = load_settings().map_err(|e| e.to_string())?;
+    crate::providers::switch_provider(&mut settings, name, None);
+    crate::config::save_settings(&settings).map_err(|e| e.to_string())?;
+    Ok(if crate::providers::is_cli_provider(name) {
+        format!("✅ Switched to provider: {} (model: default)", name)
+    } else {
+        format!("✅ Switched to provider: {}", name)
+    })
+}
+
+const TASK_USAGE: &str = "Usage:\n/task create <title>\n/task list\n/task show <id>\n/task start <id>";
+
+async fn cmd_task(bot: Bot, chat_id: ChatId, args: &[&str]) -> Result<(), RequestError> {
+    use crate::core::task_store::{begin_task, finish_task};
+
+    if args.first() != Some(&"start") {
+        return send_chunked(&bot, chat_id, &task_reply(args)).await;
+    }
+    let Some(id) = args.get(1) else {
+        bot.send_message(chat_id, TASK_USAGE).await?;
+        return Ok(());
+    };
+    let settings = match load_settings() {
+        Ok(s) => s,
+        Err(e) => {
+            bot.send_message(chat_id, format!("Could not load settings: {}", e)).await?;
+            return Ok(());
+        }
+    };
+    let (record, task) = match begin_task(id, &settings) {
+        Ok(started) => started,
+        Err(e) => {
+            bot.send_message(chat_id, format!("Cannot start task: {}", e)).await?;
+            return Ok(());
+        }
+    };
+    let agent = task.agent_id.clone().unwrap_or_default();
+    bot.send_message(chat_id, format!("▶️ Task {} ({}) started on @{}.", record.id, record.title, agent))
+        .await?;
+
+    tokio::spawn(async move {
+        let result = crate::heartbeat::tasks::TaskSpawner::spawn_task(&task, &settings).await;
+        let text = match finish_task(&record.id, &result) {
+            Ok(None) => return,
+            Ok(Some(done)) => match (&result, done.output) {
+                (Ok(_), Some(out)) => format!("✅ Task {} ({}) completed.\n\n{}", done.id, done.title, out),
+                _ => format!("❌ Task {} ({}) failed: {}", done.id, done.title, done.error.unwrap_or_default()),
+            },
+            Err(e) => format!("Task {} finished but could not be saved: {}", record.id, e),
+        };
+        if let Err(e) = send_chunked(&bot, chat_id, &text).await {
+            tracing::error!("Failed to report task {}: {}", record.id, e);
+        }
+    });
+    Ok(())
+}
+
+/// Reply for `/task create|list|show`.
+fn task_reply(args: &[&str]) -> String {
+    use crate::core::task_store::{load_task_store, save_task_store, TaskRecord};
+
+    match args {
+        ["create", title @ ..] if !title.is_empty() => {
+            let record = TaskRecord::new(&title.join(" "), None, None, None, Vec::new());
+            let saved = load_task_store().and_then(|mut store| {
+                store.tasks.push(record.clone());
+                save_task_store(&store)
+            });
+            match saved {
+                Ok(()) => format!("Created task: {} ({})", record.id, record.title),
+                Err(e) => format!("Failed to create task: {}", e),
+            }
+        }
+        ["list"] => match load_task_store() {
+            Ok(store) if store.tasks.is_empty() => "No tasks.".to_string(),
+            Ok(store) => {
+                let mut out = String::from("Tasks:");
+                for t in &store.tasks {
+                    out.push_str(&format!(
+                        "\n- {} | {} | {} | @{}",
+                        t.id,
+                        t.title,
+                        store.display_status(t),
+                        t.agent_id.as_deref().unwrap_or("unassigned")
+                    ));
+                }
+                out
+            }
+            Err(e) => format!("Failed to load tasks: {}", e),
+        },
+        ["show", id] => match load_task_store() {
+            Ok(store) => match store.get(id) {
+                Some(t) => {
+                    let mut out = format!(
+                        "Task: {}\nTitle: {}\nAgent: {}\nPriority: {}\nStatus: {}",
+                        t.id,
+                        t.title,
+                        t.agent_id.as_deref().unwrap_or("unassigned"),
+                        t.priority,
+                        t.status
+                    );
+                    if let Some(desc) = &t.description {
+                        out.push_str(&format!("\nDescription: {}", desc));
+                    }
+                    if let Some(output) = &t.output {
+                        out.push_str(&format!("\nOutput:\n{}", output));
+                    }
+                    if let Some(err) = &t.error {
+                        out.push_str(&format!("\nError: {}", err));
+                    }
+                    out
+                }
+                None => format!("Task not found: {}", id),
+            },
+            Err(e) => format!("Failed to load tasks: {}", e),
+        },
+        _ => TASK_USAGE.to_string(),
+    }
+}
+
 async fn cmd_memory(bot: Bot, chat_id: ChatId, sub: Option<&str>, args: &[&str]) -> Result<(), RequestError> {
     match sub.unwrap_or("") {
         "stats" => match crate::memory::Memory::stats() {
@@ -783,11 +1135,7 @@ async fn cmd_memory(bot: Bot, chat_id: ChatId, sub: Option<&str>, args: &[&str])
                                 entry.value.chars().take(140).collect::<String>()
                             ));
                         }
-                        if out.len() > 3900 {
-                            out.truncate(3900);
-                            out.push_str("\n...[truncated]");
-                        }
-                        bot.send_message(chat_id, out).await?;
+                        send_chunked(&bot, chat_id, &out).await?;
                     }
                 }
                 Err(e) => {
@@ -828,12 +1176,7 @@ async fn cmd_brain(bot: Bot, chat_id: ChatId, sub: Option<&str>, args: &[&str])
                 return Ok(());
             }
             let content = std::fs::read_to_string(&path).unwrap_or_default();
-            let preview = if content.len() > 3500 {
-                format!("{}...\n[truncated]", &content[..3500])
-            } else {
-                content
-            };
-            bot.send_message(chat_id, format!("BRAIN.md ({})\n\n{}", path.display(), preview)).await?;
+            send_chunked(&bot, chat_id, &format!("BRAIN.md ({})\n\n{}", path.display(), content)).await?;
         }
         "status" => {
             let last_check = crate::memory::Memory::get("brain.last_check", crate::memory::MemoryScope::Global, None)
@@ -898,24 +1241,20 @@ async fn cmd_board_discuss(bot: Bot, chat_id: ChatId, topic: &str) -> Result<(),
         .team_id
         .clone()
         .unwrap_or_else(|| "board".to_string());
-    match crate::board::run_board_discussion(&settings, &team_id, topic, None).await {
+    match crate::board::run_board_discussion(&settings, &team_id, topic, None, false, false).await {
         Ok(output) => {
             let decision = output
                 .split("CEO (")
                 .nth(1)
                 .map(|s| format!("CEO ({s}"))
                 .unwrap_or_else(|| output.clone());
-            let mut response = format!(
+            let response = format!(
                 "Board Discussion\nTeam: @{}\nTopic: {}\n\nDecision:\n{}",
                 team_id,
                 topic,
                 decision.trim()
             );
-            if response.len() > 3900 {
-                response.truncate(3900);
-                response.push_str("\n...[truncated]");
-            }
-            bot.send_message(chat_id, response).await?;
+            send_chunked(&bot, chat_id, &response).await?;
         }
         Err(e) => {
             bot.send_message(chat_id, format!("Board discussion failed: {}", e)).await?;
@@ -926,21 +1265,13 @@ async fn cmd_board_discuss(bot: Bot, chat_id: ChatId, topic: &str) -> Result<(),
 
 async fn cmd_logs(bot: Bot, chat_id: ChatId, log_type: &str, lines: usize) -> Result<(), RequestError> {
     let limit = lines.clamp(10, 400);
-    let log_dir = match directories::ProjectDirs::from("com", "tinyvegeta", "tinyvegeta") {
-        Some(p) => p.data_dir().join("logs"),
-        None => {
+    let log_dir = match crate::logging::get_log_dir() {
+        Ok(dir) => dir,
+        Err(_) => {
             bot.send_message(chat_id, "Could not resolve log directory.").await?;
             return Ok(());
         }
     };
-    let path = log_dir.join("tinyvegeta.log");
-    let content = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(e) => {
-            bot.send_message(chat_id, format!("Failed to read logs: {}", e)).await?;
-            return Ok(());
-        }
-    };
 
     let needle = match log_type {
         "telegram" => Some("telegram"),
@@ -953,28 +1284,35 @@ async fn cmd_logs(bot: Bot, chat_id: ChatId, log_type: &str, lines: usize) -> Re
         }
     };
 
-    let mut filtered: Vec<&str> = content.lines().collect();
-    if let Some(n) = needle {
-        filtered.retain(|line| line.to_lowercase().contains(n));
-    }
-    let start = filtered.len().saturating_sub(limit);
-    let tail = filtered[start..].join("\n");
+    let filtered = crate::logging::tail_log_lines(&log_dir, limit, |content, n| {
+        let mut lines: Vec<String> = content
+            .lines()
+            .filter(|line| needle.is_none_or(|k| line.to_lowercase().contains(k)))
+            .map(String::from)
+            .collect();
+        lines.drain(..lines.len().saturating_sub(n));
+        lines
+    });
+    let tail = match filtered {
+        Ok(lines) => lines.join("\n"),
+        Err(e) => {
+            bot.send_message(chat_id, format!("Failed to read logs: {}", e)).await?;
+            return Ok(());
+        }
+    };
 
-    let mut response = format!("Logs ({}, last {}):\n{}", log_type, limit, tail);
-    if response.len() > 3900 {
-        response = format!("Logs ({}, last {}):\n{}", log_type, limit, &response.chars().rev().take(3600).collect::<String>().chars().rev().collect::<String>());
-    }
-    if response.trim().is_empty() {
-        response = format!("No {} logs found.", log_type);
+    if tail.trim().is_empty() {
+        bot.send_message(chat_id, format!("No {} logs found.", log_type)).await?;
+    } else {
+        send_chunked(&bot, chat_id, &format!("Logs ({}, last {}):\n{}", log_type, limit, tail)).await?;
     }
-    bot.send_message(chat_id, response).await?;
     Ok(())
 }
 
 async fn cmd_releasecheck(bot: Bot, chat_id: ChatId) -> Result<(), RequestError> {
-    let exe = std::env::current_exe()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|_| "tinyvegeta".to_string());
+    let Some(exe) = self_exe_or_report(&bot, chat_id).await? else {
+        return Ok(());
+    };
     match TokioCommand::new(exe).arg("releasecheck").output().await {
         Ok(out) => {
             let text = format!(
@@ -982,14 +1320,11 @@ async fn cmd_releasecheck(bot: Bot, chat_id: ChatId) -> Result<(), RequestError>
                 String::from_utf8_lossy(&out.stdout),
                 String::from_utf8_lossy(&out.stderr)
             );
-            let reply = if text.trim().is_empty() {
-                "releasecheck finished with no output".to_string()
-            } else if text.len() > 3900 {
-                format!("{}...\n[truncated]", &text[..3900])
+            if text.trim().is_empty() {
+                bot.send_message(chat_id, "releasecheck finished with no output").await?;
             } else {
-                text
-            };
-            bot.send_message(chat_id, reply).await?;
+                send_chunked(&bot, chat_id, &text).await?;
+            }
         }
         Err(e) => {
             bot.send_message(chat_id, format!("releasecheck failed: {}", e)).await?;
@@ -1132,9 +1467,9 @@ async fn cmd_sovereign(bot: Bot, chat_id: ChatId, args: &[&str]) -> Result<(), R
                 goal_parts.join(" ")
             };
 
-            let exe = std::env::current_exe()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_else(|_| "tinyvegeta".to_string());
+            let Some(exe) = self_exe_or_report(&bot, chat_id).await? else {
+                return Ok(());
+            };
 
             let mut cmd = std::process::Command::new(exe);
             cmd.arg("sovereign")
@@ -1239,7 +1574,12 @@ fn set_triage_enabled(enabled: bool) {
     let _ = Memory::set("triage.enabled", if enabled { "true" } else { "false" }, MemoryScope::Global, None);
 }
 
-fn triage_agent_candidate(message: &str) -> Option<String> {
+/// Agent to auto-route `message` to: the first matching `routing.rules`
+/// entry, else a keyword guess that names a configured agent.
+fn triage_agent_candidate(message: &str, settings: &Settings) -> Option<String> {
+    if let Some(rule) = crate::task::matching_rule(message, settings) {
+        return Some(rule.agent.clone());
+    }
     let m = message.to_lowercase();
     let picks = [
         ("security", &["vulnerability", "security", "auth", "xss", "csrf", "token"][..]),
@@ -1249,12 +1589,11 @@ fn triage_agent_candidate(message: &str) -> Option<String> {
         ("sales", &["lead", "pipeline", "deal", "prospect", "pricing"][..]),
         ("coder", &["bug", "code", "refactor", "test", "build", "rust", "api"][..]),
     ];
-    for (agent, terms) in picks {
-        if terms.iter().any(|t| m.contains(t)) {
-            return Some(agent.to_string());
-        }
-    }
-    None
+    picks
+        .into_iter()
+        .find(|(_, terms)| terms.iter().any(|t| m.contains(t)))
+        .map(|(agent, _)| agent.to_string())
+        .filter(|agent| settings.agents.contains_key(agent))
 }
 
 async fn cmd_triage(bot: Bot, chat_id: ChatId, arg: &str) -> Result<(), RequestError> {
@@ -1345,12 +1684,7 @@ async fn cmd_soul(bot: Bot, msg: &Message, args: &[&str]) -> Result<(), RequestE
             return Ok(());
         }
         let content = std::fs::read_to_string(&target.soul_path).unwrap_or_default();
-        let preview = if content.len() > 3500 {
-            format!("{}...\n[truncated]", &content[..3500])
-        } else {
-            content
-        };
-        bot.send_message(msg.chat.id, format!("SOUL.md for @{}:\n\n{}", target.agent_id, preview)).await?;
+        send_chunked(&bot, msg.chat.id, &format!("SOUL.md for @{}:\n\n{}", target.agent_id, content)).await?;
         return Ok(());
     }
 
@@ -1396,27 +1730,29 @@ async fn cmd_restart(bot: Bot, msg: Message) -> Result<(), RequestError> {
     if !PairingManager::is_approved(&sender_id) {
         if PairingManager::is_pending(&sender_id) {
             bot.send_message(msg.chat.id, "Your request is pending approval.").await?;
-        } else {
-            match PairingManager::add_pending(&sender_id, &sender) {
-                Ok(code) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("Pair first. Your code: {}\nApprove with:\ntinyvegeta pairing approve {}", code, code),
-                    ).await?;
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to add pending sender for /restart: {}", e);
-                }
+            return Ok(());
+        }
+        match PairingManager::add_pending(&sender_id, &sender) {
+            Ok(PairingOutcome::Approved(_)) => {}
+            Ok(PairingOutcome::Pending(code)) => {
+                bot.send_message(
+                    msg.chat.id,
+                    format!("Pair first. Your code: {}\nApprove with:\ntinyvegeta pairing approve {}", code, code),
+                ).await?;
+                return Ok(());
+            }
+            Err(e) => {
+                tracing::warn!("Failed to add pending sender for /restart: {}", e);
+                return Ok(());
             }
         }
-        return Ok(());
     }
 
     bot.send_message(msg.chat.id, "Restarting TinyVegeta daemon...").await?;
 
-    let exe = std::env::current_exe()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|_| "tinyvegeta".to_string());
+    let Some(exe) = self_exe_or_report(&bot, msg.chat.id).await? else {
+        return Ok(());
+    };
 
     let spawn_result = std::process::Command::new("nohup")
         .arg(exe)
@@ -1459,29 +1795,23 @@ async fn cmd_upgrade(bot: Bot, chat_id: ChatId) -> Result<(), RequestError> {
         );
         let mut picked = text.lines().rev().take(30).map(|s| s.to_string()).collect::<Vec<_>>();
         picked.reverse();
-        let mut out = picked.join("\n");
-        if out.len() > 3500 {
-            out.truncate(3500);
-            out.push_str("\n...[truncated]");
-        }
-        out
+        picked.join("\n")
     };
 
     match out {
         Ok(output) => {
             if !output.status.success() {
                 let summary = summarize(&output.stdout, &output.stderr);
-                bot.send_message(chat_id, format!("Upgrade failed:\n{}", summary))
-                    .await?;
+                send_chunked(&bot, chat_id, &format!("Upgrade failed:\n{}", summary)).await?;
                 return Ok(());
             }
 
             bot.send_message(chat_id, "Upgrade installed. Restarting TinyVegeta daemon...")
                 .await?;
 
-            let exe = std::env::current_exe()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_else(|_| "tinyvegeta".to_string());
+            let Some(exe) = self_exe_or_report(&bot, chat_id).await? else {
+                return Ok(());
+            };
             let spawn_result = std::process::Command::new("nohup")
                 .arg(exe)
                 .arg("restart")
@@ -1522,6 +1852,7 @@ const HELP_TEXT: &str = r#"TinyVegeta Commands:
 /upgrade - Reinstall from Git and restart daemon
 /doctor - Run health checks
 /provider [name] - Show or switch provider
+/models list [provider] - List a provider's models
 /memory stats - Memory statistics
 /memory search <query> - Search memory
 /brain show - Show BRAIN.md
@@ -1533,6 +1864,11 @@ const HELP_TEXT: &str = r#"TinyVegeta Commands:
 /sovereign [start|stop|status] - Control autonomous sovereign loop
 /reset @agent [@agent2...] - Reset specific agents
 /triage [on|off|status] - Auto-triage controls
+/ctx <context> | <message> - Send with one-off context
+/task create <title> - Create a task
+/task list - List tasks
+/task show <id> - Show task details
+/task start <id> - Run a task and report back
 /soul [@agent] - Start SOUL edit mode
 /soul show [@agent] - Preview SOUL.md
 /soul cancel - Cancel SOUL edit mode
@@ -1543,3 +1879,120 @@ Direct Messages:
 - Use @agent_id to route to specific agent
 - Use @team_id to route to team
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_provider_callback, enqueue_chat_message, parse_ctx_command, task_reply, triage_agent_candidate};
+    use crate::config::test_support::TempHome;
+    use crate::config::{load_settings, save_settings, AgentConfig, Settings};
+    use crate::telegram::test_support::{fake_bot_api, test_message};
+
+    #[test]
+    fn triage_follows_routing_rules_before_keywords() {
+        let mut settings = Settings::default();
+        for id in ["operations", "sales"] {
+            settings.agents.insert(id.to_string(), AgentConfig::default());
+        }
+        assert_eq!(triage_agent_candidate("deploy the pricing page", &settings).as_deref(), Some("operations"));
+        assert_eq!(triage_agent_candidate("fix this bug", &settings), None);
+
+        settings.routing.rules.push(crate::config::RoutingRule {
+            pattern: "pricing".to_string(),
+            agent: "sales".to_string(),
+            priority: 0,
+        });
+        assert_eq!(triage_agent_candidate("deploy the pricing page", &settings).as_deref(), Some("sales"));
+    }
+
+    #[test]
+    fn provider_callback_switches_and_saves_settings() {
+        let _home = TempHome::new();
+        let mut settings = Settings::default();
+        settings.agents.insert("assistant".to_string(), AgentConfig::default());
+        save_settings(&settings).unwrap();
+
+        let text = apply_provider_callback("provider:codex").unwrap();
+        assert_eq!(text, "✅ Switched to provider: codex (model: default)");
+        let saved = load_settings().unwrap();
+        assert_eq!(saved.models.provider, "codex");
+        let agent = &saved.agents["assistant"];
+        assert_eq!(agent.provider.as_deref(), Some("codex"));
+        assert_eq!(agent.model.as_deref(), Some("default"));
+
+        assert!(apply_provider_callback("provider:nope").is_err());
+        assert!(apply_provider_callback("other:codex").is_err());
+        assert_eq!(load_settings().unwrap().models.provider, "codex");
+    }
+
+    #[test]
+    fn ctx_command_splits_context_from_message() {
+        assert_eq!(
+            parse_ctx_command(" reply in JSON only | list open tasks "),
+            Some(("reply in JSON only".to_string(), "list open tasks".to_string()))
+        );
+        assert_eq!(
+            parse_ctx_command("be terse | a | b"),
+            Some(("be terse".to_string(), "a | b".to_string()))
+        );
+        assert_eq!(parse_ctx_command("no separator"), None);
+        assert_eq!(parse_ctx_command(" | message"), None);
+    }
+
+    #[test]
+    fn task_command_creates_and_lists_tasks() {
+        let _home = TempHome::new();
+        save_settings(&Settings::default()).unwrap();
+
+        assert_eq!(task_reply(&["list"]), "No tasks.");
+        let created = task_reply(&["create", "Write", "release", "notes"]);
+        assert!(created.starts_with("Created task: "), "{}", created);
+
+        let listed = task_reply(&["list"]);
+        assert!(listed.contains("| Write release notes | pending | @unassigned"), "{}", listed);
+
+        let id = crate::core::task_store::load_task_store().unwrap().tasks[0].id.clone();
+        assert!(task_reply(&["show", &id]).contains("Title: Write release notes"));
+        assert_eq!(task_reply(&["show", "missing"]), "Task not found: missing");
+        assert!(task_reply(&["create"]).starts_with("Usage:"));
+    }
+
+    #[tokio::test]
+    async fn redelivered_messages_are_enqueued_once() {
+        let _home = TempHome::new();
+        save_settings(&Settings::default()).unwrap();
+
+        // Acknowledgements to the chat are not checked.
+        let (bot, _) = fake_bot_api().await;
+        for id in [5, 5, 6] {
+            let msg = test_message(id, "fix the build");
+            enqueue_chat_message(&bot, &msg, "Ana", "42", "fix the build".to_string(), Vec::new(), None)
+                .await
+                .unwrap();
+        }
+
+        let queued = crate::core::Queue::incoming().unwrap();
+        let ids: Vec<_> = queued.iter().map(|q| q.data.message_id).collect();
+        assert_eq!(ids, [Some(5), Some(6)]);
+    }
+
+    #[tokio::test]
+    async fn oversized_messages_get_a_too_large_reply() {
+        let _home = TempHome::new();
+        let mut settings = Settings::default();
+        settings.queue.max_message_bytes = 2048;
+        save_settings(&settings).unwrap();
+
+        let (bot, sent) = fake_bot_api().await;
+        // Text alone is under the limit; the file references push it over.
+        let text = "a".repeat(1900);
+        let files: Vec<String> = (0..4).map(|i| format!("/tmp/files/upload-{}-{}.txt", i, "x".repeat(20))).collect();
+        let _ = enqueue_chat_message(&bot, &test_message(7, "see attached"), "Ana", "42", text.clone(), files, None).await;
+
+        assert!(crate::core::Queue::incoming().unwrap().is_empty());
+        let bodies = sent.lock().unwrap().clone();
+        assert!(bodies.iter().any(|b| b.contains("Message too large (3 KB, limit 2 KB)")), "{:?}", bodies);
+
+        let _ = enqueue_chat_message(&bot, &test_message(8, "see attached"), "Ana", "42", text, Vec::new(), None).await;
+        assert_eq!(crate::core::Queue::incoming().unwrap().len(), 1);
+    }
+}
@@ -5,7 +5,7 @@ use teloxide::prelude::*;
 use teloxide::types::Message;
 
 use crate::core::{Queue, MessageData};
-use super::pairing::PairingManager;
+use super::pairing::{PairingManager, PairingOutcome};
 
 /// Handle incoming messages.
 pub async fn handle_message(bot: Bot, msg: Message) -> Result<(), teloxide::RequestError> {
@@ -28,21 +28,23 @@ pub async fn handle_message(bot: Bot, msg: Message) -> Result<(), teloxide::Requ
                 msg.chat.id,
                 "Your request is pending approval. Please wait or use the pairing code from shell."
             ).await?;
-        } else {
-            // New sender - generate pairing code
-            match PairingManager::add_pending(&sender_id.to_string(), &sender) {
-                Ok(code) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("Welcome! Your pairing code is: {}\n\nApprove from shell with:\ntinyvegeta pairing approve {}", code, code)
-                    ).await?;
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to add pending sender: {}", e);
-                }
+            return Ok(());
+        }
+        // New sender - generate pairing code, unless allowlisted
+        match PairingManager::add_pending(&sender_id.to_string(), &sender) {
+            Ok(PairingOutcome::Approved(_)) => {}
+            Ok(PairingOutcome::Pending(code)) => {
+                bot.send_message(
+                    msg.chat.id,
+                    format!("Welcome! Your pairing code is: {}\n\nApprove from shell with:\ntinyvegeta pairing approve {}", code, code)
+                ).await?;
+                return Ok(());
+            }
+            Err(e) => {
+                tracing::warn!("Failed to add pending sender: {}", e);
+                return Ok(());
             }
         }
-        return Ok(());
     }
     
     // Get message text
@@ -72,10 +74,16 @@ pub async fn handle_message(bot: Bot, msg: Message) -> Result<(), teloxide::Requ
     }
     
     // Enqueue message
-    match Queue::enqueue(message_data) {
-        Ok(id) => {
+    let settings = crate::config::load_settings().unwrap_or_default();
+    match Queue::enqueue_validated(message_data, &settings) {
+        Ok((id, warning)) => {
             tracing::info!("Enqueued message {} from {} to agent {:?}", id, sender, target_agent);
-            // No status message - process and respond directly.
+            if let Some(warning) = warning {
+                bot.send_message(msg.chat.id, format!("{}.", warning)).await?;
+            }
+        }
+        Err(crate::error::Error::NotFound(reason)) => {
+            bot.send_message(msg.chat.id, format!("{}.", reason)).await?;
         }
         Err(e) => {
             tracing::error!("Failed to enqueue message: {}", e);
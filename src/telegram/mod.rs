@@ -4,5 +4,42 @@ pub mod pairing;
 pub mod commands;
 pub mod handler;
 pub mod client;
+pub mod rate_limit;
+pub mod webhook;
 
 pub use client::run_telegram_daemon;
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::sync::{Arc, Mutex};
+
+    use teloxide::types::Message;
+    use teloxide::Bot;
+
+    /// A bot pointed at a local stand-in for the Bot API, which answers every
+    /// call with success and records each request body.
+    pub(crate) async fn fake_bot_api() -> (Bot, Arc<Mutex<Vec<String>>>) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let recorder = sent.clone();
+        let api = axum::Router::new().fallback(move |body: String| {
+            recorder.lock().unwrap().push(body);
+            async { axum::Json(serde_json::json!({ "ok": true, "result": true })) }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, api).await.unwrap() });
+        (Bot::new("123:test").set_api_url(api_url.parse().unwrap()), sent)
+    }
+
+    /// A private-chat text message `id` from user 42 ("Ana").
+    pub(crate) fn test_message(id: i32, text: &str) -> Message {
+        serde_json::from_value(serde_json::json!({
+            "message_id": id,
+            "date": 1_700_000_000,
+            "chat": { "id": 42, "type": "private", "first_name": "Ana" },
+            "from": { "id": 42, "is_bot": false, "first_name": "Ana" },
+            "text": text
+        }))
+        .unwrap()
+    }
+}
@@ -3,7 +3,7 @@
 
 use ulid::Ulid;
 
-use crate::config::{get_settings_path, load_settings, ApprovedSender, PendingSender};
+use crate::config::{load_settings, save_settings, ApprovedSender, PendingSender, Settings};
 
 /// Pairing mode.
 #[derive(Debug, Clone, PartialEq)]
@@ -21,6 +21,15 @@ impl PairingMode {
     }
 }
 
+/// Result of [`PairingManager::add_pending`].
+#[derive(Debug, Clone)]
+pub enum PairingOutcome {
+    /// The sender must be approved with this code.
+    Pending(String),
+    /// The sender is on `pairing.auto_approve_ids` and was approved directly.
+    Approved(ApprovedSender),
+}
+
 /// Pairing manager.
 pub struct PairingManager;
 
@@ -67,22 +76,30 @@ impl PairingManager {
         code[..8].to_uppercase()
     }
 
-    /// Add a pending sender.
-    pub fn add_pending(sender_id: &str, sender_name: &str) -> Result<String, String> {
+    /// Add a pending sender, or approve it right away when it is on the
+    /// auto-approve allowlist.
+    pub fn add_pending(sender_id: &str, sender_name: &str) -> Result<PairingOutcome, String> {
         // Check if already approved or pending
         if Self::is_approved(sender_id) {
             return Err("Sender already approved".to_string());
         }
 
+        // Load settings
+        let mut settings = load_settings().map_err(|e| e.to_string())?;
+
+        if settings.pairing.auto_approve_ids.iter().any(|id| id == sender_id) {
+            let approved = approve_sender(&mut settings, sender_id, sender_name);
+            save_settings(&settings).map_err(|e| e.to_string())?;
+            tracing::info!("Auto-approved allowlisted sender: {} ({})", sender_name, sender_id);
+            return Ok(PairingOutcome::Approved(approved));
+        }
+
         if Self::is_pending(sender_id) {
             return Err("Sender already pending".to_string());
         }
 
         let code = Self::generate_code();
 
-        // Load settings
-        let mut settings = load_settings().map_err(|e| e.to_string())?;
-
         // Ensure pending_senders exists
         if settings.pairing.pending_senders.is_none() {
             settings.pairing.pending_senders = Some(Vec::new());
@@ -98,13 +115,51 @@ impl PairingManager {
         }
 
         // Save settings
-        let path = get_settings_path().map_err(|e| e.to_string())?;
-        let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-        std::fs::write(path, content).map_err(|e| e.to_string())?;
+        save_settings(&settings).map_err(|e| e.to_string())?;
 
         tracing::info!("Added pending sender: {} ({})", sender_name, sender_id);
 
-        Ok(code)
+        Ok(PairingOutcome::Pending(code))
+    }
+
+    /// Put a sender id on the auto-approve allowlist. A sender already
+    /// pending is approved now and returned. Adding an id twice is a no-op.
+    pub fn allow(sender_id: &str) -> Result<Option<ApprovedSender>, String> {
+        let mut settings = load_settings().map_err(|e| e.to_string())?;
+
+        if !settings.pairing.auto_approve_ids.iter().any(|id| id == sender_id) {
+            settings.pairing.auto_approve_ids.push(sender_id.to_string());
+        }
+        let pending_name = settings
+            .pairing
+            .pending_senders
+            .iter()
+            .flatten()
+            .find(|p| p.sender_id == sender_id)
+            .map(|p| p.sender_name.clone());
+        let promoted = pending_name.map(|name| approve_sender(&mut settings, sender_id, &name));
+
+        save_settings(&settings).map_err(|e| e.to_string())?;
+        tracing::info!("Allowlisted sender: {}", sender_id);
+
+        Ok(promoted)
+    }
+
+    /// Remove a sender id from the allowlist. Returns whether it was listed;
+    /// an already approved sender stays approved (see [`Self::unpair`]).
+    pub fn disallow(sender_id: &str) -> Result<bool, String> {
+        let mut settings = load_settings().map_err(|e| e.to_string())?;
+
+        let before = settings.pairing.auto_approve_ids.len();
+        settings.pairing.auto_approve_ids.retain(|id| id != sender_id);
+        let removed = settings.pairing.auto_approve_ids.len() != before;
+
+        if removed {
+            save_settings(&settings).map_err(|e| e.to_string())?;
+            tracing::info!("Removed sender from allowlist: {}", sender_id);
+        }
+
+        Ok(removed)
     }
 
     /// Approve a sender by code.
@@ -141,9 +196,7 @@ impl PairingManager {
         }
 
         // Save settings
-        let path = get_settings_path().map_err(|e| e.to_string())?;
-        let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-        std::fs::write(path, content).map_err(|e| e.to_string())?;
+        save_settings(&settings).map_err(|e| e.to_string())?;
 
         tracing::info!(
             "Approved sender: {} ({})",
@@ -163,9 +216,7 @@ impl PairingManager {
         }
 
         // Save settings
-        let path = get_settings_path().map_err(|e| e.to_string())?;
-        let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-        std::fs::write(path, content).map_err(|e| e.to_string())?;
+        save_settings(&settings).map_err(|e| e.to_string())?;
 
         tracing::info!("Unpaired sender: {}", sender_id);
 
@@ -189,12 +240,78 @@ impl PairingManager {
         settings.pairing.soul_owner_sender_id = Some(sender_id.to_string());
 
         // Save settings
-        let path = get_settings_path().map_err(|e| e.to_string())?;
-        let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-        std::fs::write(path, content).map_err(|e| e.to_string())?;
+        save_settings(&settings).map_err(|e| e.to_string())?;
 
         tracing::info!("Set soul owner: {}", sender_id);
 
         Ok(())
     }
 }
+
+/// Approve `sender_id` in `settings`, dropping any pending request for it.
+/// Returns the existing entry if it was already approved.
+fn approve_sender(settings: &mut Settings, sender_id: &str, sender_name: &str) -> ApprovedSender {
+    if let Some(pending) = &mut settings.pairing.pending_senders {
+        pending.retain(|p| p.sender_id != sender_id);
+    }
+    let approved = settings.pairing.approved_senders.get_or_insert_with(Vec::new);
+    if let Some(existing) = approved.iter().find(|a| a.sender_id == sender_id) {
+        return existing.clone();
+    }
+    let sender = ApprovedSender {
+        sender_id: sender_id.to_string(),
+        sender_name: sender_name.to_string(),
+        paired_at: chrono::Utc::now().timestamp_millis(),
+    };
+    approved.push(sender.clone());
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PairingManager, PairingOutcome};
+    use crate::config::{load_settings, save_settings, Settings};
+    use crate::config::test_support::TempHome;
+
+    fn setup() -> TempHome {
+        let home = TempHome::new();
+        save_settings(&Settings::default()).unwrap();
+        home
+    }
+
+    #[test]
+    fn allowlisted_senders_skip_the_code() {
+        let _home = setup();
+        assert!(PairingManager::allow("42").unwrap().is_none());
+
+        match PairingManager::add_pending("42", "Ada").unwrap() {
+            PairingOutcome::Approved(sender) => assert_eq!(sender.sender_name, "Ada"),
+            other => panic!("expected approval, got {:?}", other),
+        }
+        assert!(PairingManager::is_approved("42"));
+        assert!(!PairingManager::is_pending("42"));
+
+        assert!(matches!(PairingManager::add_pending("7", "Bob").unwrap(), PairingOutcome::Pending(_)));
+        assert!(PairingManager::is_pending("7"));
+    }
+
+    #[test]
+    fn allowing_a_pending_sender_promotes_it_once() {
+        let _home = setup();
+        PairingManager::add_pending("7", "Bob").unwrap();
+
+        let promoted = PairingManager::allow("7").unwrap().expect("pending sender promoted");
+        assert_eq!(promoted.sender_name, "Bob");
+        assert!(PairingManager::is_approved("7") && !PairingManager::is_pending("7"));
+
+        // Allowing again changes nothing.
+        assert!(PairingManager::allow("7").unwrap().is_none());
+        let pairing = load_settings().unwrap().pairing;
+        assert_eq!(pairing.auto_approve_ids, vec!["7".to_string()]);
+        assert_eq!(pairing.approved_senders.unwrap().len(), 1);
+
+        assert!(PairingManager::disallow("7").unwrap());
+        assert!(!PairingManager::disallow("7").unwrap());
+        assert!(PairingManager::is_approved("7"));
+    }
+}
@@ -0,0 +1,74 @@
+//! Per-sender sliding-window rate limit for messages entering the queue.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Length of the rate limit window.
+pub const WINDOW: Duration = Duration::from_secs(60);
+
+/// Recent enqueue times per sender.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    hits: HashMap<String, VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    /// Record an enqueue by `sender_id` at `now` if fewer than `limit` happened
+    /// in the last [`WINDOW`]; otherwise return how long until the oldest one
+    /// leaves the window. A `limit` of `0` disables the check.
+    pub fn check(&mut self, sender_id: &str, limit: u32, now: Instant) -> Result<(), Duration> {
+        if limit == 0 {
+            return Ok(());
+        }
+        let hits = self.hits.entry(sender_id.to_string()).or_default();
+        while hits.front().is_some_and(|t| now.duration_since(*t) >= WINDOW) {
+            hits.pop_front();
+        }
+        if hits.len() >= limit as usize {
+            let oldest = hits.front().copied().unwrap_or(now);
+            return Err(WINDOW.saturating_sub(now.duration_since(oldest)));
+        }
+        hits.push_back(now);
+        // Senders who went quiet do not keep an entry.
+        self.hits.retain(|_, h| h.back().is_some_and(|t| now.duration_since(*t) < WINDOW));
+        Ok(())
+    }
+}
+
+/// Check the shared limiter for `sender_id`; see [`RateLimiter::check`].
+pub fn check_sender(sender_id: &str, limit: u32) -> Result<(), Duration> {
+    static LIMITER: OnceLock<Mutex<RateLimiter>> = OnceLock::new();
+    LIMITER
+        .get_or_init(|| Mutex::new(RateLimiter::default()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .check(sender_id, limit, Instant::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RateLimiter, WINDOW};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn bursts_stop_at_the_limit_and_resume_after_the_window() {
+        let mut limiter = RateLimiter::default();
+        let start = Instant::now();
+
+        for i in 0..5 {
+            assert!(limiter.check("42", 5, start + Duration::from_secs(i)).is_ok());
+        }
+        let wait = limiter.check("42", 5, start + Duration::from_secs(10)).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(50));
+        // Other senders have their own window.
+        assert!(limiter.check("7", 5, start + Duration::from_secs(10)).is_ok());
+
+        // The first hit leaves the window; exactly one more fits.
+        assert!(limiter.check("42", 5, start + WINDOW).is_ok());
+        assert!(limiter.check("42", 5, start + WINDOW).is_err());
+        assert!(limiter.check("42", 5, start + WINDOW * 3).is_ok());
+
+        assert!((0..100).all(|_| limiter.check("42", 0, start).is_ok()));
+    }
+}
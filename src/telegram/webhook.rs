@@ -0,0 +1,139 @@
+//! Telegram webhook mode: Telegram posts updates to a public URL instead of
+//! the bot long-polling for them.
+//!
+//! [`run_webhook`] registers the URL with `setWebhook`, serves
+//! [`webhook_router`] until Ctrl+C or SIGTERM, then clears the webhook so a
+//! later polling start works.
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use teloxide::prelude::*;
+use teloxide::types::Update;
+
+use crate::config::ChannelConfig;
+use crate::error::Error;
+
+/// Path [`webhook_router`] serves.
+pub const WEBHOOK_PATH: &str = "/telegram/webhook";
+
+/// Header Telegram sends the `secret_token` given to `setWebhook` in.
+pub const SECRET_HEADER: &str = "x-telegram-bot-api-secret-token";
+
+#[derive(Clone)]
+struct WebhookState {
+    bot: Bot,
+    secret: String,
+}
+
+/// Router receiving updates on [`WEBHOOK_PATH`]. Requests whose
+/// [`SECRET_HEADER`] is not `secret` are rejected with 401.
+pub fn webhook_router(bot: Bot, secret: String) -> Router {
+    Router::new()
+        .route(WEBHOOK_PATH, post(receive_update))
+        .with_state(WebhookState { bot, secret })
+}
+
+async fn receive_update(State(state): State<WebhookState>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    let secret = headers.get(SECRET_HEADER).and_then(|v| v.to_str().ok());
+    if secret != Some(state.secret.as_str()) {
+        tracing::warn!("Rejected Telegram webhook request without a valid secret token");
+        return StatusCode::UNAUTHORIZED;
+    }
+    let update: Update = match serde_json::from_slice(&body) {
+        Ok(update) => update,
+        Err(e) => {
+            tracing::warn!("Rejected malformed Telegram update: {}", e);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+    // Answer right away; Telegram resends updates that are not acknowledged in time.
+    tokio::spawn(super::client::dispatch_update(state.bot, update));
+    StatusCode::OK
+}
+
+/// Register `telegram.webhook_url` and serve updates on
+/// `telegram.webhook_listen` until shutdown.
+pub async fn run_webhook(bot: Bot, telegram: &ChannelConfig) -> Result<(), Error> {
+    let url = telegram
+        .webhook_url
+        .as_deref()
+        .ok_or_else(|| Error::Telegram("channels.telegram.webhook_url is required in webhook mode".to_string()))?;
+    let url = reqwest::Url::parse(url).map_err(|e| Error::Telegram(format!("invalid webhook_url '{}': {}", url, e)))?;
+    let secret = telegram
+        .webhook_secret
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+
+    let listener = tokio::net::TcpListener::bind(&telegram.webhook_listen).await?;
+    bot.set_webhook(url.clone())
+        .secret_token(secret.clone())
+        .await
+        .map_err(|e| Error::Telegram(format!("setWebhook failed: {}", e)))?;
+    tracing::info!("Telegram webhook registered at {}, listening on {}", url, telegram.webhook_listen);
+
+    let served = axum::serve(listener, webhook_router(bot.clone(), secret))
+        .with_graceful_shutdown(crate::web::server::shutdown_signal())
+        .await;
+
+    match bot.delete_webhook().await {
+        Ok(_) => tracing::info!("Telegram webhook cleared"),
+        Err(e) => tracing::warn!("Failed to clear Telegram webhook: {}", e),
+    }
+    served.map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{webhook_router, SECRET_HEADER, WEBHOOK_PATH};
+    use crate::config::test_support::TempHome;
+    use crate::config::{save_settings, Settings};
+    use crate::core::Queue;
+    use crate::telegram::pairing::PairingManager;
+    use crate::telegram::test_support::{fake_bot_api, test_message};
+
+    /// Serve `router` on a free local port and return its base URL.
+    async fn serve(router: axum::Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn posted_updates_are_verified_and_enqueued() {
+        let _home = TempHome::new();
+        save_settings(&Settings::default()).unwrap();
+        PairingManager::allow("42").unwrap();
+
+        // Replies to the sender are not checked.
+        let (bot, _) = fake_bot_api().await;
+        let url = format!("{}{}", serve(webhook_router(bot, "s3cret".to_string())).await, WEBHOOK_PATH);
+
+        let update = serde_json::json!({ "update_id": 1, "message": test_message(5, "fix the build") });
+        let client = reqwest::Client::new();
+        let post = |secret: &'static str| client.post(&url).header(SECRET_HEADER, secret).json(&update).send();
+
+        assert_eq!(post("wrong").await.unwrap().status(), 401);
+        assert_eq!(client.post(&url).json(&update).send().await.unwrap().status(), 401);
+        assert!(Queue::incoming().unwrap().is_empty());
+
+        assert_eq!(post("s3cret").await.unwrap().status(), 200);
+        let mut queued = Vec::new();
+        for _ in 0..50 {
+            queued = Queue::incoming().unwrap();
+            if !queued.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(queued.len(), 1);
+        let data = &queued[0].data;
+        assert_eq!((data.channel.as_str(), data.sender_id.as_str()), ("telegram", "42"));
+        assert_eq!(data.message, "fix the build");
+        assert_eq!(data.response_chat_id, Some(42));
+        assert_eq!(data.message_id, Some(5));
+    }
+}
@@ -6,13 +6,22 @@ use std::process::Command;
 use crate::error::Error;
 pub type Result<T> = std::result::Result<T, Error>;
 
-/// The tmux session name used by TinyVegeta.
+/// The tmux session name used by TinyVegeta without a profile.
 pub const TMUX_SESSION: &str = "tinyvegeta";
 
+/// The tmux session of the active profile: `tinyvegeta` or
+/// `tinyvegeta-<profile>`, so daemons of different profiles can run side by side.
+pub fn session_name() -> String {
+    match crate::config::active_profile().ok().flatten() {
+        Some(profile) => format!("{}-{}", TMUX_SESSION, profile),
+        None => TMUX_SESSION.to_string(),
+    }
+}
+
 /// Check if a tmux session exists.
 pub fn session_exists() -> Result<bool> {
     let output = Command::new("tmux")
-        .args(["has-session", "-t", TMUX_SESSION])
+        .args(["has-session", "-t", &session_name()])
         .output()?;
 
     Ok(output.status.success())
@@ -26,7 +35,7 @@ pub fn is_running() -> Result<bool> {
 
     // Check if the session has at least one client attached
     let output = Command::new("tmux")
-        .args(["list-clients", "-t", TMUX_SESSION])
+        .args(["list-clients", "-t", &session_name()])
         .output()?;
 
     // If there are clients attached, it's running
@@ -38,7 +47,7 @@ pub fn start_daemon(binary_path: &str) -> Result<()> {
     if session_exists()? {
         return Err(Error::Tmux(format!(
             "Session '{}' already exists. Stop it first with 'tinyvegeta stop'.",
-            TMUX_SESSION
+            session_name()
         )));
     }
 
@@ -50,7 +59,7 @@ pub fn start_daemon(binary_path: &str) -> Result<()> {
     );
 
     let output = Command::new("tmux")
-        .args(["new-session", "-d", "-s", TMUX_SESSION, "-n", "tinyvegeta"])
+        .args(["new-session", "-d", "-s", &session_name(), "-n", "tinyvegeta"])
         .output()?;
 
     if !output.status.success() {
@@ -62,10 +71,14 @@ pub fn start_daemon(binary_path: &str) -> Result<()> {
     }
 
     // Send the start commands to the session
-    let daemon_cmd = format!("{} start-internal", binary_path);
+    // The tmux shell does not inherit our environment, so pass the profile on.
+    let daemon_cmd = match crate::config::active_profile()? {
+        Some(profile) => format!("{} --profile {} start-internal", binary_path, profile),
+        None => format!("{} start-internal", binary_path),
+    };
 
     let output = Command::new("tmux")
-        .args(["send-keys", "-t", TMUX_SESSION, &daemon_cmd, "Enter"])
+        .args(["send-keys", "-t", &session_name(), &daemon_cmd, "Enter"])
         .output()?;
 
     if !output.status.success() {
@@ -78,7 +91,7 @@ pub fn start_daemon(binary_path: &str) -> Result<()> {
 
     tracing::info!(
         "Started TinyVegeta daemon in tmux session '{}'",
-        TMUX_SESSION
+        session_name()
     );
     Ok(())
 }
@@ -88,13 +101,13 @@ pub fn stop_daemon() -> Result<()> {
     if !session_exists()? {
         return Err(Error::Tmux(format!(
             "No session '{}' found. Is TinyVegeta running?",
-            TMUX_SESSION
+            session_name()
         )));
     }
 
     // Kill the session
     let output = Command::new("tmux")
-        .args(["kill-session", "-t", TMUX_SESSION])
+        .args(["kill-session", "-t", &session_name()])
         .output()?;
 
     if !output.status.success() {
@@ -125,7 +138,7 @@ pub fn attach() -> Result<()> {
 
     // Detach any existing client and attach
     let output = Command::new("tmux")
-        .args(["attach-session", "-t", TMUX_SESSION])
+        .args(["attach-session", "-t", &session_name()])
         .output()?;
 
     if !output.status.success() {
@@ -144,7 +157,7 @@ pub fn get_status() -> Result<String> {
 
     // Get session info
     let output = Command::new("tmux")
-        .args(["list-session", "-t", TMUX_SESSION, "-F", "#{session_info}"])
+        .args(["list-session", "-t", &session_name(), "-F", "#{session_info}"])
         .output()?;
 
     if output.status.success() {
@@ -7,10 +7,11 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::config::{load_settings, AgentConfig};
+use crate::config::{load_settings, AgentConfig, NewAgent};
+use crate::error::Error;
 
 /// Agent API response.
-#[derive(Serialize)]
+#[derive(Debug, Serialize)]
 pub struct AgentResponse {
     pub id: String,
     pub name: String,
@@ -66,32 +67,27 @@ pub async fn get_agent(Path(id): Path<String>) -> Result<Json<AgentResponse>, St
 }
 
 /// Create a new agent.
+///
+/// Goes through [`crate::config::create_agent`], like `tinyvegeta agent add`.
 pub async fn create_agent(
     Json(payload): Json<CreateAgentRequest>,
 ) -> Result<Json<AgentResponse>, StatusCode> {
     let mut settings = load_settings().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
-    if settings.agents.contains_key(&payload.id) {
-        return Err(StatusCode::CONFLICT);
-    }
-    
-    let agent = AgentConfig {
+    let new = NewAgent {
+        id: payload.id,
         name: Some(payload.name),
         provider: Some(payload.provider),
         model: payload.model,
         working_directory: payload.working_directory.map(|p| p.into()),
-        is_sovereign: false,
     };
+    let created = crate::config::create_agent(&mut settings, new).map_err(|e| match e {
+        Error::Config(_) => StatusCode::BAD_REQUEST,
+        Error::AlreadyExists(_) => StatusCode::CONFLICT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    })?;
     
-    let id = payload.id.clone();
-    settings.agents.insert(id.clone(), agent.clone());
-    
-    // Save settings
-    let path = crate::config::get_settings_path().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let content = serde_json::to_string_pretty(&settings).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    std::fs::write(path, content).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    Ok(Json(AgentResponse::from((id, agent))))
+    Ok(Json(AgentResponse::from(created)))
 }
 
 /// Delete an agent.
@@ -103,9 +99,45 @@ pub async fn delete_agent(Path(id): Path<String>) -> Result<StatusCode, StatusCo
     }
     
     // Save settings
-    let path = crate::config::get_settings_path().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let content = serde_json::to_string_pretty(&settings).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    std::fs::write(path, content).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    crate::config::save_settings(&settings).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: &str) -> CreateAgentRequest {
+        CreateAgentRequest {
+            id: id.to_string(),
+            name: "Analyst".to_string(),
+            provider: "claude".to_string(),
+            model: None,
+            working_directory: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn create_agent_matches_cli_validation_and_scaffolding() {
+        let home = crate::config::test_support::TempHome::new();
+        std::fs::write(home.tinyvegeta_dir().join("settings.json"), r#"{"teams": {"ops": {"name": "Ops", "agents": []}}}"#)
+            .unwrap();
+
+        let Json(created) = create_agent(Json(request(" Analyst "))).await.unwrap();
+        assert_eq!(created.id, "analyst");
+        assert_eq!(created.model.as_deref(), Some("default"));
+        let workdir = std::path::PathBuf::from(created.working_directory.unwrap());
+        assert!(workdir.ends_with("tinyvegeta-workspace/analyst"));
+        assert!(workdir.join("SOUL.md").exists());
+
+        let settings = load_settings().unwrap();
+        assert!(settings.agents.contains_key("analyst"));
+        assert!(!home.tinyvegeta_dir().join("settings.json.tmp").exists());
+
+        assert_eq!(create_agent(Json(request("analyst"))).await.unwrap_err(), StatusCode::CONFLICT);
+        assert_eq!(create_agent(Json(request("ops"))).await.unwrap_err(), StatusCode::CONFLICT);
+        assert_eq!(create_agent(Json(request("bad id!"))).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        assert_eq!(create_agent(Json(request("  "))).await.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,142 @@
+//! Server-Sent Events stream for live agent health and queue depth.
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::response::sse::{Event, KeepAlive, Sse};
+use serde::Serialize;
+use tokio_stream::{wrappers::IntervalStream, Stream, StreamExt};
+
+use crate::core::Queue;
+use crate::memory::{Memory, MemoryScope};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const HEALTH_PREFIX: &str = "agent.health.";
+
+/// Queue depth by stage.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct QueueDepth {
+    pub incoming: usize,
+    pub processing: usize,
+    pub outgoing: usize,
+    pub total: usize,
+}
+
+/// A changed `agent.health.<agent_id>.<field>` key; `value` is `None` when removed.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AgentHealthChange {
+    pub agent_id: String,
+    pub field: String,
+    pub value: Option<String>,
+}
+
+/// Changes since the previous poll.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct LiveDelta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue: Option<QueueDepth>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<AgentHealthChange>,
+}
+
+#[derive(Default)]
+struct Snapshot {
+    queue: Option<QueueDepth>,
+    health: BTreeMap<String, String>,
+}
+
+impl Snapshot {
+    fn capture() -> Self {
+        let queue = Queue::stats(None).ok().map(|q| QueueDepth {
+            incoming: q.incoming,
+            processing: q.processing,
+            outgoing: q.outgoing,
+            total: q.total,
+        });
+        let health = Memory::list(MemoryScope::Global, None, None)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|e| e.key.starts_with(HEALTH_PREFIX))
+            .map(|e| (e.key, e.value))
+            .collect();
+        Self { queue, health }
+    }
+}
+
+fn split_health_key(key: &str) -> Option<(String, String)> {
+    let rest = key.strip_prefix(HEALTH_PREFIX)?;
+    let (agent_id, field) = rest.rsplit_once('.')?;
+    Some((agent_id.to_string(), field.to_string()))
+}
+
+fn diff(prev: &Snapshot, curr: &Snapshot) -> Option<LiveDelta> {
+    let queue = (curr.queue != prev.queue).then(|| curr.queue.clone()).flatten();
+
+    let mut agents = Vec::new();
+    for (key, value) in &curr.health {
+        if prev.health.get(key) != Some(value) {
+            if let Some((agent_id, field)) = split_health_key(key) {
+                agents.push(AgentHealthChange { agent_id, field, value: Some(value.clone()) });
+            }
+        }
+    }
+    for key in prev.health.keys().filter(|k| !curr.health.contains_key(*k)) {
+        if let Some((agent_id, field)) = split_health_key(key) {
+            agents.push(AgentHealthChange { agent_id, field, value: None });
+        }
+    }
+
+    if queue.is_none() && agents.is_empty() {
+        None
+    } else {
+        Some(LiveDelta { queue, agents })
+    }
+}
+
+/// Stream live deltas as SSE.
+///
+/// The first event carries the full current state. The stream owns all of its
+/// state and spawns no tasks, so it is simply dropped when the client disconnects.
+pub async fn live_events() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut prev = Snapshot::default();
+    let stream = IntervalStream::new(tokio::time::interval(POLL_INTERVAL)).filter_map(move |_| {
+        let curr = Snapshot::capture();
+        let delta = diff(&prev, &curr);
+        prev = curr;
+        let delta = delta?;
+        let event = Event::default().event("delta").json_data(&delta).ok()?;
+        Some(Ok(event))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(queue_total: usize, health: &[(&str, &str)]) -> Snapshot {
+        Snapshot {
+            queue: Some(QueueDepth { incoming: queue_total, processing: 0, outgoing: 0, total: queue_total }),
+            health: health.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn diff_reports_only_changes() {
+        let a = snapshot(1, &[("agent.health.coder.status", "ok"), ("agent.health.qa.status", "ok")]);
+        assert!(diff(&a, &snapshot(1, &[("agent.health.coder.status", "ok"), ("agent.health.qa.status", "ok")])).is_none());
+
+        let b = snapshot(2, &[("agent.health.coder.status", "error")]);
+        let delta = diff(&a, &b).unwrap();
+        assert_eq!(delta.queue.map(|q| q.total), Some(2));
+        assert_eq!(
+            delta.agents,
+            vec![
+                AgentHealthChange { agent_id: "coder".into(), field: "status".into(), value: Some("error".into()) },
+                AgentHealthChange { agent_id: "qa".into(), field: "status".into(), value: None },
+            ]
+        );
+    }
+}
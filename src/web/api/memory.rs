@@ -8,6 +8,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::config::load_settings;
 use crate::memory::{Memory, MemoryEntry, MemoryScope};
 
 /// Memory API response.
@@ -50,6 +51,32 @@ pub struct MemoryQuery {
     pub category: Option<String>,
 }
 
+/// Query parameters for listing memory.
+#[derive(Deserialize, Default)]
+pub struct ListMemoryQuery {
+    pub scope: Option<String>,
+    pub scope_id: Option<String>,
+    pub category: Option<String>,
+    /// Only keys starting with this prefix.
+    pub prefix: Option<String>,
+    /// Page size (default 100, at most 1000).
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// One page of memory entries, ordered by key.
+#[derive(Serialize)]
+pub struct MemoryPage {
+    /// Matching entries across all pages.
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub entries: Vec<MemoryResponse>,
+}
+
+const DEFAULT_PAGE_LIMIT: usize = 100;
+const MAX_PAGE_LIMIT: usize = 1000;
+
 /// Set a memory entry.
 pub async fn set_memory(
     Json(payload): Json<SetMemoryRequest>,
@@ -61,7 +88,8 @@ pub async fn set_memory(
         _ => MemoryScope::Global,
     };
     
-    Memory::set(&payload.key, &payload.value, scope, payload.scope_id.as_deref())
+    let track_history = load_settings().is_ok_and(|s| s.memory.track_history);
+    Memory::set_tracked(&payload.key, &payload.value, scope, payload.scope_id.as_deref(), track_history)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     // Get the entry back
@@ -91,10 +119,10 @@ pub async fn get_memory(
     Ok(Json(MemoryResponse::from(entry)))
 }
 
-/// List memory entries.
+/// List memory entries, filtered and paginated.
 pub async fn list_memory(
-    Query(query): Query<MemoryQuery>,
-) -> Result<Json<Vec<MemoryResponse>>, StatusCode> {
+    Query(query): Query<ListMemoryQuery>,
+) -> Result<Json<MemoryPage>, StatusCode> {
     let scope = match query.scope.as_deref() {
         Some("agent") => MemoryScope::Agent,
         Some("team") => MemoryScope::Team,
@@ -102,15 +130,24 @@ pub async fn list_memory(
         _ => MemoryScope::Global,
     };
     
-    let entries = Memory::list(scope, query.scope_id.as_deref(), query.category.as_deref())
+    let mut entries = Memory::list(scope, query.scope_id.as_deref(), query.category.as_deref())
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(prefix) = query.prefix.as_deref() {
+        entries.retain(|e| e.key.starts_with(prefix));
+    }
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
     
-    let responses: Vec<MemoryResponse> = entries
+    let total = entries.len();
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT);
+    let entries = entries
         .into_iter()
+        .skip(offset)
+        .take(limit)
         .map(MemoryResponse::from)
         .collect();
     
-    Ok(Json(responses))
+    Ok(Json(MemoryPage { total, offset, limit, entries }))
 }
 
 /// Delete a memory entry.
@@ -176,3 +213,51 @@ pub async fn memory_stats() -> Result<Json<MemoryStatsResponse>, StatusCode> {
         total: stats.total,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn list_memory_filters_and_paginates_by_key() {
+        let _home = crate::config::test_support::TempHome::new();
+        for i in 1..=5 {
+            Memory::set(&format!("note.{}", i), "v", MemoryScope::Global, None).unwrap();
+        }
+        Memory::set("other", "v", MemoryScope::Global, None).unwrap();
+        Memory::set("note.agent", "v", MemoryScope::Agent, Some("coder")).unwrap();
+
+        let list = |prefix: Option<&str>, limit: Option<usize>, offset: Option<usize>| {
+            let query = ListMemoryQuery {
+                prefix: prefix.map(String::from),
+                limit,
+                offset,
+                ..ListMemoryQuery::default()
+            };
+            async move { list_memory(Query(query)).await.unwrap().0 }
+        };
+        let keys = |page: &MemoryPage| page.entries.iter().map(|e| e.key.clone()).collect::<Vec<_>>();
+
+        let all = list(None, None, None).await;
+        assert_eq!((all.total, all.offset, all.limit), (6, 0, 100));
+
+        let page = list(Some("note."), Some(2), Some(2)).await;
+        assert_eq!(page.total, 5);
+        assert_eq!(keys(&page), ["note.3", "note.4"]);
+        assert_eq!(keys(&list(Some("note."), Some(2), Some(4)).await), ["note.5"]);
+
+        let past_end = list(Some("note."), Some(2), Some(10)).await;
+        assert_eq!(past_end.total, 5);
+        assert!(past_end.entries.is_empty());
+
+        assert_eq!(list(None, Some(0), None).await.limit, 1);
+        assert_eq!(list(None, Some(5000), None).await.limit, MAX_PAGE_LIMIT);
+
+        let agent = ListMemoryQuery {
+            scope: Some("agent".to_string()),
+            scope_id: Some("coder".to_string()),
+            ..ListMemoryQuery::default()
+        };
+        assert_eq!(keys(&list_memory(Query(agent)).await.unwrap().0), ["note.agent"]);
+    }
+}
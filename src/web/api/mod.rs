@@ -3,7 +3,11 @@
 pub mod agents;
 pub mod teams;
 pub mod memory;
+pub mod events;
+pub mod tasks;
 
 pub use agents::{list_agents, get_agent, create_agent, delete_agent};
 pub use teams::{list_teams, get_team, create_team, delete_team};
 pub use memory::{set_memory, get_memory, list_memory, delete_memory, search_memory, memory_stats};
+pub use tasks::{list_tasks, get_task, create_task, delete_task, start_task};
+pub use events::live_events;
@@ -0,0 +1,141 @@
+//! API endpoints for tasks.
+
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::config::load_settings;
+use crate::core::task_store::{begin_task, finish_task, load_task_store, save_task_store, TaskRecord};
+use crate::error::Error;
+use crate::heartbeat::tasks::TaskSpawner;
+
+/// Create task request.
+#[derive(Deserialize)]
+pub struct CreateTaskRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub agent_id: Option<String>,
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+/// List all tasks.
+pub async fn list_tasks() -> Result<Json<Vec<TaskRecord>>, StatusCode> {
+    let store = load_task_store().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(store.tasks))
+}
+
+/// Get a single task.
+pub async fn get_task(Path(id): Path<String>) -> Result<Json<TaskRecord>, StatusCode> {
+    let store = load_task_store().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let task = store.get(&id).cloned().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(task))
+}
+
+/// Create a new task.
+pub async fn create_task(
+    Json(payload): Json<CreateTaskRequest>,
+) -> Result<(StatusCode, Json<TaskRecord>), StatusCode> {
+    if payload.title.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut record = TaskRecord::new(
+        payload.title.trim(),
+        payload.description,
+        payload.agent_id,
+        payload.priority.as_deref(),
+        payload
+            .tags
+            .into_iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+    );
+
+    record.depends_on = payload.depends_on;
+
+    let mut store = load_task_store().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    store.add(record.clone()).map_err(|_| StatusCode::BAD_REQUEST)?;
+    save_task_store(&store).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((StatusCode::CREATED, Json(record)))
+}
+
+/// Delete a task.
+pub async fn delete_task(Path(id): Path<String>) -> Result<StatusCode, StatusCode> {
+    let mut store = load_task_store().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let before = store.tasks.len();
+    store.tasks.retain(|t| t.id != id);
+    if store.tasks.len() == before {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    save_task_store(&store).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Start a task in the background; poll `GET /api/tasks/:id` for the result.
+pub async fn start_task(
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<TaskRecord>), StatusCode> {
+    let settings = load_settings().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (snapshot, task) = begin_task(&id, &settings).map_err(|e| match e {
+        Error::NotFound(_) => StatusCode::NOT_FOUND,
+        Error::AlreadyExists(_) | Error::Blocked(_) => StatusCode::CONFLICT,
+        Error::Config(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    })?;
+
+    tokio::spawn(async move {
+        let result = TaskSpawner::spawn_task(&task, &settings).await;
+        if let Err(e) = finish_task(&id, &result) {
+            tracing::warn!("Failed to save task {}: {}", id, e);
+        }
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(snapshot)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn task_handlers_round_trip_through_task_store() {
+        let home = crate::config::test_support::TempHome::new();
+        std::fs::write(home.tinyvegeta_dir().join("settings.json"), "{}").unwrap();
+
+        let (status, Json(created)) = create_task(Json(CreateTaskRequest {
+            title: "Write report".to_string(),
+            description: None,
+            agent_id: Some("ghost".to_string()),
+            priority: Some("high".to_string()),
+            tags: vec!["ops".to_string(), " ".to_string()],
+            depends_on: Vec::new(),
+        }))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created.priority, "high");
+        assert_eq!(created.tags, vec!["ops".to_string()]);
+
+        let Json(listed) = list_tasks().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        let Json(fetched) = get_task(Path(created.id.clone())).await.unwrap();
+        assert_eq!(fetched.status, "pending");
+
+        // Unknown agent is rejected without touching the task.
+        assert_eq!(start_task(Path(created.id.clone())).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        assert_eq!(start_task(Path("missing".to_string())).await.unwrap_err(), StatusCode::NOT_FOUND);
+
+        assert_eq!(delete_task(Path(created.id.clone())).await.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(get_task(Path(created.id)).await.unwrap_err(), StatusCode::NOT_FOUND);
+        assert!(load_task_store().unwrap().tasks.is_empty());
+    }
+}
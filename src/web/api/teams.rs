@@ -80,9 +80,7 @@ pub async fn create_team(
     settings.teams.insert(id.clone(), team.clone());
     
     // Save settings
-    let path = crate::config::get_settings_path().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let content = serde_json::to_string_pretty(&settings).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    std::fs::write(path, content).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    crate::config::save_settings(&settings).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     Ok(Json(TeamResponse::from((id, team))))
 }
@@ -96,9 +94,7 @@ pub async fn delete_team(Path(id): Path<String>) -> Result<StatusCode, StatusCod
     }
     
     // Save settings
-    let path = crate::config::get_settings_path().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let content = serde_json::to_string_pretty(&settings).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    std::fs::write(path, content).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    crate::config::save_settings(&settings).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     Ok(StatusCode::NO_CONTENT)
 }
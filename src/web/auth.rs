@@ -1,6 +1,12 @@
 //! JWT authentication for web server.
 #![allow(dead_code)]
 
+use axum::{
+    extract::{Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::Next,
+    response::Response,
+};
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -65,6 +71,42 @@ pub fn extract_token(auth_header: Option<&str>) -> Result<&str, String> {
     Ok(&header[7..])
 }
 
+/// Generate a random API token (64 hex chars).
+pub fn generate_api_token() -> String {
+    format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple())
+}
+
+/// Compare tokens without short-circuiting on the first differing byte.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Middleware requiring `Authorization: Bearer <token>` to match the configured token.
+///
+/// With no token configured every request passes through.
+pub async fn require_api_token(
+    State(expected): State<Option<String>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if let Some(expected) = expected.as_deref() {
+        let header = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok());
+        match extract_token(header) {
+            Ok(token) if tokens_match(token, expected) => {}
+            _ => return Err(StatusCode::UNAUTHORIZED),
+        }
+    }
+    Ok(next.run(request).await)
+}
+
 /// Hash a password.
 pub fn hash_password(password: &str) -> Result<String, String> {
     bcrypt::hash(password, bcrypt::DEFAULT_COST).map_err(|e| e.to_string())
@@ -88,6 +130,36 @@ mod tests {
         assert_eq!(claims.name, "Test User");
     }
 
+    #[tokio::test]
+    async fn api_routes_require_valid_bearer_token() {
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let app = crate::web::router::create_app_router_with_token(Some("secret".to_string()));
+        let request = |uri: &str, auth: Option<&str>| {
+            let mut builder = Request::builder().uri(uri);
+            if let Some(auth) = auth {
+                builder = builder.header(AUTHORIZATION, auth);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+        let status = |uri: &'static str, auth: Option<&'static str>| {
+            let app = app.clone();
+            async move { app.oneshot(request(uri, auth)).await.unwrap().status() }
+        };
+
+        assert_eq!(status("/health", None).await, StatusCode::OK);
+        assert_eq!(status("/api/agents", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/api/agents", Some("Bearer wrong")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/api/agents", Some("secret")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/api/events", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/api/events", Some("Bearer secret")).await, StatusCode::OK);
+
+        let open = crate::web::router::create_app_router_with_token(None);
+        assert_eq!(open.oneshot(request("/api/events", None)).await.unwrap().status(), StatusCode::OK);
+    }
+
     #[test]
     fn test_password_hashing() {
         let hash = hash_password("password123").unwrap();
@@ -0,0 +1,242 @@
+//! `Idempotency-Key` support for mutating API requests.
+//!
+//! A `POST`/`PUT`/`PATCH`/`DELETE` carrying the header is executed once; a
+//! replay of the same key on the same route within the window gets the stored
+//! response back (marked `Idempotent-Replayed: true`) instead of re-running.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Request header carrying the client's idempotency key.
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// Response header set on replayed responses.
+pub const IDEMPOTENT_REPLAYED: &str = "idempotent-replayed";
+
+/// Longest accepted key.
+const MAX_KEY_LEN: usize = 255;
+
+#[derive(Clone)]
+struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl CachedResponse {
+    fn replay(&self) -> Response {
+        let mut response = Response::new(Body::from(self.body.clone()));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers.clone();
+        response
+            .headers_mut()
+            .insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+enum Slot {
+    /// The first request with this key is still running.
+    InFlight(Instant),
+    Done(Instant, CachedResponse),
+}
+
+impl Slot {
+    fn started(&self) -> Instant {
+        match self {
+            Slot::InFlight(at) | Slot::Done(at, _) => *at,
+        }
+    }
+}
+
+/// Recently seen idempotency keys and their responses, shared by the router.
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    window: Duration,
+    slots: Arc<Mutex<HashMap<String, Slot>>>,
+}
+
+impl IdempotencyStore {
+    /// Remember keys for `window` after their first use.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            slots: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn slots(&self) -> std::sync::MutexGuard<'_, HashMap<String, Slot>> {
+        self.slots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for IdempotencyStore {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(crate::config::Web::default().idempotency_window_secs))
+    }
+}
+
+/// Clears an `InFlight` slot unless its response was stored, so a request
+/// dropped mid-way (say, by a client disconnect) does not hold its key.
+struct InFlightGuard<'a> {
+    store: &'a IdempotencyStore,
+    key: String,
+    stored: bool,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if !self.stored {
+            self.store.slots().remove(&self.key);
+        }
+    }
+}
+
+fn is_mutating(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE)
+}
+
+/// Middleware replaying stored responses for repeated `Idempotency-Key`s.
+///
+/// Keys are scoped to the method and path. A replay while the first request
+/// is still running gets `409 Conflict`; server errors are not stored, so the
+/// client can retry them with the same key.
+pub async fn idempotent(State(store): State<IdempotencyStore>, request: Request, next: Next) -> Response {
+    if !is_mutating(request.method()) {
+        return next.run(request).await;
+    }
+    let key = match request.headers().get(IDEMPOTENCY_KEY).map(|v| v.to_str()) {
+        None => return next.run(request).await,
+        Some(Ok(key)) if !key.trim().is_empty() && key.len() <= MAX_KEY_LEN => key.trim().to_string(),
+        Some(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+    let slot_key = format!("{} {} {}", request.method(), request.uri().path(), key);
+
+    {
+        let mut slots = store.slots();
+        slots.retain(|_, slot| slot.started().elapsed() < store.window);
+        match slots.get(&slot_key) {
+            Some(Slot::Done(_, cached)) => return cached.replay(),
+            Some(Slot::InFlight(_)) => return StatusCode::CONFLICT.into_response(),
+            None => {
+                slots.insert(slot_key.clone(), Slot::InFlight(Instant::now()));
+            }
+        }
+    }
+
+    let mut in_flight = InFlightGuard { store: &store, key: slot_key, stored: false };
+
+    let (parts, body) = next.run(request).await.into_parts();
+    let Ok(body) = to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if !parts.status.is_server_error() {
+        let cached = CachedResponse {
+            status: parts.status,
+            headers: parts.headers.clone(),
+            body: body.clone(),
+        };
+        store.slots().insert(in_flight.key.clone(), Slot::Done(Instant::now(), cached));
+        in_flight.stored = true;
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::post, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    fn app(store: IdempotencyStore, calls: Arc<AtomicUsize>) -> Router {
+        let created = move || {
+            let calls = calls.clone();
+            async move {
+                let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                (StatusCode::CREATED, format!("created #{}", n))
+            }
+        };
+        Router::new()
+            .route("/items", post(created.clone()).get(created))
+            .route("/broken", post(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .route(
+                "/slow",
+                post(|| async {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    StatusCode::CREATED
+                }),
+            )
+            .route_layer(middleware::from_fn_with_state(store, idempotent))
+    }
+
+    async fn send(app: &Router, method: Method, uri: &str, key: Option<&str>) -> (StatusCode, bool, String) {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some(key) = key {
+            builder = builder.header(IDEMPOTENCY_KEY, key);
+        }
+        let response = app.clone().oneshot(builder.body(Body::empty()).unwrap()).await.unwrap();
+        let replayed = response.headers().contains_key(IDEMPOTENT_REPLAYED);
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, replayed, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn replayed_keys_return_the_stored_response_without_rerunning() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(IdempotencyStore::default(), calls.clone());
+
+        let first = send(&app, Method::POST, "/items", Some("k1")).await;
+        assert_eq!(first, (StatusCode::CREATED, false, "created #1".to_string()));
+        let replay = send(&app, Method::POST, "/items", Some("k1")).await;
+        assert_eq!(replay, (StatusCode::CREATED, true, "created #1".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // New keys, missing keys and reads all execute.
+        assert_eq!(send(&app, Method::POST, "/items", Some("k2")).await.2, "created #2");
+        assert_eq!(send(&app, Method::POST, "/items", None).await.2, "created #3");
+        assert_eq!(send(&app, Method::GET, "/items", Some("k1")).await.2, "created #4");
+        assert_eq!(send(&app, Method::POST, "/items", Some(" ")).await.0, StatusCode::BAD_REQUEST);
+
+        // Server errors are not stored.
+        assert!(!send(&app, Method::POST, "/broken", Some("k1")).await.1);
+        assert!(!send(&app, Method::POST, "/broken", Some("k1")).await.1);
+    }
+
+    #[tokio::test]
+    async fn keys_expire_after_the_window() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(IdempotencyStore::new(Duration::from_millis(20)), calls.clone());
+
+        send(&app, Method::POST, "/items", Some("k1")).await;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        let again = send(&app, Method::POST, "/items", Some("k1")).await;
+        assert_eq!(again, (StatusCode::CREATED, false, "created #2".to_string()));
+    }
+
+    #[tokio::test]
+    async fn cancelled_requests_release_their_key() {
+        let app = app(IdempotencyStore::default(), Arc::new(AtomicUsize::new(0)));
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/slow")
+            .header(IDEMPOTENCY_KEY, "k1")
+            .body(Body::empty())
+            .unwrap();
+        // The client gives up before the handler finishes.
+        assert!(tokio::time::timeout(Duration::from_millis(10), app.clone().oneshot(request)).await.is_err());
+
+        let retry = send(&app, Method::POST, "/slow", Some("k1")).await;
+        assert_eq!((retry.0, retry.1), (StatusCode::CREATED, false));
+    }
+}
@@ -2,6 +2,7 @@
 
 pub mod api;
 pub mod auth;
+pub mod idempotency;
 pub mod router;
 pub mod server;
 
@@ -1,11 +1,18 @@
 //! Route definitions for web server.
 
+use std::time::Duration;
+
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
 
 use super::api;
+use super::auth::require_api_token;
+use super::idempotency::{idempotent, IdempotencyStore};
+use crate::config::load_settings;
+use crate::error::Result;
 
 /// Create the API router.
 pub fn create_api_router() -> Router {
@@ -23,12 +30,46 @@ pub fn create_api_router() -> Router {
         .route("/memory/:key", get(api::get_memory).delete(api::delete_memory))
         .route("/memory/search", get(api::search_memory))
         .route("/memory/stats", get(api::memory_stats))
+        
+        // Tasks
+        .route("/tasks", get(api::list_tasks).post(api::create_task))
+        .route("/tasks/:id", get(api::get_task).delete(api::delete_task))
+        .route("/tasks/:id/start", post(api::start_task))
+        
+        // Live events (SSE)
+        .route("/events", get(api::live_events))
+}
+
+/// Create the full app router, protecting `/api/*` with `web.api_token` if set.
+///
+/// Fails if settings cannot be loaded rather than serving without a token.
+pub fn create_app_router() -> Result<Router> {
+    let web = load_settings()?.web;
+    let token = web.api_token.filter(|t| !t.trim().is_empty());
+    if token.is_none() {
+        tracing::warn!(
+            "web.api_token is not set; /api routes are unauthenticated (run `tinyvegeta web --generate-token`)"
+        );
+    }
+    let idempotency = IdempotencyStore::new(Duration::from_secs(web.idempotency_window_secs));
+    Ok(create_app_router_with(token, idempotency))
+}
+
+/// Create the full app router with an explicit API token (`None` leaves `/api/*` open).
+#[cfg(test)]
+pub fn create_app_router_with_token(token: Option<String>) -> Router {
+    create_app_router_with(token, IdempotencyStore::default())
 }
 
-/// Create the full app router.
-pub fn create_app_router() -> Router {
+/// Create the full app router with an explicit API token and idempotency store.
+///
+/// Authentication runs before idempotency, so rejected requests never claim a key.
+pub fn create_app_router_with(token: Option<String>, idempotency: IdempotencyStore) -> Router {
+    let api = create_api_router()
+        .route_layer(middleware::from_fn_with_state(idempotency, idempotent))
+        .route_layer(middleware::from_fn_with_state(token, require_api_token));
     Router::new()
-        .nest("/api", create_api_router())
+        .nest("/api", api)
         .route("/health", get(health_check))
 }
 
@@ -1,9 +1,14 @@
 //! Web server using Axum.
 
+use std::future::Future;
 use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpSocket};
 use tower_http::cors::{Any, CorsLayer};
 
 use super::router::create_app_router;
+use crate::config::get_home_dir;
 
 /// Web server configuration.
 pub struct WebServerConfig {
@@ -20,26 +25,94 @@ impl Default for WebServerConfig {
     }
 }
 
-/// Run the web server.
-pub async fn run_server(config: WebServerConfig) -> Result<(), Box<dyn std::error::Error>> {
+/// Path of the PID file written while the web server runs.
+pub fn web_pid_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(get_home_dir()?.join("web.pid"))
+}
+
+/// Bind with `SO_REUSEADDR` so a restart right after a crash does not fail
+/// with "address already in use".
+fn bind_reuse(addr: SocketAddr) -> std::io::Result<TcpListener> {
+    let socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    socket.set_reuseaddr(true)?;
+    socket.bind(addr)?;
+    socket.listen(1024)
+}
+
+/// Resolve on Ctrl+C or SIGTERM.
+pub(crate) async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Web server shutting down");
+}
+
+/// Serve the app on `listener` until `shutdown` resolves, draining in-flight requests.
+pub async fn serve_until(
+    listener: TcpListener,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
     let app = create_app_router()
+        .map_err(std::io::Error::other)?
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
                 .allow_methods(Any)
                 .allow_headers(Any),
         );
-    
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown)
+        .await
+}
+
+/// Run the web server.
+pub async fn run_server(config: WebServerConfig) -> Result<(), Box<dyn std::error::Error>> {
     let addr: SocketAddr = format!("{}:{}", config.host, config.port)
         .parse()
         .map_err(|e| format!("Invalid address: {}", e))?;
-    
+
     tracing::info!("Starting web server on {}", addr);
-    
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    
-    axum::serve(listener, app).await?;
-    
+
+    let listener = bind_reuse(addr)?;
+
+    let pid_path = web_pid_path()?;
+    if let Some(parent) = pid_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let pid = std::process::id();
+    std::fs::write(&pid_path, pid.to_string())?;
+
+    let result = serve_until(listener, shutdown_signal()).await;
+
+    // Only remove the PID file if it is still ours.
+    if read_pid(&pid_path) == Some(pid) {
+        let _ = std::fs::remove_file(&pid_path);
+    }
+    result?;
+
     Ok(())
 }
 
@@ -49,6 +122,97 @@ pub async fn run_web_server(port: u16) -> Result<(), Box<dyn std::error::Error>>
         port,
         ..Default::default()
     };
-    
+
     run_server(config).await
 }
+
+fn read_pid(path: &std::path::Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn is_pid_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Stop a running web server via its PID file.
+///
+/// Sends SIGTERM, waits up to `wait` for exit, then removes the PID file.
+/// Returns the PID that was signalled, or `None` if no server was running.
+pub async fn stop_web_server(wait: Duration) -> Result<Option<u32>, Box<dyn std::error::Error>> {
+    let pid_path = web_pid_path()?;
+    let Some(pid) = read_pid(&pid_path) else {
+        let _ = std::fs::remove_file(&pid_path);
+        return Ok(None);
+    };
+    if !is_pid_alive(pid) {
+        std::fs::remove_file(&pid_path)?;
+        return Ok(None);
+    }
+
+    let status = std::process::Command::new("kill")
+        .arg("-TERM")
+        .arg(pid.to_string())
+        .status()?;
+    if !status.success() {
+        return Err(format!("Failed to signal web server (pid {})", pid).into());
+    }
+
+    let deadline = tokio::time::Instant::now() + wait;
+    while is_pid_alive(pid) {
+        if tokio::time::Instant::now() >= deadline {
+            return Err(format!("Web server (pid {}) did not exit within {:?}", pid, wait).into());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let _ = std::fs::remove_file(&pid_path);
+    Ok(Some(pid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn server_starts_on_ephemeral_port_and_stops_cleanly() {
+        let _home = crate::config::test_support::TempHome::new();
+        crate::config::save_settings(&crate::config::Settings::default()).unwrap();
+        let listener = bind_reuse("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve_until(listener, async {
+            let _ = stop_rx.await;
+        }));
+
+        let body = reqwest::get(format!("http://{}/health", addr))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "OK");
+
+        stop_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server did not shut down")
+            .unwrap()
+            .unwrap();
+
+        // The port is immediately reusable.
+        drop(bind_reuse(addr).unwrap());
+    }
+
+    #[tokio::test]
+    async fn server_refuses_to_start_without_settings() {
+        let _home = crate::config::test_support::TempHome::new();
+        let listener = bind_reuse("127.0.0.1:0".parse().unwrap()).unwrap();
+        let err = serve_until(listener, std::future::pending()).await.unwrap_err();
+        assert!(err.to_string().contains("Settings file not found"), "{}", err);
+    }
+}